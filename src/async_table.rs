@@ -0,0 +1,331 @@
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use interface::Entry;
+use interface::Key;
+use interface::QueryType;
+use interface::SharedTable;
+use interface::Table;
+use interface::Value;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::task;
+
+//The future every AsyncTable method gives back
+pub type TableFuture<T> = BoxFuture<'static, Result<T, String>>;
+
+/**
+ *  A table for async code, with the same operations as Table. Each one gives back a future for
+ *  the result instead of blocking the thread until it is done. The futures do not borrow the
+ *  table, so they can be handed to other tasks.
+*/
+pub trait AsyncTable<E: Entry + Send + 'static> {
+    /// The Key type for this database.
+    /// Must implement the Key trait
+    type Key: Key<E> + Send + 'static;
+
+    /// Insert an entry into the table. Gives back a key for the entry in the table.
+    fn insert(&self, entry: E) -> TableFuture<Self::Key>;
+
+    /// Find a key in the table. Gives back Some(Entry) if the entry for the key is in the table,
+    /// None otherwise.
+    fn lookup(&self, key: Self::Key) -> TableFuture<Option<E>>;
+
+    /// Search for entries in the table with a field matching a value. Gives back a vector of keys
+    /// and entries for the results.
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> TableFuture<Vec<(Self::Key, E)>>;
+
+    /// Update an entry at a given key with a new entry
+    fn update(&self, key: Self::Key, entry: E) -> TableFuture<()>;
+
+    /// Removes the entry for the given key in the table
+    fn remove(&self, key: Self::Key) -> TableFuture<()>;
+
+    /// Check whether a given key is in the table
+    fn contains(&self, key: Self::Key) -> TableFuture<bool>;
+
+    /// Same as Table::query
+    fn query(&self, q: QueryType<E>, key: Option<Self::Key>) -> TableFuture<Vec<(Self::Key, E)>>;
+}
+
+//Runs f with the table on the blocking thread pool of the tokio runtime. Has to be called from
+//inside a tokio runtime
+fn spawn_with<T, R, F>(mut table: T, f: F) -> TableFuture<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: FnOnce(&mut T) -> Result<R, String> + Send + 'static,
+{
+    task::spawn_blocking(move || f(&mut table))
+        .map(|joined| match joined {
+            Ok(result) => result,
+            Err(e) => Err(e.to_string()),
+        })
+        .boxed()
+}
+
+/**
+ *  Runs any Table as an AsyncTable. The table is kept behind a Mutex, and each call locks it on
+ *  the blocking thread pool of the tokio runtime, so the calls run one at a time.
+ *  The methods have to be called from inside a tokio runtime.
+*/
+pub struct BlockingTable<T> {
+    table: Arc<Mutex<T>>,
+}
+
+impl<T> BlockingTable<T> {
+    pub fn new(table: T) -> BlockingTable<T> {
+        BlockingTable {
+            table: Arc::new(Mutex::new(table)),
+        }
+    }
+
+    //Locks the table and runs f with it on the blocking thread pool
+    fn run<R, F>(&self, f: F) -> TableFuture<R>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: FnOnce(&mut T) -> Result<R, String> + Send + 'static,
+    {
+        spawn_with(self.table.clone(), move |table| {
+            let mut table = table.lock().map_err(|e| e.to_string())?;
+            f(&mut table)
+        })
+    }
+}
+
+impl<T> Clone for BlockingTable<T> {
+    fn clone(&self) -> BlockingTable<T> {
+        BlockingTable {
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<E, T> AsyncTable<E> for BlockingTable<T>
+where
+    E: Entry + Send + 'static,
+    E::FieldNames: Send + 'static,
+    T: Table<E> + Send + 'static,
+    T::Key: Send + 'static,
+{
+    type Key = T::Key;
+
+    fn insert(&self, entry: E) -> TableFuture<Self::Key> {
+        self.run(move |table| Ok(table.insert(entry)))
+    }
+
+    fn lookup(&self, key: Self::Key) -> TableFuture<Option<E>> {
+        self.run(move |table| Ok(table.lookup(key)))
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> TableFuture<Vec<(Self::Key, E)>> {
+        self.run(move |table| table.search(field_name, field_value))
+    }
+
+    fn update(&self, key: Self::Key, entry: E) -> TableFuture<()> {
+        self.run(move |table| table.update(key, entry))
+    }
+
+    fn remove(&self, key: Self::Key) -> TableFuture<()> {
+        self.run(move |table| table.remove(key))
+    }
+
+    fn contains(&self, key: Self::Key) -> TableFuture<bool> {
+        self.run(move |table| Ok(table.contains(key)))
+    }
+
+    fn query(&self, q: QueryType<E>, key: Option<Self::Key>) -> TableFuture<Vec<(Self::Key, E)>> {
+        self.run(move |table| table.query(q, key))
+    }
+}
+
+/**
+ *  Runs a SharedTable, like MysqlTable or PgTable, as an AsyncTable. Each call runs on the blocking
+ *  thread pool of the tokio runtime with the same table, which needs no Mutex since it can be
+ *  changed through a shared reference. The calls do not wait on each other.
+ *  The methods have to be called from inside a tokio runtime.
+*/
+pub struct SharedBlockingTable<T> {
+    table: Arc<T>,
+}
+
+impl<T> SharedBlockingTable<T> {
+    pub fn new(table: T) -> SharedBlockingTable<T> {
+        SharedBlockingTable {
+            table: Arc::new(table),
+        }
+    }
+}
+
+impl<T> Clone for SharedBlockingTable<T> {
+    fn clone(&self) -> SharedBlockingTable<T> {
+        SharedBlockingTable {
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<E, T> AsyncTable<E> for SharedBlockingTable<T>
+where
+    E: Entry + Send + 'static,
+    E::FieldNames: Send + 'static,
+    T: SharedTable<E> + 'static,
+    T::Key: 'static,
+{
+    type Key = T::Key;
+
+    fn insert(&self, entry: E) -> TableFuture<Self::Key> {
+        spawn_with(self.table.clone(), move |table| Ok(table.insert(entry)))
+    }
+
+    fn lookup(&self, key: Self::Key) -> TableFuture<Option<E>> {
+        spawn_with(self.table.clone(), move |table| Ok(table.lookup(key)))
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> TableFuture<Vec<(Self::Key, E)>> {
+        spawn_with(self.table.clone(), move |table| {
+            table.search(field_name, field_value)
+        })
+    }
+
+    fn update(&self, key: Self::Key, entry: E) -> TableFuture<()> {
+        spawn_with(self.table.clone(), move |table| table.update(key, entry))
+    }
+
+    fn remove(&self, key: Self::Key) -> TableFuture<()> {
+        spawn_with(self.table.clone(), move |table| table.remove(key))
+    }
+
+    fn contains(&self, key: Self::Key) -> TableFuture<bool> {
+        spawn_with(self.table.clone(), move |table| Ok(table.contains(key)))
+    }
+
+    fn query(&self, q: QueryType<E>, key: Option<Self::Key>) -> TableFuture<Vec<(Self::Key, E)>> {
+        spawn_with(self.table.clone(), move |table| table.query(q, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use async_table::AsyncTable;
+    use async_table::BlockingTable;
+    use async_table::SharedBlockingTable;
+    use interface::QueryType;
+    use interface::SortDirection;
+    use interface::Value;
+    use tests::Department;
+    use tests::DepartmentFields;
+    use tokio::runtime;
+    use vec_table::SharedVecTable;
+    use vec_table::VecTable;
+
+    #[test]
+    fn test_blocking_table() {
+        let runtime = runtime::Builder::new_current_thread().build().unwrap();
+        let _guard = runtime.enter();
+        let department_table = BlockingTable::new(VecTable::new());
+
+        let ece = Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        };
+        let me = Department {
+            name: "Mechanical Engineering".to_string(),
+            abreviation: "ME".to_string(),
+        };
+
+        //Both inserts are started before either is waited on
+        let ece_future = department_table.insert(ece.clone());
+        let me_future = department_table.insert(me);
+        let ece_key = runtime.block_on(ece_future).unwrap();
+        let me_key = runtime.block_on(me_future).unwrap();
+        assert_ne!(ece_key, me_key);
+
+        let found = runtime.block_on(department_table.lookup(ece_key)).unwrap();
+        assert_eq!(found.unwrap().abreviation, "ECE");
+        let found = runtime
+            .block_on(department_table.search(
+                DepartmentFields::Abreviation,
+                Value::String("ME".to_string()),
+            ))
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, me_key);
+
+        runtime
+            .block_on(department_table.update(
+                ece_key,
+                Department {
+                    abreviation: "ECEN".to_string(),
+                    ..ece
+                },
+            ))
+            .unwrap();
+        let all = runtime
+            .block_on(department_table.query(
+                QueryType::GetAll(10, DepartmentFields::Abreviation, SortDirection::Asc, 1),
+                None,
+            ))
+            .unwrap();
+        assert_eq!(all[0].1.abreviation, "ECEN");
+
+        runtime.block_on(department_table.remove(ece_key)).unwrap();
+        assert_eq!(
+            runtime.block_on(department_table.contains(ece_key)),
+            Ok(false)
+        );
+        assert!(runtime.block_on(department_table.remove(ece_key)).is_err());
+    }
+
+    #[test]
+    fn test_shared_blocking_table() {
+        let runtime = runtime::Builder::new_current_thread().build().unwrap();
+        let _guard = runtime.enter();
+        let department_table = SharedBlockingTable::new(SharedVecTable::new());
+
+        //Every insert is started before any is waited on, so they run at the same time
+        let futures: Vec<_> = (0..20)
+            .map(|i| {
+                department_table.insert(Department {
+                    name: "Department ".to_string() + &i.to_string(),
+                    abreviation: (i % 2).to_string(),
+                })
+            })
+            .collect();
+        let keys: Vec<_> = futures
+            .into_iter()
+            .map(|future| runtime.block_on(future).unwrap())
+            .collect();
+
+        let found = runtime
+            .block_on(department_table.search(
+                DepartmentFields::Abreviation,
+                Value::String("1".to_string()),
+            ))
+            .unwrap();
+        assert_eq!(found.len(), 10);
+        let found = runtime.block_on(department_table.lookup(keys[3])).unwrap();
+        assert_eq!(found.unwrap().name, "Department 3");
+
+        let copy = department_table.clone();
+        runtime.block_on(copy.remove(keys[3])).unwrap();
+        assert_eq!(
+            runtime.block_on(department_table.contains(keys[3])),
+            Ok(false)
+        );
+        assert!(runtime.block_on(department_table.remove(keys[3])).is_err());
+    }
+}
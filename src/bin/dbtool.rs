@@ -0,0 +1,546 @@
+extern crate database_lib;
+extern crate rpassword;
+
+use database_lib::dynamic_entry;
+use database_lib::dynamic_entry::DynamicEntry;
+use database_lib::dynamic_entry::DynamicFieldName;
+use database_lib::dynamic_entry::Schema;
+use database_lib::interface::Entry;
+use database_lib::interface::Table;
+use database_lib::interface::Value;
+use database_lib::log_table::LogTable;
+use database_lib::log_table::LogTableKey;
+use database_lib::my_types;
+use database_lib::my_types::MysqlTable;
+use database_lib::my_types::MysqlTableKey;
+use database_lib::transfer;
+use database_lib::transfer::CsvOptions;
+use database_lib::transfer::ImportReport;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::process;
+use std::str::FromStr;
+use std::sync::Arc;
+
+static USAGE: &str = "\
+Usage: dbtool (--file <path> | --mysql <config>) --schema <columns> [--json] <command>
+
+  --file <path>       Use the table in a file, made if it does not exist
+  --mysql <config>    Use a MySQL table. The config file has a line of name = value for each of
+                      user, database, table and key, and can have password and version
+  --schema <columns>  The columns of the table, as name:type,name:type. The types are integer,
+                      float, string and boolean
+  --json              Print entries as JSON instead of a text table
+
+The options can go anywhere on the line. Everything after -- is part of the command, even if it
+starts with --.
+
+Commands:
+  list                              Every entry
+  get <key>                         The entry for a key
+  search <field> <value>            The entries with a field equal to a value
+  insert <field>=<value>...         Insert an entry. Every field needs a value
+  update <key> <field>=<value>...   Change some fields of an entry
+  delete <key>                      Remove the entry for a key
+  export (jsonl|csv) [<path>]       Write every entry to a file, or to standard out
+  import (jsonl|csv) [<path>]       Insert the entries in a file, or from standard in";
+
+//Where the table is
+enum Backend {
+    File(String),
+    Mysql(String),
+}
+
+struct Options {
+    backend: Backend,
+    schema: Arc<Schema>,
+    json: bool,
+    command: Vec<String>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let result = parse_args(args).and_then(|options| open_and_run(&options, &mut io::stdout()));
+    if let Err(e) = result {
+        eprintln!("dbtool: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut backend = None;
+    let mut schema = None;
+    let mut json = false;
+    let mut args = args.into_iter();
+    let mut command = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            command.extend(args.by_ref());
+            break;
+        }
+        match arg.as_str() {
+            "--file" | "--mysql" | "--schema" => {
+                let value = match args.next() {
+                    Some(value) => value,
+                    None => return Err(format!("{} needs a value", arg)),
+                };
+                match arg.as_str() {
+                    "--file" => backend = Some(Backend::File(value)),
+                    "--mysql" => backend = Some(Backend::Mysql(value)),
+                    _ => schema = Some(value),
+                }
+            }
+            "--json" => json = true,
+            _ => command.push(arg),
+        }
+    }
+
+    let schema = match schema {
+        Some(schema) => Schema::new(dynamic_entry::parse_schema(&schema)?)?,
+        None => return Err("Need a --schema".to_string()),
+    };
+    match backend {
+        Some(backend) => Ok(Options {
+            backend,
+            schema,
+            json,
+            command,
+        }),
+        None => Err("Need a --file or --mysql table".to_string()),
+    }
+}
+
+//Opens the table with the schema in use, since a new LogTable file gets the field names of the
+//schema, and then runs the command on it
+fn open_and_run(options: &Options, out: &mut dyn Write) -> Result<(), String> {
+    let _schema = dynamic_entry::use_schema(options.schema.clone());
+    match options.backend {
+        Backend::File(ref path) => {
+            let mut table: LogTable<DynamicEntry> = LogTable::open(path)?;
+            run(&mut table, options, out)
+        }
+        Backend::Mysql(ref path) => {
+            let mut table = open_mysql_table(path)?;
+            run(&mut table, options, out)
+        }
+    }
+}
+
+//Reads a config file of name = value lines, and connects to the table it names
+fn open_mysql_table(path: &str) -> Result<MysqlTable<DynamicEntry>, String> {
+    let config = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut settings = Vec::new();
+    for line in config.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) => {
+                settings.push((name.trim().to_string(), value.trim().to_string()))
+            }
+            _ => return Err(format!("Bad line in {}: {}", path, line)),
+        }
+    }
+    let setting = |name: &str| {
+        settings
+            .iter()
+            .find(|(n, _value)| n == name)
+            .map(|(_n, value)| value.clone())
+    };
+    let required = |name: &str| setting(name).ok_or_else(|| format!("{} needs a {}", path, name));
+
+    let user = required("user")?;
+    let password = match setting("password") {
+        Some(password) => password,
+        None => {
+            eprint!("{}'s password: ", user);
+            rpassword::read_password().map_err(|e| e.to_string())?
+        }
+    };
+    let pool = my_types::open_mysql(user, password)?;
+    let mut table = MysqlTable::new(pool);
+    table.db_name = required("database")?;
+    table.tb_name = required("table")?;
+    table.key_name = required("key")?;
+    table.version_name = setting("version");
+    Ok(table)
+}
+
+//Reading and printing the keys of each kind of table
+trait ToolKey: Sized {
+    fn parse(text: &str) -> Result<Self, String>;
+    fn show(&self) -> String;
+}
+
+impl ToolKey for LogTableKey {
+    fn parse(text: &str) -> Result<Self, String> {
+        match text.parse() {
+            Ok(id) => Ok(LogTableKey { id, valid: true }),
+            Err(_) => Err(format!("Bad key {}", text)),
+        }
+    }
+
+    fn show(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+impl ToolKey for MysqlTableKey {
+    fn parse(text: &str) -> Result<Self, String> {
+        match text.parse() {
+            Ok(id) => Ok(MysqlTableKey { id, valid: true }),
+            Err(_) => Err(format!("Bad key {}", text)),
+        }
+    }
+
+    fn show(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+//Runs the command on the table, and writes what it prints to out
+fn run<T>(table: &mut T, options: &Options, out: &mut dyn Write) -> Result<(), String>
+where
+    T: Table<DynamicEntry>,
+    T::Key: ToolKey,
+{
+    let args: Vec<&str> = options.command.iter().map(|arg| arg.as_str()).collect();
+    match args.as_slice() {
+        ["list"] => {
+            let entries = table.iter().collect::<Result<Vec<_>, String>>()?;
+            print_entries(out, &entries, options.json)
+        }
+        ["get", key] => {
+            let key = T::Key::parse(key)?;
+            match table.lookup(key.clone()) {
+                Some(entry) => print_entries(out, &[(key, entry)], options.json),
+                None => Err("Key not found".to_string()),
+            }
+        }
+        ["search", field, value] => {
+            let field = DynamicFieldName::from_str(field)?;
+            let value = parse_value(field, value)?;
+            print_entries(out, &table.search(field, value)?, options.json)
+        }
+        ["insert", assignments @ ..] if !assignments.is_empty() => {
+            let assignments = parse_assignments(assignments)?;
+            let mut values = Vec::new();
+            for field in DynamicEntry::get_field_names() {
+                match assignments.iter().find(|(f, _value)| *f == field) {
+                    Some((_f, value)) => values.push(value.clone()),
+                    None => return Err(format!("Need a value for {}", field)),
+                }
+            }
+            let key = table.insert(DynamicEntry::from_fields(&values)?);
+            if !table.contains(key.clone()) {
+                return Err("Could not insert the entry".to_string());
+            }
+            writeln!(out, "{}", key.show()).map_err(|e| e.to_string())
+        }
+        ["update", key, assignments @ ..] if !assignments.is_empty() => {
+            let assignments = parse_assignments(assignments)?;
+            table.patch(T::Key::parse(key)?, assignments)
+        }
+        ["delete", key] => table.remove(T::Key::parse(key)?),
+        ["export", format] | ["export", format, _] => {
+            let writer: Box<dyn Write> = match args.get(2) {
+                Some(path) => Box::new(File::create(path).map_err(|e| format!("{}: {}", path, e))?),
+                None => Box::new(out),
+            };
+            let count = match *format {
+                "jsonl" => transfer::export_jsonl(&*table, writer)?,
+                "csv" => transfer::export_csv(&*table, writer, &CsvOptions::new())?,
+                _ => return Err(format!("Unknown format {}", format)),
+            };
+            eprintln!("Exported {} entries", count);
+            Ok(())
+        }
+        ["import", format] | ["import", format, _] => {
+            let reader: Box<dyn Read> = match args.get(2) {
+                Some(path) => Box::new(File::open(path).map_err(|e| format!("{}: {}", path, e))?),
+                None => Box::new(io::stdin()),
+            };
+            let report: ImportReport<T::Key> = match *format {
+                "jsonl" => transfer::import_jsonl(table, reader)?,
+                "csv" => transfer::import_csv(table, reader, &CsvOptions::new())?,
+                _ => return Err(format!("Unknown format {}", format)),
+            };
+            for (line, e) in &report.errors {
+                eprintln!("line {}: {}", line, e);
+            }
+            eprintln!("Imported {} entries", report.keys.len());
+            if report.errors.is_empty() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} entries could not be imported",
+                    report.errors.len()
+                ))
+            }
+        }
+        _ => Err("Bad command. See dbtool --help".to_string()),
+    }
+}
+
+fn parse_value(field: DynamicFieldName, text: &str) -> Result<Value, String> {
+    let value_type = field.value_type()?;
+    Value::from_text(text, value_type)
+        .ok_or_else(|| format!("Incorrect type for {}. Should be {:?}", field, value_type))
+}
+
+//Reads field=value arguments
+fn parse_assignments(args: &[&str]) -> Result<Vec<(DynamicFieldName, Value)>, String> {
+    let mut assignments = Vec::new();
+    for arg in args {
+        let mut parts = arg.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(field), Some(value)) => {
+                let field = DynamicFieldName::from_str(field)?;
+                assignments.push((field, parse_value(field, value)?));
+            }
+            _ => return Err(format!("Expected <field>=<value>, got {}", arg)),
+        }
+    }
+    Ok(assignments)
+}
+
+fn print_entries<K: ToolKey>(
+    out: &mut dyn Write,
+    entries: &[(K, DynamicEntry)],
+    json: bool,
+) -> Result<(), String> {
+    let mut lines = Vec::new();
+    if json {
+        for (key, entry) in entries {
+            lines.push(format!(
+                "{{\"key\":{},\"entry\":{}}}",
+                key.show(),
+                transfer::entry_to_json(entry)?
+            ));
+        }
+    } else {
+        let mut rows = vec![header_row()];
+        for (key, entry) in entries {
+            let mut row = vec![key.show()];
+            row.extend(entry.get_fields().iter().map(|value| value.to_string()));
+            rows.push(row);
+        }
+        lines = text_table(rows);
+    }
+    for line in lines {
+        writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+//The lines of a text table, with each column as wide as the widest thing in it. The first row is
+//the header, and gets a line of dashes under it
+fn text_table(mut rows: Vec<Vec<String>>) -> Vec<String> {
+    let mut widths = vec![0; rows[0].len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    rows.insert(1, separator);
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:1$}", cell, width))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+fn header_row() -> Vec<String> {
+    let mut header = vec!["key".to_string()];
+    header.extend(
+        DynamicEntry::get_field_names()
+            .iter()
+            .map(|field| field.to_string()),
+    );
+    header
+}
+
+#[cfg(test)]
+mod tests {
+
+    use database_lib::interface::ValueType;
+    use open_and_run;
+    use parse_args;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use text_table;
+    use Backend;
+    use Options;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("database_lib_{}_{}", name, process::id()))
+    }
+
+    //The columns of the tables in the tests
+    static SCHEMA: &str = "Name:string,Size:integer,Open:boolean";
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(args(
+            "--file rooms.log --schema Name:string,Size:integer,Open:boolean list --json",
+        ))
+        .unwrap();
+        assert!(options.json);
+        assert_eq!(options.command, vec!["list"]);
+        assert_eq!(
+            options.schema.columns()[1],
+            ("Size".to_string(), ValueType::Integer)
+        );
+        match options.backend {
+            Backend::File(path) => assert_eq!(path, "rooms.log"),
+            Backend::Mysql(_) => panic!("Expected a file table"),
+        }
+
+        let line = format!("search --schema {} Name --mysql my.conf -- --json", SCHEMA);
+        let options = parse_args(args(&line)).unwrap();
+        assert!(!options.json);
+        assert_eq!(options.command, vec!["search", "Name", "--json"]);
+        match options.backend {
+            Backend::Mysql(path) => assert_eq!(path, "my.conf"),
+            Backend::File(_) => panic!("Expected a MySQL table"),
+        }
+
+        assert_eq!(
+            parse_args(args("--file rooms.log list")).err(),
+            Some("Need a --schema".to_string())
+        );
+        let line = format!("list --schema {}", SCHEMA);
+        assert_eq!(
+            parse_args(args(&line)).err(),
+            Some("Need a --file or --mysql table".to_string())
+        );
+        let line = format!("list --schema {} --file", SCHEMA);
+        assert_eq!(
+            parse_args(args(&line)).err(),
+            Some("--file needs a value".to_string())
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let path = temp_path("dbtool_run");
+        let _ = fs::remove_file(&path);
+        //Each command opens the file again, like running dbtool again does
+        let command = |line: &str| -> Result<String, String> {
+            let line = format!("--file {} --schema {} {}", path.display(), SCHEMA, line);
+            let options: Options = parse_args(args(&line))?;
+            let mut out = Vec::new();
+            open_and_run(&options, &mut out)?;
+            Ok(String::from_utf8(out).unwrap())
+        };
+
+        assert_eq!(
+            command("insert Name=Lab Size=30 Open=true"),
+            Ok("0\n".to_string())
+        );
+        assert_eq!(
+            command("insert Open=false Size=4 Name=Office"),
+            Ok("1\n".to_string())
+        );
+        assert_eq!(
+            command("list"),
+            Ok("key  Name    Size  Open\n\
+                ---  ------  ----  -----\n\
+                0    Lab     30    true\n\
+                1    Office  4     false\n"
+                .to_string())
+        );
+        assert_eq!(
+            command("get --json 1"),
+            Ok(
+                "{\"key\":1,\"entry\":{\"Name\":\"Office\",\"Size\":4,\"Open\":false}}\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(command("update 1 Size=6"), Ok(String::new()));
+        assert_eq!(
+            command("search Size 6 --json"),
+            Ok(
+                "{\"key\":1,\"entry\":{\"Name\":\"Office\",\"Size\":6,\"Open\":false}}\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(command("delete 0"), Ok(String::new()));
+        assert_eq!(
+            command("list"),
+            Ok("key  Name    Size  Open\n\
+                ---  ------  ----  -----\n\
+                1    Office  6     false\n"
+                .to_string())
+        );
+
+        assert_eq!(command("get 0"), Err("Key not found".to_string()));
+        assert_eq!(
+            command("insert Name=Hall Size=200"),
+            Err("Need a value for Open".to_string())
+        );
+        assert_eq!(
+            command("search Size big"),
+            Err("Incorrect type for Size. Should be Integer".to_string())
+        );
+        assert_eq!(
+            command("list 1"),
+            Err("Bad command. See dbtool --help".to_string())
+        );
+
+        //The file has the columns of the schema it was made with
+        assert_eq!(
+            command("list --schema Name:string"),
+            Err(
+                "The file has fields [\"Name\", \"Size\", \"Open\"], but the table has fields \
+                 [\"Name\"]"
+                    .to_string()
+            )
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_text_table() {
+        let rows = vec![
+            vec!["key".to_string(), "Name".to_string()],
+            vec!["10".to_string(), "Lab".to_string()],
+            vec!["2".to_string(), "Électricité".to_string()],
+        ];
+        assert_eq!(
+            text_table(rows),
+            vec![
+                "key  Name",
+                "---  -----------",
+                "10   Lab",
+                "2    Électricité",
+            ]
+        );
+        assert_eq!(
+            text_table(vec![vec!["key".to_string()]]),
+            vec!["key", "---"]
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//!
+//! The parts of SQL that each database writes differently. The statements in sql_builder are
+//! built against a Dialect, so a new SQL backend only needs a Dialect to reuse them.
+//!
+
+use interface;
+use interface::ValueType;
+
+/**
+ *  How a database hands back the key it picked for a row that was just inserted.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertedKey {
+    //INSERT ... RETURNING key, the key comes back as a row from the insert itself
+    Returning,
+    //Run this query on the same connection right after the insert
+    Query(&'static str),
+}
+
+/**
+ *  SQL syntax for one kind of database.
+*/
+pub trait Dialect {
+    /// Quotes a table or column name so it can never be read as a keyword or break out of the name
+    fn quote_identifier(&self, name: &str) -> String;
+
+    /// The placeholder for the nth parameter of a statement, starting at 1
+    fn placeholder(&self, n: usize) -> String;
+
+    /// The clause that goes at the end of a SELECT to limit it, starting with a space. Gives an
+    /// empty string when there is neither a limit or an offset.
+    fn limit_offset(&self, limit: Option<u64>, offset: Option<u64>) -> String;
+
+    /// A condition matching a column against a LIKE pattern, ignoring case. The pattern is
+    /// escaped with escape_like.
+    fn like(&self, column: &str, placeholder: &str) -> String;
+
+    /// How to get the key of a row that was just inserted
+    fn inserted_key(&self) -> InsertedKey;
+
+    /// The column type to store a kind of Value in
+    fn type_name(&self, value_type: ValueType) -> &'static str;
+
+    /// The column type and options for a key that the database picks on insert
+    fn key_type_name(&self) -> &'static str;
+
+    /// The clause that goes after the VALUES of an INSERT, starting with a space, so a row that
+    /// clashes with an existing row on match_columns updates the update_columns of that row
+    /// instead. The columns need a UNIQUE index for the database to notice the clash. The key of
+    /// the existing row is still found the way inserted_key says.
+    fn on_conflict_update(
+        &self,
+        match_columns: &[String],
+        update_columns: &[String],
+        key_column: &str,
+    ) -> String;
+
+    /// Escapes the wildcards in a string so LIKE matches it literally. Add % around the result to
+    /// search for it anywhere in a column.
+    fn escape_like(&self, text: &str) -> String {
+        interface::escape_like(text)
+    }
+}
+
+/**
+ *  MySQL and MariaDB
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MySql;
+
+impl Dialect for MySql {
+    fn quote_identifier(&self, name: &str) -> String {
+        "`".to_string() + &name.replace('`', "``") + "`"
+    }
+
+    fn placeholder(&self, _n: usize) -> String {
+        "?".to_string()
+    }
+
+    fn limit_offset(&self, limit: Option<u64>, offset: Option<u64>) -> String {
+        //LIMIT offset, count. There is no OFFSET without a LIMIT, so use the largest limit it allows
+        match (limit, offset) {
+            (Some(limit), Some(offset)) => {
+                " LIMIT ".to_string() + &offset.to_string() + ", " + &limit.to_string()
+            }
+            (Some(limit), None) => " LIMIT ".to_string() + &limit.to_string(),
+            (None, Some(offset)) => {
+                " LIMIT ".to_string() + &offset.to_string() + ", 18446744073709551615"
+            }
+            (None, None) => "".to_string(),
+        }
+    }
+
+    fn like(&self, column: &str, placeholder: &str) -> String {
+        //LIKE already ignores case with the default collations, and \ is the default escape
+        column.to_string() + " LIKE " + placeholder
+    }
+
+    fn inserted_key(&self) -> InsertedKey {
+        InsertedKey::Query("SELECT LAST_INSERT_ID()")
+    }
+
+    fn type_name(&self, value_type: ValueType) -> &'static str {
+        match value_type {
+            ValueType::Integer => "INT",
+            ValueType::Float => "FLOAT",
+            ValueType::String => "VARCHAR(255)",
+            ValueType::Boolean => "BOOLEAN",
+        }
+    }
+
+    fn key_type_name(&self) -> &'static str {
+        "INT NOT NULL AUTO_INCREMENT PRIMARY KEY"
+    }
+
+    fn on_conflict_update(
+        &self,
+        _match_columns: &[String],
+        update_columns: &[String],
+        key_column: &str,
+    ) -> String {
+        //MySQL uses whichever UNIQUE index clashed, so the match columns are not named.
+        //Setting the key to LAST_INSERT_ID(key) makes LAST_INSERT_ID give the updated row's key
+        let key = self.quote_identifier(key_column);
+        let mut set_vec = vec![key.clone() + " = LAST_INSERT_ID(" + &key + ")"];
+        for column in update_columns {
+            let column = self.quote_identifier(column);
+            set_vec.push(column.clone() + " = VALUES(" + &column + ")");
+        }
+        " ON DUPLICATE KEY UPDATE ".to_string() + &set_vec.join(", ")
+    }
+}
+
+/**
+ *  SQLite 3
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    fn quote_identifier(&self, name: &str) -> String {
+        "\"".to_string() + &name.replace('"', "\"\"") + "\""
+    }
+
+    fn placeholder(&self, n: usize) -> String {
+        "?".to_string() + &n.to_string()
+    }
+
+    fn limit_offset(&self, limit: Option<u64>, offset: Option<u64>) -> String {
+        //A negative limit means no limit, which is the only way to have an OFFSET without one
+        let mut clause = match limit {
+            Some(limit) => " LIMIT ".to_string() + &limit.to_string(),
+            None if offset.is_some() => " LIMIT -1".to_string(),
+            None => "".to_string(),
+        };
+        if let Some(offset) = offset {
+            clause = clause + " OFFSET " + &offset.to_string();
+        }
+        clause
+    }
+
+    fn like(&self, column: &str, placeholder: &str) -> String {
+        //LIKE ignores case for ASCII, but has no escape character unless one is given
+        column.to_string() + " LIKE " + placeholder + " ESCAPE '\\'"
+    }
+
+    fn inserted_key(&self) -> InsertedKey {
+        InsertedKey::Query("SELECT last_insert_rowid()")
+    }
+
+    fn type_name(&self, value_type: ValueType) -> &'static str {
+        match value_type {
+            ValueType::Integer => "INTEGER",
+            ValueType::Float => "REAL",
+            ValueType::String => "TEXT",
+            ValueType::Boolean => "INTEGER",
+        }
+    }
+
+    fn key_type_name(&self) -> &'static str {
+        "INTEGER PRIMARY KEY AUTOINCREMENT"
+    }
+
+    fn on_conflict_update(
+        &self,
+        match_columns: &[String],
+        update_columns: &[String],
+        _key_column: &str,
+    ) -> String {
+        on_conflict_do_update(self, match_columns, update_columns)
+    }
+}
+
+/**
+ *  PostgreSQL
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Postgres;
+
+impl Dialect for Postgres {
+    fn quote_identifier(&self, name: &str) -> String {
+        "\"".to_string() + &name.replace('"', "\"\"") + "\""
+    }
+
+    fn placeholder(&self, n: usize) -> String {
+        "$".to_string() + &n.to_string()
+    }
+
+    fn limit_offset(&self, limit: Option<u64>, offset: Option<u64>) -> String {
+        let mut clause = match limit {
+            Some(limit) => " LIMIT ".to_string() + &limit.to_string(),
+            None => "".to_string(),
+        };
+        if let Some(offset) = offset {
+            clause = clause + " OFFSET " + &offset.to_string();
+        }
+        clause
+    }
+
+    fn like(&self, column: &str, placeholder: &str) -> String {
+        //LIKE is case sensitive in postgres, ILIKE matches the other databases. ILIKE only takes
+        //text, so other columns are cast the way MySQL converts them for LIKE
+        column.to_string() + "::text ILIKE " + placeholder
+    }
+
+    fn inserted_key(&self) -> InsertedKey {
+        InsertedKey::Returning
+    }
+
+    fn type_name(&self, value_type: ValueType) -> &'static str {
+        match value_type {
+            ValueType::Integer => "INTEGER",
+            ValueType::Float => "REAL",
+            ValueType::String => "TEXT",
+            ValueType::Boolean => "BOOLEAN",
+        }
+    }
+
+    fn key_type_name(&self) -> &'static str {
+        "INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY"
+    }
+
+    fn on_conflict_update(
+        &self,
+        match_columns: &[String],
+        update_columns: &[String],
+        _key_column: &str,
+    ) -> String {
+        on_conflict_do_update(self, match_columns, update_columns)
+    }
+}
+
+//ON CONFLICT (match columns) DO UPDATE SET column = EXCLUDED.column, for SQLite and PostgreSQL
+fn on_conflict_do_update(
+    dialect: &dyn Dialect,
+    match_columns: &[String],
+    update_columns: &[String],
+) -> String {
+    let match_vec: Vec<String> = match_columns
+        .iter()
+        .map(|c| dialect.quote_identifier(c))
+        .collect();
+    //The row has to be updated to come back from RETURNING, so set the match columns when there
+    //is nothing else to set
+    let update_columns = if update_columns.is_empty() {
+        match_columns
+    } else {
+        update_columns
+    };
+    let set_vec: Vec<String> = update_columns
+        .iter()
+        .map(|c| {
+            let column = dialect.quote_identifier(c);
+            column.clone() + " = EXCLUDED." + &column
+        })
+        .collect();
+    " ON CONFLICT (".to_string() + &match_vec.join(", ") + ") DO UPDATE SET " + &set_vec.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use dialect::Dialect;
+    use dialect::InsertedKey;
+    use dialect::MySql;
+    use dialect::Postgres;
+    use dialect::Sqlite;
+    use interface::ValueType;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(MySql.quote_identifier("order"), "`order`");
+        assert_eq!(MySql.quote_identifier("bad`name"), "`bad``name`");
+        assert_eq!(Sqlite.quote_identifier("First Name"), "\"First Name\"");
+        assert_eq!(Postgres.quote_identifier("bad\"name"), "\"bad\"\"name\"");
+    }
+
+    #[test]
+    fn test_placeholder() {
+        assert_eq!(MySql.placeholder(3), "?");
+        assert_eq!(Sqlite.placeholder(3), "?3");
+        assert_eq!(Postgres.placeholder(3), "$3");
+    }
+
+    #[test]
+    fn test_limit_offset() {
+        assert_eq!(MySql.limit_offset(Some(10), Some(20)), " LIMIT 20, 10");
+        assert_eq!(MySql.limit_offset(Some(10), None), " LIMIT 10");
+        assert_eq!(
+            MySql.limit_offset(None, Some(20)),
+            " LIMIT 20, 18446744073709551615"
+        );
+        assert_eq!(Sqlite.limit_offset(None, Some(20)), " LIMIT -1 OFFSET 20");
+        assert_eq!(
+            Postgres.limit_offset(Some(10), Some(20)),
+            " LIMIT 10 OFFSET 20"
+        );
+        assert_eq!(Postgres.limit_offset(None, Some(20)), " OFFSET 20");
+        assert_eq!(Postgres.limit_offset(None, None), "");
+    }
+
+    #[test]
+    fn test_like() {
+        assert_eq!(MySql.like("`email`", "?"), "`email` LIKE ?");
+        assert_eq!(
+            Sqlite.like("\"email\"", "?1"),
+            "\"email\" LIKE ?1 ESCAPE '\\'"
+        );
+        assert_eq!(Postgres.like("\"email\"", "$1"), "\"email\"::text ILIKE $1");
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(MySql.escape_like("100%_sure\\"), "100\\%\\_sure\\\\");
+        assert_eq!(Postgres.escape_like("O'Brien"), "O'Brien");
+    }
+
+    #[test]
+    fn test_inserted_key() {
+        assert_eq!(
+            MySql.inserted_key(),
+            InsertedKey::Query("SELECT LAST_INSERT_ID()")
+        );
+        assert_eq!(
+            Sqlite.inserted_key(),
+            InsertedKey::Query("SELECT last_insert_rowid()")
+        );
+        assert_eq!(Postgres.inserted_key(), InsertedKey::Returning);
+    }
+
+    #[test]
+    fn test_on_conflict_update() {
+        let match_columns = vec!["bannerID".to_string()];
+        let update_columns = vec!["firstname".to_string(), "bannerID".to_string()];
+
+        assert_eq!(
+            MySql.on_conflict_update(&match_columns, &update_columns, "userID"),
+            " ON DUPLICATE KEY UPDATE `userID` = LAST_INSERT_ID(`userID`), \
+             `firstname` = VALUES(`firstname`), `bannerID` = VALUES(`bannerID`)"
+        );
+        assert_eq!(
+            Postgres.on_conflict_update(&match_columns, &update_columns, "userID"),
+            " ON CONFLICT (\"bannerID\") DO UPDATE SET \"firstname\" = EXCLUDED.\"firstname\", \
+             \"bannerID\" = EXCLUDED.\"bannerID\""
+        );
+        assert_eq!(
+            Sqlite.on_conflict_update(&match_columns, &[], "userID"),
+            " ON CONFLICT (\"bannerID\") DO UPDATE SET \"bannerID\" = EXCLUDED.\"bannerID\""
+        );
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(MySql.type_name(ValueType::String), "VARCHAR(255)");
+        assert_eq!(Sqlite.type_name(ValueType::Boolean), "INTEGER");
+        assert_eq!(Postgres.type_name(ValueType::Float), "REAL");
+    }
+}
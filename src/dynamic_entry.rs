@@ -0,0 +1,526 @@
+use interface::Entry;
+use interface::FieldName;
+use interface::Value;
+use interface::ValueType;
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::Arc;
+
+//Entries whose columns are only known when the program runs, for admin tools and importers that
+//work on any table. Each entry keeps the Schema it was made for, so its columns can be read by
+//name anywhere. Entry also makes entries and lists the fields without an entry to ask, so those
+//use the schema set on the thread with use_schema. Each thread can use a different schema, and a
+//thread can change the schema it uses to work on another table.
+
+thread_local! {
+    //The schema entries and field names use on this thread
+    static CURRENT_SCHEMA: RefCell<Option<Arc<Schema>>> = const { RefCell::new(None) };
+}
+
+/**
+ *  The name and type of each column of a table, in order
+*/
+#[derive(Debug, PartialEq)]
+pub struct Schema {
+    columns: Vec<(String, ValueType)>,
+}
+
+impl Schema {
+    /**
+     *  Makes a schema from the name and type of each column. The names cannot be empty or be
+     *  used twice.
+     */
+    pub fn new(columns: Vec<(String, ValueType)>) -> Result<Arc<Schema>, String> {
+        for (i, (name, _value_type)) in columns.iter().enumerate() {
+            if name.is_empty() || columns[..i].iter().any(|(n, _value_type)| n == name) {
+                return Err(format!("Bad column {:?} in the schema", name));
+            }
+        }
+        Ok(Arc::new(Schema { columns }))
+    }
+
+    /// The name and type of each column, in order
+    pub fn columns(&self) -> &[(String, ValueType)] {
+        &self.columns
+    }
+
+    //Where a column is, and its type
+    fn find(&self, name: &str) -> Option<(usize, ValueType)> {
+        self.columns
+            .iter()
+            .position(|(n, _value_type)| n == name)
+            .map(|index| (index, self.columns[index].1))
+    }
+}
+
+/// Makes DynamicEntry use a schema on this thread, until the guard it gives back is dropped.
+/// Then the schema used before is used again.
+pub fn use_schema(schema: Arc<Schema>) -> SchemaGuard {
+    let previous = CURRENT_SCHEMA.with(|current| current.replace(Some(schema)));
+    SchemaGuard {
+        previous,
+        phantom: PhantomData,
+    }
+}
+
+/// The schema DynamicEntry uses on this thread, if use_schema set one
+pub fn current_schema() -> Option<Arc<Schema>> {
+    CURRENT_SCHEMA.with(|current| current.borrow().clone())
+}
+
+fn schema() -> Result<Arc<Schema>, String> {
+    current_schema().ok_or_else(|| "No DynamicEntry schema is in use on this thread".to_string())
+}
+
+/**
+ *  Puts the schema that was used before use_schema back when it is dropped. It stays on the
+ *  thread that made it, since the schema is only used on that thread.
+*/
+pub struct SchemaGuard {
+    previous: Option<Arc<Schema>>,
+    phantom: PhantomData<*const ()>,
+}
+
+impl Drop for SchemaGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_SCHEMA.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Reads a schema written as name:type,name:type. The types are integer, float, string and
+/// boolean.
+pub fn parse_schema(text: &str) -> Result<Vec<(String, ValueType)>, String> {
+    let mut columns: Vec<(String, ValueType)> = Vec::new();
+    for column in text.split(',') {
+        let mut parts = column.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let value_type = match parts.next().map(|t| t.trim().to_lowercase()) {
+            Some(ref t) if t == "integer" => ValueType::Integer,
+            Some(ref t) if t == "float" => ValueType::Float,
+            Some(ref t) if t == "string" => ValueType::String,
+            Some(ref t) if t == "boolean" => ValueType::Boolean,
+            _ => return Err(format!("Bad column {:?} in the schema", column)),
+        };
+        if name.is_empty() || columns.iter().any(|(n, _t)| n == name) {
+            return Err(format!("Bad column {:?} in the schema", column));
+        }
+        columns.push((name.to_string(), value_type));
+    }
+    Ok(columns)
+}
+
+/**
+ *  A column of the schema in use on the thread, by where it is in the schema
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynamicFieldName(usize);
+
+impl FieldName for DynamicFieldName {}
+
+impl fmt::Display for DynamicFieldName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match current_schema()
+            .as_ref()
+            .and_then(|schema| schema.columns.get(self.0))
+        {
+            Some((name, _value_type)) => write!(f, "{}", name),
+            None => write!(f, "#{}", self.0),
+        }
+    }
+}
+
+impl FromStr for DynamicFieldName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match schema()?.find(s) {
+            Some((index, _value_type)) => Ok(DynamicFieldName(index)),
+            None => Err("Field does not exist".to_string()),
+        }
+    }
+}
+
+impl DynamicFieldName {
+    /// Where the column is in the schema
+    pub fn index(&self) -> usize {
+        self.0
+    }
+
+    /// The type of the column, or an Err if the schema in use does not have it
+    pub fn value_type(&self) -> Result<ValueType, String> {
+        match schema()?.columns.get(self.0) {
+            Some((_name, value_type)) => Ok(*value_type),
+            None => Err(format!("Field #{} is not in the schema", self.0)),
+        }
+    }
+}
+
+/**
+ *  An ordered map of column name to value, with the columns of a schema in the same order. The
+ *  entry keeps its schema, so the columns can be read by name on any thread.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicEntry {
+    schema: Arc<Schema>,
+    values: Vec<Value>,
+}
+
+impl DynamicEntry {
+    /**
+     *  Makes an entry with a value for each column of a schema, in order
+     */
+    pub fn new(schema: Arc<Schema>, values: Vec<Value>) -> Result<DynamicEntry, String> {
+        if values.len() != schema.columns.len() {
+            return Err(format!(
+                "Expected {} fields, got {}",
+                schema.columns.len(),
+                values.len()
+            ));
+        }
+        for ((name, value_type), value) in schema.columns.iter().zip(&values) {
+            if value.get_type() != *value_type {
+                return Err(format!(
+                    "Incorrect type for {}. Should be {:?}",
+                    name, value_type
+                ));
+            }
+        }
+        Ok(DynamicEntry { schema, values })
+    }
+
+    /**
+     *  Makes an entry for the schema in use from column names and values, in any order. Every
+     *  column in the schema has to be given once.
+     */
+    pub fn from_columns(columns: Vec<(String, Value)>) -> Result<DynamicEntry, String> {
+        let schema = schema()?;
+        let mut values: Vec<Option<Value>> = vec![None; schema.columns.len()];
+        for (name, value) in columns {
+            let slot = match schema
+                .find(&name)
+                .and_then(|(index, _t)| values.get_mut(index))
+            {
+                Some(slot) => slot,
+                None => return Err(format!("Unknown column {}", name)),
+            };
+            if slot.is_some() {
+                return Err(format!("Column {} given twice", name));
+            }
+            *slot = Some(value);
+        }
+        let mut fields = Vec::new();
+        for ((name, _value_type), value) in schema.columns.iter().zip(values) {
+            match value {
+                Some(value) => fields.push(value),
+                None => return Err(format!("Missing column {}", name)),
+            }
+        }
+        DynamicEntry::new(schema, fields)
+    }
+
+    /**
+     *  The schema the entry was made for
+     */
+    pub fn schema(&self) -> &Arc<Schema> {
+        &self.schema
+    }
+
+    /**
+     *  The column names and values of the entry, in the order of its schema
+     */
+    pub fn columns(&self) -> Vec<(String, Value)> {
+        self.schema
+            .columns
+            .iter()
+            .map(|(name, _value_type)| name.clone())
+            .zip(self.values.iter().cloned())
+            .collect()
+    }
+
+    /**
+     *  The value of a column, or None if there is no column with that name
+     */
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        let (index, _value_type) = self.schema.find(column)?;
+        self.values.get(index)
+    }
+
+    /**
+     *  Changes the value of a column. The value has to be the type of the column.
+     */
+    pub fn set(&mut self, column: &str, value: Value) -> Result<(), String> {
+        let (index, value_type) = match self.schema.find(column) {
+            Some(found) => found,
+            None => return Err("Field does not exist".to_string()),
+        };
+        if value.get_type() != value_type {
+            return Err(format!(
+                "Incorrect type for {}. Should be {:?}",
+                column, value_type
+            ));
+        }
+        match self.values.get_mut(index) {
+            Some(old) => {
+                *old = value;
+                Ok(())
+            }
+            None => Err(format!("The entry has no value for {}", column)),
+        }
+    }
+}
+
+impl Entry for DynamicEntry {
+    type FieldNames = DynamicFieldName;
+
+    fn from_fields(values: &[Value]) -> Result<Self, String> {
+        DynamicEntry::new(schema()?, values.to_vec())
+    }
+
+    //Without a schema in use there are no fields
+    fn get_field_names() -> Vec<Self::FieldNames> {
+        match current_schema() {
+            Some(schema) => (0..schema.columns.len()).map(DynamicFieldName).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_field_types() -> Vec<ValueType> {
+        match current_schema() {
+            Some(schema) => schema
+                .columns
+                .iter()
+                .map(|(_name, value_type)| *value_type)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_fields(&self) -> Vec<Value> {
+        self.values.clone()
+    }
+
+    fn get_field(&self, field_name: DynamicFieldName) -> Option<Value> {
+        self.values.get(field_name.0).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use dynamic_entry::current_schema;
+    use dynamic_entry::parse_schema;
+    use dynamic_entry::use_schema;
+    use dynamic_entry::DynamicEntry;
+    use dynamic_entry::DynamicFieldName;
+    use dynamic_entry::Schema;
+    use dynamic_entry::SchemaGuard;
+    use interface::Entry;
+    use interface::Filter;
+    use interface::QueryType;
+    use interface::SortDirection;
+    use interface::Table;
+    use interface::Value;
+    use interface::ValueType;
+    use std::str::FromStr;
+    use std::thread;
+    use vec_table::VecTable;
+
+    fn use_test_schema() -> SchemaGuard {
+        use_schema(
+            Schema::new(parse_schema("Name:string,Size:integer,Open:boolean").unwrap()).unwrap(),
+        )
+    }
+
+    fn room(name: &str, size: i32, open: bool) -> DynamicEntry {
+        DynamicEntry::from_columns(vec![
+            ("Open".to_string(), Value::Boolean(open)),
+            ("Name".to_string(), Value::String(name.to_string())),
+            ("Size".to_string(), Value::Integer(size)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_schema() {
+        assert_eq!(
+            parse_schema("Name:string, Size : Integer,Open:boolean"),
+            Ok(vec![
+                ("Name".to_string(), ValueType::String),
+                ("Size".to_string(), ValueType::Integer),
+                ("Open".to_string(), ValueType::Boolean),
+            ])
+        );
+        assert!(parse_schema("Name").is_err());
+        assert!(parse_schema("Name:text").is_err());
+        assert!(parse_schema(":string").is_err());
+        assert!(parse_schema("Name:string,Name:integer").is_err());
+    }
+
+    #[test]
+    fn test_dynamic_entry() {
+        let _schema = use_test_schema();
+
+        let mut entry = room("Lab", 30, true);
+        assert_eq!(
+            entry.get_fields(),
+            vec![
+                Value::String("Lab".to_string()),
+                Value::Integer(30),
+                Value::Boolean(true),
+            ]
+        );
+        assert_eq!(entry.get("Size"), Some(&Value::Integer(30)));
+        assert_eq!(entry.get("Color"), None);
+        assert_eq!(entry.columns()[0].0, "Name");
+
+        assert!(entry.set("Size", Value::Integer(40)).is_ok());
+        assert_eq!(entry.get("Size"), Some(&Value::Integer(40)));
+        assert_eq!(
+            entry.set("Size", Value::String("big".to_string())),
+            Err("Incorrect type for Size. Should be Integer".to_string())
+        );
+        assert_eq!(
+            entry.set("Color", Value::Integer(1)),
+            Err("Field does not exist".to_string())
+        );
+
+        assert_eq!(
+            DynamicEntry::from_columns(vec![("Name".to_string(), Value::Integer(1))]),
+            Err("Missing column Size".to_string())
+        );
+        assert_eq!(
+            DynamicEntry::from_columns(vec![("Color".to_string(), Value::Integer(1))]),
+            Err("Unknown column Color".to_string())
+        );
+        assert!(DynamicEntry::from_fields(&[Value::Integer(1)]).is_err());
+
+        let size = DynamicFieldName::from_str("Size").unwrap();
+        assert_eq!(size.index(), 1);
+        assert_eq!(size.value_type(), Ok(ValueType::Integer));
+        assert_eq!(size.to_string(), "Size");
+        assert!(DynamicFieldName::from_str("Color").is_err());
+    }
+
+    #[test]
+    fn test_dynamic_vectable() {
+        let _schema = use_test_schema();
+        let name = DynamicFieldName::from_str("Name").unwrap();
+        let size = DynamicFieldName::from_str("Size").unwrap();
+        let open = DynamicFieldName::from_str("Open").unwrap();
+
+        let mut table: VecTable<DynamicEntry> = VecTable::new();
+        let lab = table.insert(room("Lab", 30, true));
+        table.insert(room("Office", 4, false));
+        table.insert(room("Hall", 200, true));
+
+        let found = table.search(open, Value::Boolean(true)).unwrap();
+        assert_eq!(found.len(), 2);
+
+        let sorted = table
+            .query(QueryType::GetAll(10, size, SortDirection::Desc, 1), None)
+            .unwrap();
+        let names: Vec<Value> = sorted
+            .iter()
+            .map(|(_key, entry)| entry.get_field(name).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                Value::String("Hall".to_string()),
+                Value::String("Lab".to_string()),
+                Value::String("Office".to_string()),
+            ]
+        );
+
+        let changed = table
+            .update_where(
+                Filter::LessThan(size, Value::Integer(50)),
+                vec![(open, Value::Boolean(false))],
+            )
+            .unwrap();
+        assert_eq!(changed, 2);
+        assert_eq!(
+            table.lookup(lab).unwrap().get("Open"),
+            Some(&Value::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn test_value_type() {
+        let _schema = use_test_schema();
+        let open = DynamicFieldName::from_str("Open").unwrap();
+        assert_eq!(open.value_type(), Ok(ValueType::Boolean));
+        assert_eq!(
+            DynamicFieldName(3).value_type(),
+            Err("Field #3 is not in the schema".to_string())
+        );
+        assert_eq!(DynamicFieldName(3).to_string(), "#3");
+
+        let mut entry = room("Lab", 30, true);
+        entry.values.pop();
+        assert_eq!(
+            entry.set("Open", Value::Boolean(false)),
+            Err("The entry has no value for Open".to_string())
+        );
+
+        //Another thread has no schema until it sets one
+        let no_schema = "No DynamicEntry schema is in use on this thread".to_string();
+        thread::spawn(move || {
+            assert_eq!(open.value_type(), Err(no_schema.clone()));
+            assert_eq!(DynamicFieldName::from_str("Open"), Err(no_schema.clone()));
+            assert_eq!(DynamicEntry::from_fields(&[]), Err(no_schema));
+            assert!(DynamicEntry::get_field_names().is_empty());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_dynamic_schemas() {
+        let rooms = use_test_schema();
+        let lab = room("Lab", 30, true);
+        let size = DynamicFieldName::from_str("Size").unwrap();
+
+        let people = Schema::new(vec![
+            ("Size".to_string(), ValueType::Float),
+            ("Name".to_string(), ValueType::String),
+        ])
+        .unwrap();
+        let guard = use_schema(people.clone());
+        assert_eq!(size.value_type(), Ok(ValueType::String));
+        assert_eq!(
+            DynamicEntry::get_field_types(),
+            vec![ValueType::Float, ValueType::String]
+        );
+        let ada = DynamicEntry::from_columns(vec![
+            ("Name".to_string(), Value::String("Ada".to_string())),
+            ("Size".to_string(), Value::Float(1.7)),
+        ])
+        .unwrap();
+        assert_eq!(ada.schema(), &people);
+
+        //An entry keeps the names of its own columns
+        assert_eq!(lab.get("Size"), Some(&Value::Integer(30)));
+        assert_eq!(lab.columns()[2], ("Open".to_string(), Value::Boolean(true)));
+        thread::spawn(move || assert_eq!(ada.get("Name"), Some(&Value::String("Ada".to_string()))))
+            .join()
+            .unwrap();
+
+        //Dropping the guard puts the schema before it back
+        drop(guard);
+        assert_eq!(size.value_type(), Ok(ValueType::Integer));
+        drop(rooms);
+        assert_eq!(current_schema(), None);
+
+        assert_eq!(
+            Schema::new(vec![
+                ("Name".to_string(), ValueType::String),
+                ("Name".to_string(), ValueType::Integer),
+            ]),
+            Err("Bad column \"Name\" in the schema".to_string())
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use interface::Entry;
+use interface::Value;
+
+//Reading and writing the pieces of the files tables keep on disk. Numbers are little endian,
+//and strings are their length as a u32 and then their UTF-8 bytes.
+
+//The tag in front of each Value, for which kind it is
+const INTEGER: u8 = 0;
+const FLOAT: u8 = 1;
+const STRING: u8 = 2;
+const BOOLEAN: u8 = 3;
+
+pub fn write_u32(bytes: &mut Vec<u8>, n: u32) {
+    bytes.extend_from_slice(&n.to_le_bytes());
+}
+
+pub fn write_u64(bytes: &mut Vec<u8>, n: u64) {
+    bytes.extend_from_slice(&n.to_le_bytes());
+}
+
+pub fn write_str(bytes: &mut Vec<u8>, s: &str) {
+    write_u32(bytes, s.len() as u32);
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+pub fn write_value(bytes: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Integer(i) => {
+            bytes.push(INTEGER);
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::Float(f) => {
+            bytes.push(FLOAT);
+            bytes.extend_from_slice(&f.to_bits().to_le_bytes());
+        }
+        Value::String(s) => {
+            bytes.push(STRING);
+            write_str(bytes, s);
+        }
+        Value::Boolean(b) => {
+            bytes.push(BOOLEAN);
+            bytes.push(*b as u8);
+        }
+    }
+}
+
+//The names of the fields of E, so a file can only be read back as the same kind of entry
+pub fn write_field_names<E: Entry>(bytes: &mut Vec<u8>) {
+    let field_names = E::get_field_names();
+    write_u32(bytes, field_names.len() as u32);
+    for field in field_names {
+        write_str(bytes, &field.to_string());
+    }
+}
+
+//The CRC-32 (IEEE) checksum of some bytes, to tell when they were not all written
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/**
+ *  Goes through bytes written by the write functions, in the same order they were written
+*/
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    //How many bytes have been read
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    //The bytes that have not been read yet
+    pub fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    //Whether everything has been read
+    pub fn is_done(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.position < len {
+            return Err("Unexpected end of file".to_string());
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        let mut n = [0; 4];
+        n.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_le_bytes(n))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, String> {
+        let mut n = [0; 8];
+        n.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(n))
+    }
+
+    pub fn read_string(&mut self) -> Result<String, String> {
+        let len = self.read_u32()? as usize;
+        String::from_utf8(self.read_bytes(len)?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    //Checks that the field names from write_field_names are the ones E has
+    pub fn read_field_names<E: Entry>(&mut self) -> Result<(), String> {
+        let field_count = self.read_u32()?;
+        let mut file_fields = Vec::new();
+        for _ in 0..field_count {
+            file_fields.push(self.read_string()?);
+        }
+        let table_fields: Vec<String> = E::get_field_names()
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        if file_fields != table_fields {
+            return Err(format!(
+                "The file has fields {:?}, but the table has fields {:?}",
+                file_fields, table_fields
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn read_value(&mut self) -> Result<Value, String> {
+        match self.read_u8()? {
+            INTEGER => Ok(Value::Integer(self.read_u32()? as i32)),
+            FLOAT => Ok(Value::Float(f32::from_bits(self.read_u32()?))),
+            STRING => Ok(Value::String(self.read_string()?)),
+            BOOLEAN => match self.read_u8()? {
+                0 => Ok(Value::Boolean(false)),
+                1 => Ok(Value::Boolean(true)),
+                b => Err(format!("Bad boolean {}", b)),
+            },
+            tag => Err(format!("Bad value tag {}", tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use file_format::crc32;
+    use file_format::write_str;
+    use file_format::write_u64;
+    use file_format::write_value;
+    use file_format::Reader;
+    use interface::Value;
+
+    #[test]
+    fn test_round_trip() {
+        let values = vec![
+            Value::Integer(-7),
+            Value::Float(2.5),
+            Value::Float(-0.0),
+            Value::String("Électrique".to_string()),
+            Value::String(String::new()),
+            Value::Boolean(true),
+        ];
+        let mut bytes = Vec::new();
+        write_u64(&mut bytes, 1 << 40);
+        write_str(&mut bytes, "name");
+        for value in &values {
+            write_value(&mut bytes, value);
+        }
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.read_u64(), Ok(1 << 40));
+        assert_eq!(reader.read_string(), Ok("name".to_string()));
+        for value in &values {
+            assert_eq!(reader.read_value().as_ref(), Ok(value));
+        }
+        assert!(reader.is_done());
+        assert_eq!(reader.read_u8(), Err("Unexpected end of file".to_string()));
+
+        let mut reader = Reader::new(&bytes[..bytes.len() - 1]);
+        reader.read_u64().unwrap();
+        reader.read_string().unwrap();
+        for _ in 0..values.len() - 1 {
+            reader.read_value().unwrap();
+        }
+        assert!(reader.read_value().is_err());
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
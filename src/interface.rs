@@ -1,4 +1,9 @@
+use sql_builder::MAX_LIMIT;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::str::FromStr;
 
 /**
@@ -9,8 +14,12 @@ use std::str::FromStr;
  *  ensure that a key cannot be used for any entry other than the one it was created for.
  *
  *  The Downcast trait allows us to turn a Box<dyn Key> into the concrete type that it came from
+ *
+ *  Keys have to be Clone because the default methods of Table use a key more than once, like
+ *  patch looking an entry up and then updating it, and upsert updating an entry and then
+ *  returning its key. Every key so far is a small Copy struct, so this costs nothing.
 */
-pub trait Key<E: Entry>: Debug + PartialEq {}
+pub trait Key<E: Entry>: Debug + PartialEq + Clone {}
 pub trait ITryInto<T> {
     fn itry_into(self) -> Result<T, String>;
 }
@@ -23,13 +32,48 @@ pub enum Value {
     Boolean(bool),
 }
 
-impl ToString for Value {
-    fn to_string(&self) -> String {
+//The kinds of Value, without any data
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum ValueType {
+    Integer,
+    Float,
+    String,
+    Boolean,
+}
+
+impl Value {
+    pub fn get_type(&self) -> ValueType {
+        match self {
+            Value::Integer(_) => ValueType::Integer,
+            Value::Float(_) => ValueType::Float,
+            Value::String(_) => ValueType::String,
+            Value::Boolean(_) => ValueType::Boolean,
+        }
+    }
+
+    /// Reads a value of a type from text, the way Display writes it. Booleans can also be 1 or 0.
+    /// Gives back None if the text is not that type.
+    pub fn from_text(text: &str, value_type: ValueType) -> Option<Value> {
+        match value_type {
+            ValueType::Integer => text.trim().parse().ok().map(Value::Integer),
+            ValueType::Float => text.trim().parse().ok().map(Value::Float),
+            ValueType::String => Some(Value::String(text.to_string())),
+            ValueType::Boolean => match text.trim().to_lowercase().as_str() {
+                "true" | "1" => Some(Value::Boolean(true)),
+                "false" | "0" => Some(Value::Boolean(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Value::Integer(temp) => temp.to_string(),
-            Value::Float(temp) => temp.to_string(),
-            Value::String(temp) => temp.clone(),
-            Value::Boolean(temp) => temp.to_string(),
+            Value::Integer(temp) => write!(f, "{}", temp),
+            Value::Float(temp) => write!(f, "{}", temp),
+            Value::String(temp) => write!(f, "{}", temp),
+            Value::Boolean(temp) => write!(f, "{}", temp),
         }
     }
 }
@@ -70,58 +114,6 @@ impl ITryInto<bool> for Value {
     }
 }
 
-mod value_tests {
-
-    use interface::ITryInto;
-    use interface::Value;
-
-    #[test]
-    fn value_string_to_string() {
-        assert_eq!(
-            Value::String("hello".to_string()).to_string(),
-            "hello".to_string()
-        );
-    }
-
-    #[test]
-    fn value_integer_to_string() {
-        assert_eq!(Value::Integer(42).to_string(), "42".to_string());
-    }
-
-    #[test]
-    fn value_float_to_string() {
-        assert_eq!(Value::Float(42.612).to_string(), "42.612".to_string());
-    }
-
-    #[test]
-    fn value_bool_to_string() {
-        assert_eq!(Value::Boolean(true).to_string(), "true".to_string());
-    }
-
-    #[test]
-    fn value_into_i32() {
-        assert_eq!(Value::Integer(42).itry_into(), Ok(42));
-    }
-
-    #[test]
-    fn value_into_f32() {
-        assert_eq!(Value::Float(42.0).itry_into(), Ok(42.0));
-    }
-
-    #[test]
-    fn value_into_string() {
-        assert_eq!(
-            Value::String("hello".to_string()).itry_into(),
-            Ok("hello".to_string())
-        );
-    }
-
-    #[test]
-    fn value_into_bool() {
-        assert_eq!(Value::Boolean(true).itry_into(), Ok(true));
-    }
-}
-
 //Enum for query
 //Shows what type of query along with the data needed for it
 //The data does not include the key becuase it needs Self
@@ -150,7 +142,16 @@ pub enum QueryType<E: Entry> {
         u16,
     ),
 }
+
+//Whether an upsert made a new entry or changed one that was already in the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upserted {
+    Inserted,
+    Updated,
+}
+
 //This enum is to determine direction in QueryType
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SortDirection {
     Asc,  //Ascending
     Desc, //Descending
@@ -166,10 +167,438 @@ pub trait Entry: Clone {
 
     fn from_fields(values: &[Value]) -> Result<Self, String>;
     fn get_field_names() -> Vec<Self::FieldNames>;
+    /// The type of each field, in the same order as get_field_names. There is no entry to look at
+    /// the values of, so it has no default and every Entry has to list them. It is used to check
+    /// values before they are written, by patch, update_where and the imports in transfer.
+    fn get_field_types() -> Vec<ValueType>;
     fn get_fields(&self) -> Vec<Value>;
     fn get_field(&self, field_name: Self::FieldNames) -> Option<Value>;
 }
 
+/// Checks that each value has the type of the field it is going into. Returns an Err naming the
+/// first field that does not match.
+pub fn check_field_types<E: Entry>(assignments: &[(E::FieldNames, Value)]) -> Result<(), String> {
+    let field_names = E::get_field_names();
+    let field_types = E::get_field_types();
+    for (field, value) in assignments {
+        let field_type = match field_names.iter().position(|f| f == field) {
+            Some(index) => field_types[index],
+            None => return Err("Bad field".to_string()),
+        };
+        if value.get_type() != field_type {
+            return Err(format!(
+                "Incorrect type for {}. Should be {:?}",
+                field.to_string(),
+                field_type
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Sorts entries by a field and gives back one page of them, the way QueryType::GetAll and
+/// QueryType::Search do. For tables that have their entries in memory. Entries with the same value
+/// stay in the order they were given in. The limit and page work the same as in Select::page.
+pub fn sort_page<K, E: Entry>(
+    mut entries: Vec<(K, &E)>,
+    limit: u16,
+    sort_field: E::FieldNames,
+    sort_dir: SortDirection,
+    page: u16,
+) -> Result<Vec<(K, E)>, String> {
+    // sort, skip lim*(pg-1), take lim, with lim at most MAX_LIMIT and page 0 the same as page 1
+    let limit = limit.min(MAX_LIMIT);
+    let start = u64::from(limit) * u64::from(page.saturating_sub(1));
+
+    let field_index = match E::get_field_names().iter().position(|f| *f == sort_field) {
+        Some(field_index) => field_index,
+        None => return Err("Bad field".to_string()),
+    };
+
+    entries.sort_by(|(_, a), (_, b)| {
+        let ordering = a.get_fields()[field_index]
+            .partial_cmp(&b.get_fields()[field_index])
+            .unwrap_or(Ordering::Equal);
+        match sort_dir {
+            SortDirection::Asc => ordering,
+            SortDirection::Desc => ordering.reverse(),
+        }
+    });
+
+    Ok(entries
+        .into_iter()
+        .skip(start as usize)
+        .take(usize::from(limit))
+        .map(|(key, entry)| (key, entry.clone()))
+        .collect())
+}
+
+/**
+ *  A condition on the fields of an entry, to pick which entries an operation works on.
+ *  Comparisons between values of different types never match.
+*/
+#[derive(Clone)]
+pub enum Filter<E: Entry> {
+    //Matches every entry
+    All,
+    //field = value
+    Equals(E::FieldNames, Value),
+    //field != value
+    NotEquals(E::FieldNames, Value),
+    //field < value
+    LessThan(E::FieldNames, Value),
+    //field <= value
+    LessOrEqual(E::FieldNames, Value),
+    //field > value
+    GreaterThan(E::FieldNames, Value),
+    //field >= value
+    GreaterOrEqual(E::FieldNames, Value),
+    //field LIKE pattern, ignoring case. % matches any run of characters, _ matches any one
+    //character and \ makes the character after it match literally
+    Like(E::FieldNames, String),
+    //Every filter matches. Matches everything when there are none
+    And(Vec<Filter<E>>),
+    //At least one filter matches. Matches nothing when there are none
+    Or(Vec<Filter<E>>),
+    //The filter does not match
+    Not(Box<Filter<E>>),
+}
+
+impl<E: Entry> Filter<E> {
+    /// Matches the entries with the text anywhere in a field, ignoring case, the way
+    /// QueryType::PartialSearch searches. Wildcards in the text are matched literally.
+    pub fn contains(field: E::FieldNames, text: &str) -> Filter<E> {
+        Filter::Like(field, "%".to_string() + &escape_like(text) + "%")
+    }
+
+    /// Checks the filter against an entry, the same way a database would
+    pub fn matches(&self, entry: &E) -> bool {
+        //Compares a field to a value, if they have the same type
+        let compare = |field: &E::FieldNames, value: &Value| match entry.get_field(*field) {
+            Some(ref found) if found.get_type() == value.get_type() => found.partial_cmp(value),
+            _ => None,
+        };
+        match self {
+            Filter::All => true,
+            Filter::Equals(field, value) => compare(field, value) == Some(Ordering::Equal),
+            Filter::NotEquals(field, value) => match compare(field, value) {
+                Some(ordering) => ordering != Ordering::Equal,
+                None => false,
+            },
+            Filter::LessThan(field, value) => compare(field, value) == Some(Ordering::Less),
+            Filter::LessOrEqual(field, value) => match compare(field, value) {
+                Some(ordering) => ordering != Ordering::Greater,
+                None => false,
+            },
+            Filter::GreaterThan(field, value) => compare(field, value) == Some(Ordering::Greater),
+            Filter::GreaterOrEqual(field, value) => match compare(field, value) {
+                Some(ordering) => ordering != Ordering::Less,
+                None => false,
+            },
+            Filter::Like(field, pattern) => match entry.get_field(*field) {
+                Some(Value::String(text)) => like_matches(&text, pattern),
+                _ => false,
+            },
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(entry)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(entry)),
+            Filter::Not(filter) => !filter.matches(entry),
+        }
+    }
+}
+
+//One piece of a LIKE pattern
+#[derive(PartialEq)]
+enum LikeToken {
+    AnyRun,
+    AnyOne,
+    Literal(char),
+}
+
+//Matches text against a LIKE pattern, ignoring case
+fn like_matches(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut tokens = Vec::new();
+    let mut pattern_chars = pattern
+        .to_lowercase()
+        .chars()
+        .collect::<Vec<char>>()
+        .into_iter();
+    while let Some(c) = pattern_chars.next() {
+        tokens.push(match c {
+            '%' => LikeToken::AnyRun,
+            '_' => LikeToken::AnyOne,
+            '\\' => LikeToken::Literal(pattern_chars.next().unwrap_or('\\')),
+            c => LikeToken::Literal(c),
+        });
+    }
+
+    //Walks both at once, and when something does not match goes back to the last % and lets it
+    //take one more character
+    let (mut t, mut p) = (0, 0);
+    let mut last_run: Option<(usize, usize)> = None;
+    while t < text.len() {
+        let token_matches = match tokens.get(p) {
+            Some(LikeToken::AnyOne) => true,
+            Some(LikeToken::Literal(c)) => *c == text[t],
+            _ => false,
+        };
+        if token_matches {
+            t += 1;
+            p += 1;
+        } else if tokens.get(p) == Some(&LikeToken::AnyRun) {
+            last_run = Some((p, t));
+            p += 1;
+        } else if let Some((run_p, run_t)) = last_run {
+            p = run_p + 1;
+            t = run_t + 1;
+            last_run = Some((run_p, run_t + 1));
+        } else {
+            return false;
+        }
+    }
+    tokens[p..].iter().all(|token| *token == LikeToken::AnyRun)
+}
+
+/// Escapes the wildcards in a string so a LIKE pattern matches it literally. Add % around the
+/// result to search for it anywhere in a field.
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if c == '\\' || c == '%' || c == '_' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/**
+ *  A value to work out for each group of entries in Table::aggregate
+*/
+#[derive(Clone)]
+pub enum Aggregate<E: Entry> {
+    //How many entries there are, as an Integer
+    Count,
+    //The total of an Integer or Float field, as the same type
+    Sum(E::FieldNames),
+    //The smallest value of a field
+    Min(E::FieldNames),
+    //The largest value of a field
+    Max(E::FieldNames),
+    //The mean of an Integer or Float field, as a Float
+    Avg(E::FieldNames),
+}
+
+impl<E: Entry> Aggregate<E> {
+    /// The field the aggregate is worked out from, if it uses one
+    pub fn field(&self) -> Option<E::FieldNames> {
+        match self {
+            Aggregate::Count => None,
+            Aggregate::Sum(field)
+            | Aggregate::Min(field)
+            | Aggregate::Max(field)
+            | Aggregate::Avg(field) => Some(*field),
+        }
+    }
+
+    /// The type of the value the aggregate gives. Returns an Err for the sum or average of a field
+    /// that is not a number.
+    pub fn value_type(&self) -> Result<ValueType, String> {
+        let number_type = |field: &E::FieldNames, what: &str| match field_type::<E>(*field)? {
+            ValueType::Integer => Ok(ValueType::Integer),
+            ValueType::Float => Ok(ValueType::Float),
+            _ => Err(format!(
+                "Cannot {} {}, it is not a number",
+                what,
+                field.to_string()
+            )),
+        };
+        match self {
+            Aggregate::Count => Ok(ValueType::Integer),
+            Aggregate::Sum(field) => number_type(field, "sum"),
+            Aggregate::Min(field) | Aggregate::Max(field) => field_type::<E>(*field),
+            Aggregate::Avg(field) => number_type(field, "average").map(|_| ValueType::Float),
+        }
+    }
+}
+
+//One group from Table::aggregate, the values of the group_by fields and then of each aggregate
+pub type AggregateRow = (Vec<Value>, Vec<Value>);
+
+/// The type of one field of E
+pub fn field_type<E: Entry>(field: E::FieldNames) -> Result<ValueType, String> {
+    match E::get_field_names().iter().position(|f| *f == field) {
+        Some(index) => Ok(E::get_field_types()[index]),
+        None => Err("Bad field".to_string()),
+    }
+}
+
+//The result of one aggregate so far, for one group
+enum Running {
+    Count,
+    IntegerSum(i64),
+    FloatSum(f64),
+    Min(Option<Value>),
+    Max(Option<Value>),
+    Avg(f64),
+}
+
+impl Running {
+    fn new<E: Entry>(aggregate: &Aggregate<E>, value_type: ValueType) -> Running {
+        match (aggregate, value_type) {
+            (Aggregate::Count, _) => Running::Count,
+            (Aggregate::Sum(_), ValueType::Integer) => Running::IntegerSum(0),
+            (Aggregate::Sum(_), _) => Running::FloatSum(0.0),
+            (Aggregate::Min(_), _) => Running::Min(None),
+            (Aggregate::Max(_), _) => Running::Max(None),
+            (Aggregate::Avg(_), _) => Running::Avg(0.0),
+        }
+    }
+
+    //Adds the value of the field from one more entry
+    fn add(&mut self, value: Option<Value>) -> Result<(), String> {
+        match (self, value) {
+            (Running::Count, _) => {}
+            (Running::IntegerSum(sum), Some(Value::Integer(i))) => *sum += i64::from(i),
+            (Running::FloatSum(sum), Some(Value::Float(f))) => *sum += f64::from(f),
+            (Running::Avg(sum), Some(Value::Integer(i))) => *sum += f64::from(i),
+            (Running::Avg(sum), Some(Value::Float(f))) => *sum += f64::from(f),
+            (Running::Min(min), Some(value)) => {
+                if min.as_ref().is_none_or(|min| value < *min) {
+                    *min = Some(value);
+                }
+            }
+            (Running::Max(max), Some(value)) => {
+                if max.as_ref().is_none_or(|max| value > *max) {
+                    *max = Some(value);
+                }
+            }
+            _ => return Err("An entry has the wrong type for an aggregate".to_string()),
+        }
+        Ok(())
+    }
+
+    //The value of the aggregate for a group with count entries in it
+    fn finish(self, count: u64) -> Result<Value, String> {
+        match self {
+            Running::Count if count <= i32::MAX as u64 => Ok(Value::Integer(count as i32)),
+            Running::Count => Err("Too many entries to count".to_string()),
+            Running::IntegerSum(sum)
+                if sum >= i64::from(i32::MIN) && sum <= i64::from(i32::MAX) =>
+            {
+                Ok(Value::Integer(sum as i32))
+            }
+            Running::IntegerSum(_) => Err("The sum is too big for an Integer".to_string()),
+            Running::FloatSum(sum) => Ok(Value::Float(sum as f32)),
+            Running::Min(Some(value)) | Running::Max(Some(value)) => Ok(value),
+            Running::Min(None) | Running::Max(None) => Err("The group is empty".to_string()),
+            Running::Avg(sum) => Ok(Value::Float((sum / count as f64) as f32)),
+        }
+    }
+}
+
+/// Works out aggregates the way Table::aggregate does, for tables that go through their entries
+/// in memory.
+pub fn aggregate_entries<K, E, I>(
+    aggregates: &[Aggregate<E>],
+    group_by: &[E::FieldNames],
+    entries: I,
+) -> Result<Vec<AggregateRow>, String>
+where
+    E: Entry,
+    I: Iterator<Item = Result<(K, E), String>>,
+{
+    let mut value_types = Vec::new();
+    for aggregate in aggregates {
+        value_types.push(aggregate.value_type()?);
+    }
+    for field in group_by {
+        field_type::<E>(*field)?;
+    }
+
+    //Each group's values, how many entries it has and its aggregates so far
+    let mut groups: Vec<(Vec<Value>, u64, Vec<Running>)> = Vec::new();
+    //Where each group is in groups, by the Debug text of its values, since Value can not be hashed
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for result in entries {
+        let (_key, entry) = result?;
+        let group: Vec<Value> = match group_by.iter().map(|f| entry.get_field(*f)).collect() {
+            Some(group) => group,
+            None => return Err("Bad field".to_string()),
+        };
+        let position = *positions.entry(format!("{:?}", group)).or_insert_with(|| {
+            let running = aggregates
+                .iter()
+                .zip(&value_types)
+                .map(|(aggregate, value_type)| Running::new(aggregate, *value_type))
+                .collect();
+            groups.push((group, 0, running));
+            groups.len() - 1
+        });
+        let (_group, count, running) = &mut groups[position];
+        *count += 1;
+        for (aggregate, running) in aggregates.iter().zip(running.iter_mut()) {
+            running.add(aggregate.field().and_then(|field| entry.get_field(field)))?;
+        }
+    }
+
+    groups.sort_by(|(a, _, _), (b, _, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mut rows = Vec::new();
+    for (group, count, running) in groups {
+        let mut values = Vec::new();
+        for running in running {
+            values.push(running.finish(count)?);
+        }
+        rows.push((group, values));
+    }
+    Ok(rows)
+}
+
+/**
+ *  Says that a field of E holds the key of an entry in a table of F, the way a foreign key does.
+ *  The field is an Integer with the id of the key in it. Tables with a join use it to find the
+ *  entry each entry refers to.
+*/
+#[derive(Clone)]
+pub struct Reference<E: Entry, F: Entry> {
+    field: E::FieldNames,
+    phantom: PhantomData<fn() -> F>,
+}
+
+impl<E: Entry, F: Entry> Reference<E, F> {
+    /// Returns an Err if the field is not an Integer, since it could not hold a key
+    pub fn new(field: E::FieldNames) -> Result<Reference<E, F>, String> {
+        match field_type::<E>(field)? {
+            ValueType::Integer => Ok(Reference {
+                field,
+                phantom: PhantomData,
+            }),
+            _ => Err(format!(
+                "{} can not hold a key, it is not an Integer",
+                field.to_string()
+            )),
+        }
+    }
+
+    pub fn field(&self) -> E::FieldNames {
+        self.field
+    }
+
+    /// The id of the key the entry refers to
+    pub fn id(&self, entry: &E) -> Option<i32> {
+        match entry.get_field(self.field) {
+            Some(Value::Integer(id)) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+//The keys and field values Table::query_fields gives back
+pub type FieldRows<K> = Vec<(K, Vec<Value>)>;
+
+//The iterator Table::scan gives back. A row that can not be read comes out as an Err
+pub type Scan<'a, K, E> = Box<dyn Iterator<Item = Result<(K, E), String>> + 'a>;
+
 /**
  * A table in a database that can store entries.
 */
@@ -210,4 +639,521 @@ pub trait Table<E: Entry> {
     /// This allows for easy addition of more query types
     fn query(&self, q: QueryType<E>, key: Option<Self::Key>)
         -> Result<Vec<(Self::Key, E)>, String>;
+
+    /// The same as query, but only gives back the values of the given fields of each entry, in the
+    /// order they are given in. Tables in a database only read those fields.
+    fn query_fields(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+        fields: &[E::FieldNames],
+    ) -> Result<FieldRows<Self::Key>, String> {
+        for field in fields {
+            field_type::<E>(*field)?;
+        }
+        let entries = self.query(q, key)?;
+        Ok(entries
+            .into_iter()
+            .map(|(key, entry)| {
+                let values = fields.iter().filter_map(|f| entry.get_field(*f)).collect();
+                (key, values)
+            })
+            .collect())
+    }
+
+    /// Go through every entry that matches the filter, without a page limit. The entries are read
+    /// as the iterator is used instead of all at once, so it works for tables of any size.
+    fn scan<'a>(&'a self, filter: Filter<E>) -> Scan<'a, Self::Key, E>;
+
+    /// Go through every entry in the table, see scan
+    fn iter<'a>(&'a self) -> Scan<'a, Self::Key, E> {
+        self.scan(Filter::All)
+    }
+
+    /// Change the given fields of every entry that matches the filter. Returns how many entries
+    /// were changed.
+    fn update_where(
+        &mut self,
+        filter: Filter<E>,
+        assignments: Vec<(E::FieldNames, Value)>,
+    ) -> Result<u64, String>;
+
+    /// Change only the given fields of the entry at a key, leaving the rest as they are. Returns an
+    /// Err without changing anything if a value is the wrong type for its field.
+    /// The default looks up the entry and updates all of it, so another change to the table can
+    /// get in between. Tables should override it to only write the given fields.
+    fn patch(
+        &mut self,
+        key: Self::Key,
+        assignments: Vec<(E::FieldNames, Value)>,
+    ) -> Result<(), String> {
+        check_field_types::<E>(&assignments)?;
+        let entry = match self.lookup(key.clone()) {
+            Some(entry) => entry,
+            None => return Err("Key not in table".to_string()),
+        };
+        let field_names = E::get_field_names();
+        let mut fields = entry.get_fields();
+        for (field, value) in assignments {
+            if let Some(index) = field_names.iter().position(|f| *f == field) {
+                fields[index] = value;
+            }
+        }
+        self.update(key, E::from_fields(&fields)?)
+    }
+
+    /// Remove every entry that matches the filter. Returns how many entries were removed.
+    fn remove_where(&mut self, filter: Filter<E>) -> Result<u64, String>;
+
+    /// Insert many entries at once. Returns the keys in the same order as the entries.
+    /// Tables should override this when they can do better than one insert at a time.
+    /// If it fails partway, the entries before the failure can be left in the table without
+    /// their keys being given back. Run it in Transactional::transaction to insert all or none.
+    fn insert_many(&mut self, entries: Vec<E>) -> Result<Vec<Self::Key>, String> {
+        Ok(entries
+            .into_iter()
+            .map(|entry| self.insert(entry))
+            .collect())
+    }
+
+    /// Update many entries at once. Stops at the first update that fails, so some of the
+    /// updates may have already happened when this returns an Err. Run it in a transaction
+    /// to keep all of them or none of them.
+    fn update_many(&mut self, updates: Vec<(Self::Key, E)>) -> Result<(), String> {
+        for (key, entry) in updates {
+            self.update(key, entry)?;
+        }
+        Ok(())
+    }
+
+    /// Insert an entry, or update the entry that has the same values for all of match_fields if
+    /// there is one. Returns the key of the entry and which one happened. Returns an Err if more
+    /// than one entry matches.
+    /// The default searches and then inserts or updates, so another change to the table can get
+    /// in between. Tables should override it with something atomic.
+    fn upsert(
+        &mut self,
+        match_fields: &[E::FieldNames],
+        entry: E,
+    ) -> Result<(Self::Key, Upserted), String> {
+        let (first_field, other_fields) = match match_fields.split_first() {
+            Some(split) => split,
+            None => return Err("Need at least one field to match on".to_string()),
+        };
+        let first_value = match entry.get_field(*first_field) {
+            Some(value) => value,
+            None => return Err("Bad field".to_string()),
+        };
+        let mut found = self.search(*first_field, first_value)?;
+        found.retain(|(_key, found_entry)| {
+            other_fields
+                .iter()
+                .all(|field| found_entry.get_field(*field) == entry.get_field(*field))
+        });
+        match found.pop() {
+            None => Ok((self.insert(entry), Upserted::Inserted)),
+            Some((key, _old_entry)) => {
+                if !found.is_empty() {
+                    return Err("More than one entry matches".to_string());
+                }
+                self.update(key.clone(), entry)?;
+                Ok((key, Upserted::Updated))
+            }
+        }
+    }
+
+    /// Remove many entries at once. Returns an Err if any of the keys are not in the table,
+    /// in which case some of the entries may have already been removed.
+    fn remove_many(&mut self, keys: Vec<Self::Key>) -> Result<(), String> {
+        for key in keys {
+            self.remove(key)?;
+        }
+        Ok(())
+    }
+
+    /// Work out aggregates over the entries that match the filter. The entries are split into
+    /// groups that have the same values for the group_by fields, and each group gives back those
+    /// values along with the value of each aggregate, sorted by the group values. With no group_by
+    /// fields every entry is in one group. Groups with no entries are left out, so nothing
+    /// matching the filter gives no groups at all.
+    fn aggregate(
+        &self,
+        aggregates: &[Aggregate<E>],
+        group_by: &[E::FieldNames],
+        filter: Filter<E>,
+    ) -> Result<Vec<AggregateRow>, String> {
+        aggregate_entries(aggregates, group_by, self.scan(filter))
+    }
+
+    /// The different values a field has in the entries that match the filter, sorted
+    fn distinct(&self, field: E::FieldNames, filter: Filter<E>) -> Result<Vec<Value>, String> {
+        let rows = self.aggregate(&[], &[field], filter)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(mut group, _)| group.pop())
+            .collect())
+    }
+
+    /// The same as distinct, with how many of the entries have each value
+    fn distinct_counts(
+        &self,
+        field: E::FieldNames,
+        filter: Filter<E>,
+    ) -> Result<Vec<(Value, u64)>, String> {
+        let mut counts = Vec::new();
+        for (mut group, mut values) in self.aggregate(&[Aggregate::Count], &[field], filter)? {
+            match (group.pop(), values.pop()) {
+                (Some(value), Some(Value::Integer(count))) => counts.push((value, count as u64)),
+                _ => return Err("Bad count".to_string()),
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/**
+ *  A table that can group changes together, so either all of them are kept or none of them are.
+ *  Between begin and commit, changes are only seen through this table. Dropping the table without
+ *  calling commit throws the changes away.
+*/
+pub trait Transactional<E: Entry>: Table<E> {
+    /// Starts a transaction. Returns an Err if one is already open.
+    fn begin(&mut self) -> Result<(), String>;
+
+    /// Keeps every change made since begin and ends the transaction
+    fn commit(&mut self) -> Result<(), String>;
+
+    /// Throws away every change made since begin and ends the transaction
+    fn rollback(&mut self) -> Result<(), String>;
+
+    /// Runs f inside a transaction. The changes f makes are committed if it returns Ok, and
+    /// rolled back if it returns Err.
+    fn transaction<T, F>(&mut self, f: F) -> Result<T, String>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Result<T, String>,
+    {
+        self.begin()?;
+        match f(self) {
+            Ok(result) => self.commit().map(|_| result),
+            Err(e) => match self.rollback() {
+                Ok(()) => Err(e),
+                Err(rollback_e) => Err(e + ", and the rollback failed: " + &rollback_e),
+            },
+        }
+    }
+}
+
+//The Err update_if_version gives when the entry was changed since the version it was given
+pub static VERSION_CONFLICT: &str = "The entry was changed since it was looked up";
+
+/**
+ *  A table that keeps a version for every entry, which goes up each time the entry is changed.
+ *  Looking up the version with an entry and then updating with update_if_version keeps two people
+ *  editing the same entry from overwriting each other without knowing it.
+*/
+pub trait Versioned<E: Entry>: Table<E> {
+    /// Lookup an entry along with its current version
+    fn lookup_version(&self, key: Self::Key) -> Option<(E, u64)>;
+
+    /// Update the entry only if it is still at the given version. Returns the new version, or
+    /// Err(VERSION_CONFLICT) if the entry was changed since then.
+    fn update_if_version(&mut self, key: Self::Key, version: u64, entry: E) -> Result<u64, String>;
+}
+
+/**
+ *  A table that can be changed through a shared reference. One table can be used from many
+ *  threads at once without putting it behind a Mutex, and each change is made atomically.
+*/
+pub trait SharedTable<E: Entry>: Send + Sync {
+    /// The Key type for this database.
+    /// Must implement the Key trait
+    type Key: Key<E> + Send;
+
+    /// Insert an entry into the table. Returns a key for the entry in the table.
+    fn insert(&self, entry: E) -> Self::Key;
+
+    /// Find a key in the table. Returns Some(Entry) if the entry for the key is in the table, None
+    /// otherwise.
+    fn lookup(&self, key: Self::Key) -> Option<E>;
+
+    /// Search for entries in the table with a field matching a value. Returns a vector of keys
+    /// and entries for the results.
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> Result<Vec<(Self::Key, E)>, String>;
+
+    /// Update an entry at a given key with a new entry
+    fn update(&self, key: Self::Key, entry: E) -> Result<(), String>;
+
+    /// Removes the entry for the given key in the table. Returns an Err if the key could not be
+    /// found.
+    fn remove(&self, key: Self::Key) -> Result<(), String>;
+
+    /// Check whether a given key is in the table
+    fn contains(&self, key: Self::Key) -> bool;
+
+    /// Same as Table::query
+    fn query(&self, q: QueryType<E>, key: Option<Self::Key>)
+        -> Result<Vec<(Self::Key, E)>, String>;
+}
+
+#[cfg(test)]
+mod value_tests {
+
+    use interface::ITryInto;
+    use interface::Value;
+    use interface::ValueType;
+
+    #[test]
+    fn value_string_to_string() {
+        assert_eq!(
+            Value::String("hello".to_string()).to_string(),
+            "hello".to_string()
+        );
+    }
+
+    #[test]
+    fn value_integer_to_string() {
+        assert_eq!(Value::Integer(42).to_string(), "42".to_string());
+    }
+
+    #[test]
+    fn value_float_to_string() {
+        assert_eq!(Value::Float(42.612).to_string(), "42.612".to_string());
+    }
+
+    #[test]
+    fn value_bool_to_string() {
+        assert_eq!(Value::Boolean(true).to_string(), "true".to_string());
+    }
+
+    #[test]
+    fn value_into_i32() {
+        assert_eq!(Value::Integer(42).itry_into(), Ok(42));
+    }
+
+    #[test]
+    fn value_into_f32() {
+        assert_eq!(Value::Float(42.0).itry_into(), Ok(42.0));
+    }
+
+    #[test]
+    fn value_into_string() {
+        assert_eq!(
+            Value::String("hello".to_string()).itry_into(),
+            Ok("hello".to_string())
+        );
+    }
+
+    #[test]
+    fn value_into_bool() {
+        assert_eq!(Value::Boolean(true).itry_into(), Ok(true));
+    }
+
+    #[test]
+    fn value_get_type() {
+        assert_eq!(Value::Integer(42).get_type(), ValueType::Integer);
+        assert_eq!(Value::Float(42.0).get_type(), ValueType::Float);
+        assert_eq!(
+            Value::String("hello".to_string()).get_type(),
+            ValueType::String
+        );
+        assert_eq!(Value::Boolean(true).get_type(), ValueType::Boolean);
+    }
+
+    #[test]
+    fn value_from_text() {
+        assert_eq!(
+            Value::from_text(" 42", ValueType::Integer),
+            Some(Value::Integer(42))
+        );
+        assert_eq!(Value::from_text("4.5", ValueType::Integer), None);
+        assert_eq!(
+            Value::from_text("4.5", ValueType::Float),
+            Some(Value::Float(4.5))
+        );
+        assert_eq!(
+            Value::from_text(" ECE ", ValueType::String),
+            Some(Value::String(" ECE ".to_string()))
+        );
+        assert_eq!(
+            Value::from_text("TRUE", ValueType::Boolean),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(
+            Value::from_text("0", ValueType::Boolean),
+            Some(Value::Boolean(false))
+        );
+        assert_eq!(Value::from_text("yes", ValueType::Boolean), None);
+    }
+}
+
+#[cfg(test)]
+mod filter_tests {
+
+    use interface::check_field_types;
+    use interface::Filter;
+    use interface::Value;
+    use tests::Department;
+    use tests::DepartmentFields;
+
+    fn ece() -> Department {
+        Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        }
+    }
+
+    #[test]
+    fn filter_compare() {
+        let ece_value = Value::String("ECE".to_string());
+        let me_value = Value::String("ME".to_string());
+
+        assert!(Filter::All.matches(&ece()));
+        assert!(Filter::Equals(DepartmentFields::Abreviation, ece_value.clone()).matches(&ece()));
+        assert!(
+            !Filter::NotEquals(DepartmentFields::Abreviation, ece_value.clone()).matches(&ece())
+        );
+        assert!(Filter::LessThan(DepartmentFields::Abreviation, me_value.clone()).matches(&ece()));
+        assert!(Filter::LessOrEqual(DepartmentFields::Abreviation, ece_value).matches(&ece()));
+        assert!(
+            !Filter::GreaterThan(DepartmentFields::Abreviation, me_value.clone()).matches(&ece())
+        );
+        assert!(!Filter::GreaterOrEqual(DepartmentFields::Abreviation, me_value).matches(&ece()));
+    }
+
+    #[test]
+    fn filter_wrong_type() {
+        let number = Value::Integer(4);
+
+        assert!(!Filter::Equals(DepartmentFields::Abreviation, number.clone()).matches(&ece()));
+        assert!(!Filter::NotEquals(DepartmentFields::Abreviation, number.clone()).matches(&ece()));
+        assert!(!Filter::GreaterThan(DepartmentFields::Abreviation, number).matches(&ece()));
+    }
+
+    #[test]
+    fn filter_like() {
+        let like = |pattern: &str| {
+            Filter::Like(DepartmentFields::Name, pattern.to_string()).matches(&ece())
+        };
+
+        assert!(like("%engineering"));
+        assert!(like("electrical%"));
+        assert!(like("%and%"));
+        assert!(like("Electrical and Computer Engineerin_"));
+        assert!(like("%"));
+        assert!(!like("Electrical"));
+        assert!(!like("%Engineering_"));
+        assert!(!like("Electrical\\%"));
+        assert!(
+            Filter::Like(DepartmentFields::Name, "100\\%".to_string()).matches(&Department {
+                name: "100%".to_string(),
+                abreviation: "H".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn filter_contains() {
+        let contains = |text: &str| Filter::contains(DepartmentFields::Name, text).matches(&ece());
+
+        assert!(contains("COMPUTER"));
+        assert!(contains(""));
+        assert!(!contains("Computers"));
+        //Wildcards in the text are not wildcards
+        assert!(!contains("Electrical%Engineering"));
+        assert!(!contains("_lectrical"));
+        assert!(
+            Filter::contains(DepartmentFields::Name, "50%").matches(&Department {
+                name: "Up 50% off".to_string(),
+                abreviation: "H".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn filter_combine() {
+        let is_ece = Filter::Equals(
+            DepartmentFields::Abreviation,
+            Value::String("ECE".to_string()),
+        );
+        let is_me = Filter::Equals(
+            DepartmentFields::Abreviation,
+            Value::String("ME".to_string()),
+        );
+
+        assert!(Filter::And(vec![is_ece.clone(), Filter::All]).matches(&ece()));
+        assert!(!Filter::And(vec![is_ece.clone(), is_me.clone()]).matches(&ece()));
+        assert!(Filter::Or(vec![is_me.clone(), is_ece.clone()]).matches(&ece()));
+        assert!(!Filter::Or(Vec::new()).matches(&ece()));
+        assert!(Filter::And(Vec::new()).matches(&ece()));
+        assert!(Filter::Not(Box::new(is_me)).matches(&ece()));
+        assert!(!Filter::Not(Box::new(is_ece)).matches(&ece()));
+    }
+
+    #[test]
+    fn field_types() {
+        assert_eq!(
+            check_field_types::<Department>(&[
+                (DepartmentFields::Name, Value::String("Physics".to_string())),
+                (
+                    DepartmentFields::Abreviation,
+                    Value::String("PHYS".to_string())
+                ),
+            ]),
+            Ok(())
+        );
+        assert_eq!(
+            check_field_types::<Department>(&[(DepartmentFields::Abreviation, Value::Integer(4))]),
+            Err("Incorrect type for Abreviation. Should be String".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod page_tests {
+
+    use interface::sort_page;
+    use interface::SortDirection;
+    use tests::Department;
+    use tests::DepartmentFields;
+
+    fn keys(page: Vec<(usize, Department)>) -> Vec<usize> {
+        page.into_iter().map(|(key, _department)| key).collect()
+    }
+
+    #[test]
+    fn sort_page_bounds() {
+        let departments: Vec<Department> = (0..250)
+            .map(|i| Department {
+                name: format!("{:03}", i),
+                abreviation: "D".to_string(),
+            })
+            .collect();
+        let page = |limit, page| {
+            let entries = departments.iter().enumerate().collect();
+            keys(
+                sort_page(
+                    entries,
+                    limit,
+                    DepartmentFields::Name,
+                    SortDirection::Asc,
+                    page,
+                )
+                .unwrap(),
+            )
+        };
+
+        assert_eq!(page(2, 1), vec![0, 1]);
+        assert_eq!(page(2, 3), vec![4, 5]);
+        //Page 0 is the same as page 1
+        assert_eq!(page(2, 0), vec![0, 1]);
+        //The limit is at most MAX_LIMIT, and a big limit and page do not overflow
+        assert_eq!(page(500, 1), (0..100).collect::<Vec<usize>>());
+        assert_eq!(page(500, 3), (200..250).collect::<Vec<usize>>());
+        assert!(page(u16::MAX, u16::MAX).is_empty());
+    }
 }
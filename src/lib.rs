@@ -1,9 +1,26 @@
+extern crate csv;
+extern crate futures_util;
 extern crate mysql as my;
+extern crate postgres as pg;
+extern crate r2d2;
+extern crate r2d2_postgres;
+extern crate tokio;
+pub mod async_table;
+pub mod dialect;
+pub mod dynamic_entry;
+mod file_format;
 pub mod interface;
+pub mod log_table;
 pub mod my_types;
 pub mod mysql_test;
+pub mod pg_types;
+pub mod postgres_test;
+pub mod sql_builder;
+mod sql_table;
 pub mod tests;
+pub mod transfer;
 pub mod vec_table;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
+extern crate serde_json;
@@ -0,0 +1,859 @@
+use file_format;
+use file_format::Reader;
+use interface::check_field_types;
+use interface::sort_page;
+use interface::Entry;
+use interface::Filter;
+use interface::Key;
+use interface::QueryType;
+use interface::Scan;
+use interface::Table;
+use interface::Upserted;
+use interface::Value;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+/**
+ *  A key for a LogTable
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct LogTableKey {
+    pub id: u64,
+    pub valid: bool,
+}
+
+impl LogTableKey {
+    //A key for an entry in the table
+    fn new(id: u64) -> LogTableKey {
+        LogTableKey { id, valid: true }
+    }
+}
+
+impl<E: Entry> Key<E> for LogTableKey {}
+
+//The key insert gives back when the entry could not be written to the file
+pub static DEFAULT_KEY: LogTableKey = LogTableKey {
+    id: 0,
+    valid: false,
+};
+
+// The start of every LogTable file, and the version of the format after it
+static FILE_MAGIC: &[u8] = b"DBLIBLOG";
+static FILE_VERSION: u32 = 1;
+
+// The tag in front of each change in a record
+const INSERT: u8 = 0;
+const UPDATE: u8 = 1;
+const REMOVE: u8 = 2;
+
+// One change to the table. Every call that changes the table writes all of its changes in one
+// record, so after a crash either all of them are in the file or none are
+enum Change<E> {
+    Insert(u64, E),
+    Update(u64, E),
+    Remove(u64),
+}
+
+/**
+ *  A table kept in a file, for durable storage without a database server.
+ *
+ *  Every change is added to the end of the file as a record with a checksum, and the file is synced
+ *  before the change is made in memory. Opening the file reads all the records again to get the
+ *  entries back. A record that was only partly written when the program stopped can only be the
+ *  last one, so it is cut off the file. A bad record with more records after it was not torn by a
+ *  crash, and the file is left alone so the records after it are not lost.
+ *
+ *  The file keeps growing with every change, even removes. LogTable::compact rewrites it with only
+ *  the entries the table has now.
+*/
+pub struct LogTable<E: Entry> {
+    file: File,
+    // How much of the file is good records. A failed write is cut back off to this
+    len: u64,
+    // The entries in the table, by key
+    entries: BTreeMap<u64, E>,
+    next_key: u64,
+}
+
+impl<E: Entry> LogTable<E> {
+    /**
+     *  Opens the table in a file, or makes a new empty table if the file does not exist or is
+     *  empty. A partly written record at the end of the file is cut off, but a bad record before
+     *  the end is an error, with the byte it starts at.
+     */
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<LogTable<E>, String> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+
+        let mut table = LogTable {
+            file,
+            len: 0,
+            entries: BTreeMap::new(),
+            next_key: 0,
+        };
+        if bytes.is_empty() {
+            let header = header::<E>(0);
+            table.append(&header)?;
+            return Ok(table);
+        }
+
+        let mut reader = Reader::new(&bytes);
+        if reader.read_bytes(FILE_MAGIC.len()).ok() != Some(FILE_MAGIC) {
+            return Err("Not a LogTable file".to_string());
+        }
+        let version = reader.read_u32()?;
+        if version != FILE_VERSION {
+            return Err(format!("Unknown LogTable file version {}", version));
+        }
+        reader.read_field_names::<E>()?;
+        table.next_key = reader.read_u64()?;
+
+        let mut good_len = reader.position();
+        while let Some(payload) = read_record(&mut reader)
+            .map_err(|e| format!("Bad record at byte {}: {}", good_len, e))?
+        {
+            let changes = read_changes::<E>(payload)
+                .map_err(|e| format!("Bad record at byte {}: {}", good_len, e))?;
+            table.apply(changes);
+            good_len = reader.position();
+        }
+        table.len = good_len as u64;
+        if good_len < bytes.len() {
+            table.file.set_len(table.len).map_err(|e| e.to_string())?;
+            table.file.sync_data().map_err(|e| e.to_string())?;
+        }
+        Ok(table)
+    }
+
+    /**
+     *  Rewrites the file for a table with only the entries it has now, so it does not have the
+     *  records for changes that were overwritten or removed anymore. Keys stay the same.
+     *  The table must not be open while it is compacted. The new file is written next to the old
+     *  one and then put in its place, so a failed compaction leaves the old file alone.
+     */
+    pub fn compact<P: AsRef<Path>>(path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let table: LogTable<E> = LogTable::open(path)?;
+
+        let mut bytes = header::<E>(table.next_key);
+        for (id, e) in table.entries {
+            bytes.extend(record(&[Change::Insert(id, e)]));
+        }
+
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        let mut temp_file = File::create(&temp_path).map_err(|e| e.to_string())?;
+        temp_file
+            .write_all(&bytes)
+            .and_then(|_| temp_file.sync_all())
+            .map_err(|e| e.to_string())?;
+        fs::rename(&temp_path, path).map_err(|e| e.to_string())
+    }
+
+    // Adds bytes to the end of the file, and waits until they are on the disk
+    fn append(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let written = self
+            .file
+            .write_all(bytes)
+            .and_then(|_| self.file.sync_data());
+        match written {
+            Ok(()) => {
+                self.len += bytes.len() as u64;
+                Ok(())
+            }
+            Err(e) => {
+                // Part of the record might have been written, and with records after it the file
+                // would not open again
+                let _ = self.file.set_len(self.len);
+                Err(e.to_string())
+            }
+        }
+    }
+
+    // Writes changes to the file, then makes them to the entries
+    fn write(&mut self, changes: Vec<Change<E>>) -> Result<(), String> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let record = record(&changes);
+        self.append(&record)?;
+        self.apply(changes);
+        Ok(())
+    }
+
+    fn apply(&mut self, changes: Vec<Change<E>>) {
+        for change in changes {
+            match change {
+                Change::Insert(id, e) | Change::Update(id, e) => {
+                    self.entries.insert(id, e);
+                    if id >= self.next_key {
+                        self.next_key = id + 1;
+                    }
+                }
+                Change::Remove(id) => {
+                    self.entries.remove(&id);
+                }
+            }
+        }
+    }
+
+    // The entries a filter matches, in the order they were inserted
+    fn matching(&self, filter: &Filter<E>) -> Vec<(LogTableKey, &E)> {
+        self.entries
+            .iter()
+            .filter(|(_id, e)| filter.matches(e))
+            .map(|(id, e)| (LogTableKey::new(*id), e))
+            .collect()
+    }
+}
+
+// The start of a file, with the key the next insert will get
+fn header<E: Entry>(next_key: u64) -> Vec<u8> {
+    let mut bytes = FILE_MAGIC.to_vec();
+    file_format::write_u32(&mut bytes, FILE_VERSION);
+    file_format::write_field_names::<E>(&mut bytes);
+    file_format::write_u64(&mut bytes, next_key);
+    bytes
+}
+
+// A record is the length of the changes in it, their checksum, and then the changes
+fn record<E: Entry>(changes: &[Change<E>]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    file_format::write_u32(&mut payload, changes.len() as u32);
+    for change in changes {
+        let (tag, id, entry) = match change {
+            Change::Insert(id, e) => (INSERT, id, Some(e)),
+            Change::Update(id, e) => (UPDATE, id, Some(e)),
+            Change::Remove(id) => (REMOVE, id, None),
+        };
+        payload.push(tag);
+        file_format::write_u64(&mut payload, *id);
+        if let Some(e) = entry {
+            for value in e.get_fields() {
+                file_format::write_value(&mut payload, &value);
+            }
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(payload.len() + 8);
+    file_format::write_u32(&mut bytes, payload.len() as u32);
+    file_format::write_u32(&mut bytes, file_format::crc32(&payload));
+    bytes.extend(payload);
+    bytes
+}
+
+// Gives the changes in the next record, or None at the end of the file or if the rest of the file
+// is a record that was not all written. Records are only ever added at the end, so a record that
+// fails its checksum with more of the file after it is an error, and so is a length that runs
+// past good records
+fn read_record<'a>(reader: &mut Reader<'a>) -> Result<Option<&'a [u8]>, String> {
+    let (len, checksum) = match (reader.read_u32(), reader.read_u32()) {
+        (Ok(len), Ok(checksum)) => (len, checksum),
+        _ => return Ok(None),
+    };
+    let rest = reader.rest();
+    let payload = match reader.read_bytes(len as usize) {
+        Ok(payload) => payload,
+        Err(_) if has_record(rest) => return Err("Bad record length".to_string()),
+        Err(_) => return Ok(None),
+    };
+    if file_format::crc32(payload) == checksum {
+        Ok(Some(payload))
+    } else if reader.is_done() {
+        Ok(None)
+    } else {
+        Err("Bad checksum".to_string())
+    }
+}
+
+// Whether there is a whole record with a good checksum anywhere in bytes. Every record has at
+// least the count of its changes, so an empty one is not counted
+fn has_record(bytes: &[u8]) -> bool {
+    (0..bytes.len()).any(|start| {
+        let mut reader = Reader::new(&bytes[start..]);
+        match (reader.read_u32(), reader.read_u32()) {
+            (Ok(len), Ok(checksum)) if len >= 4 => match reader.read_bytes(len as usize) {
+                Ok(payload) => file_format::crc32(payload) == checksum,
+                Err(_) => false,
+            },
+            _ => false,
+        }
+    })
+}
+
+fn read_changes<E: Entry>(payload: &[u8]) -> Result<Vec<Change<E>>, String> {
+    let mut reader = Reader::new(payload);
+    let field_count = E::get_field_names().len();
+    let count = reader.read_u32()?;
+    let mut changes = Vec::new();
+    for _ in 0..count {
+        let tag = reader.read_u8()?;
+        let id = reader.read_u64()?;
+        if tag == REMOVE {
+            changes.push(Change::Remove(id));
+            continue;
+        }
+        let mut fields = Vec::new();
+        for _ in 0..field_count {
+            fields.push(reader.read_value()?);
+        }
+        let entry = E::from_fields(&fields)?;
+        match tag {
+            INSERT => changes.push(Change::Insert(id, entry)),
+            UPDATE => changes.push(Change::Update(id, entry)),
+            _ => return Err(format!("Bad change tag {}", tag)),
+        }
+    }
+    if !reader.is_done() {
+        return Err("Extra data at the end of the record".to_string());
+    }
+    Ok(changes)
+}
+
+impl<E: Entry> Table<E> for LogTable<E> {
+    type Key = LogTableKey;
+
+    fn insert(&mut self, entry: E) -> Self::Key {
+        let id = self.next_key;
+        match self.write(vec![Change::Insert(id, entry)]) {
+            Ok(()) => LogTableKey::new(id),
+            Err(_) => DEFAULT_KEY,
+        }
+    }
+
+    fn lookup(&self, key: Self::Key) -> Option<E> {
+        if key.valid {
+            self.entries.get(&key.id).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        Ok(self
+            .matching(&Filter::Equals(field_name, field_value))
+            .into_iter()
+            .map(|(key, e)| (key, e.clone()))
+            .collect())
+    }
+
+    fn update(&mut self, key: Self::Key, entry: E) -> Result<(), String> {
+        if !self.contains(key) {
+            return Err("Key not found".to_string());
+        }
+        self.write(vec![Change::Update(key.id, entry)])
+    }
+
+    fn remove(&mut self, key: Self::Key) -> Result<(), String> {
+        if !self.contains(key) {
+            return Err("Key not in table".to_string());
+        }
+        self.write(vec![Change::Remove(key.id)])
+    }
+
+    fn contains(&self, key: Self::Key) -> bool {
+        key.valid && self.entries.contains_key(&key.id)
+    }
+
+    fn query(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        match q {
+            QueryType::Lookup => {
+                if let Some(key) = key {
+                    match self.lookup(key) {
+                        Some(entry) => Ok(vec![(key, entry)]),
+                        None => Err("Key not found".to_string()),
+                    }
+                } else {
+                    Err("Need a key!".to_string())
+                }
+            }
+
+            QueryType::Search(field_name, field_value, limit, sort_field, sort_dir, page) => {
+                let found = self.matching(&Filter::Equals(field_name, field_value));
+                sort_page(found, limit, sort_field, sort_dir, page)
+            }
+
+            QueryType::MultiSearch(
+                field_names,
+                field_values,
+                limit,
+                sort_field,
+                sort_dir,
+                page,
+            ) => {
+                if field_names.len() != field_values.len() {
+                    return Err("Field and Value vectors do not pair".to_string());
+                }
+                let filter = Filter::And(
+                    field_names
+                        .into_iter()
+                        .zip(field_values)
+                        .map(|(field_name, field_value)| Filter::Equals(field_name, field_value))
+                        .collect(),
+                );
+                sort_page(self.matching(&filter), limit, sort_field, sort_dir, page)
+            }
+
+            QueryType::GetAll(limit, sort_field, sort_dir, page) => sort_page(
+                self.matching(&Filter::All),
+                limit,
+                sort_field,
+                sort_dir,
+                page,
+            ),
+
+            QueryType::PartialSearch(
+                field_name,
+                field_value,
+                limit,
+                sort_field,
+                sort_dir,
+                page,
+            ) => {
+                let filter = Filter::contains(field_name, &field_value.to_string());
+                sort_page(self.matching(&filter), limit, sort_field, sort_dir, page)
+            }
+        }
+    }
+
+    fn scan<'a>(&'a self, filter: Filter<E>) -> Scan<'a, Self::Key, E> {
+        Box::new(
+            self.entries
+                .iter()
+                .filter(move |(_id, e)| filter.matches(e))
+                .map(|(id, e)| Ok((LogTableKey::new(*id), e.clone()))),
+        )
+    }
+
+    fn update_where(
+        &mut self,
+        filter: Filter<E>,
+        assignments: Vec<(E::FieldNames, Value)>,
+    ) -> Result<u64, String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let field_names = E::get_field_names();
+        let mut indexed_assignments = Vec::new();
+        for (field, value) in assignments {
+            match field_names.iter().position(|f| *f == field) {
+                Some(index) => indexed_assignments.push((index, value)),
+                None => return Err("Bad field".to_string()),
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (key, e) in self.matching(&filter) {
+            let mut fields = e.get_fields();
+            for (index, value) in &indexed_assignments {
+                fields[*index] = value.clone();
+            }
+            changes.push(Change::Update(key.id, E::from_fields(&fields)?));
+        }
+
+        let count = changes.len() as u64;
+        self.write(changes)?;
+        Ok(count)
+    }
+
+    fn remove_where(&mut self, filter: Filter<E>) -> Result<u64, String> {
+        let changes: Vec<Change<E>> = self
+            .matching(&filter)
+            .into_iter()
+            .map(|(key, _e)| Change::Remove(key.id))
+            .collect();
+        let count = changes.len() as u64;
+        self.write(changes)?;
+        Ok(count)
+    }
+
+    fn insert_many(&mut self, entries: Vec<E>) -> Result<Vec<Self::Key>, String> {
+        let first_key = self.next_key;
+        let changes: Vec<Change<E>> = (first_key..)
+            .zip(entries)
+            .map(|(id, entry)| Change::Insert(id, entry))
+            .collect();
+        let next_key = first_key + changes.len() as u64;
+        self.write(changes)?;
+        Ok((first_key..next_key).map(LogTableKey::new).collect())
+    }
+
+    fn update_many(&mut self, updates: Vec<(Self::Key, E)>) -> Result<(), String> {
+        let mut changes = Vec::new();
+        for (key, entry) in updates {
+            if !self.contains(key) {
+                return Err("Key not found".to_string());
+            }
+            changes.push(Change::Update(key.id, entry));
+        }
+        self.write(changes)
+    }
+
+    fn upsert(
+        &mut self,
+        match_fields: &[E::FieldNames],
+        entry: E,
+    ) -> Result<(Self::Key, Upserted), String> {
+        if match_fields.is_empty() {
+            return Err("Need at least one field to match on".to_string());
+        }
+        let mut match_filters = Vec::new();
+        for field in match_fields {
+            match entry.get_field(*field) {
+                Some(value) => match_filters.push(Filter::Equals(*field, value)),
+                None => return Err("Bad field".to_string()),
+            }
+        }
+
+        let matches: Vec<u64> = self
+            .matching(&Filter::And(match_filters))
+            .into_iter()
+            .map(|(key, _e)| key.id)
+            .collect();
+        match matches.len() {
+            0 => {
+                let id = self.next_key;
+                self.write(vec![Change::Insert(id, entry)])?;
+                Ok((LogTableKey::new(id), Upserted::Inserted))
+            }
+            1 => {
+                self.write(vec![Change::Update(matches[0], entry)])?;
+                Ok((LogTableKey::new(matches[0]), Upserted::Updated))
+            }
+            _ => Err("More than one entry matches".to_string()),
+        }
+    }
+
+    fn remove_many(&mut self, keys: Vec<Self::Key>) -> Result<(), String> {
+        let mut removed = HashSet::new();
+        for key in keys {
+            if !self.contains(key) || !removed.insert(key.id) {
+                return Err("Key not in table".to_string());
+            }
+        }
+        self.write(removed.into_iter().map(Change::Remove).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use interface::Filter;
+    use interface::QueryType;
+    use interface::SortDirection;
+    use interface::Table;
+    use interface::Value;
+    use log_table::LogTable;
+    use log_table::LogTableKey;
+    use std::env;
+    use std::fs;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process;
+    use tests::Department;
+    use tests::DepartmentFields;
+    use tests::User;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("database_lib_{}_{}", name, process::id()))
+    }
+
+    fn department(name: &str, abreviation: &str) -> Department {
+        Department {
+            name: name.to_string(),
+            abreviation: abreviation.to_string(),
+        }
+    }
+
+    fn abreviations(table: &LogTable<Department>) -> Vec<String> {
+        table
+            .iter()
+            .map(|result| result.unwrap().1.abreviation)
+            .collect()
+    }
+
+    #[test]
+    fn test_logtable_reopen() {
+        let path = temp_path("logtable_reopen");
+        let _ = fs::remove_file(&path);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        let ece = department_table.insert(department("Electrical and Computer Engineering", "ECE"));
+        let keys = department_table
+            .insert_many(vec![
+                department("Physics", "PHYS"),
+                department("Mechanical Engineering", "ME"),
+                department("Civil Engineering", "CE"),
+            ])
+            .unwrap();
+        department_table
+            .update(ece, department("Electrical Engineering", "EE"))
+            .unwrap();
+        department_table.remove(keys[0]).unwrap();
+        assert_eq!(
+            department_table
+                .update_where(
+                    Filter::Like(DepartmentFields::Name, "%engineering".to_string()),
+                    vec![(
+                        DepartmentFields::Name,
+                        Value::String("Engineering".to_string())
+                    )],
+                )
+                .unwrap(),
+            3
+        );
+        department_table
+            .remove_where(Filter::Equals(
+                DepartmentFields::Abreviation,
+                Value::String("CE".to_string()),
+            ))
+            .unwrap();
+        // A value of the wrong type is an error even when nothing matches
+        assert_eq!(
+            department_table.update_where(
+                Filter::Equals(
+                    DepartmentFields::Abreviation,
+                    Value::String("XX".to_string())
+                ),
+                vec![(DepartmentFields::Name, Value::Integer(4))],
+            ),
+            Err("Incorrect type for Name. Should be String".to_string())
+        );
+        assert!(department_table.remove(keys[0]).is_err());
+        assert!(department_table
+            .remove_many(vec![keys[1], keys[2]])
+            .is_err());
+        drop(department_table);
+
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["EE", "ME"]);
+        assert_eq!(
+            department_table.lookup(keys[1]).unwrap().name,
+            "Engineering"
+        );
+        assert!(!department_table.contains(keys[2]));
+        // The key of the removed entry at the end is not given out again
+        let chem = department_table.insert(department("Chemistry", "CHEM"));
+        assert_eq!(chem, LogTableKey { id: 4, valid: true });
+
+        let found = department_table
+            .query(
+                QueryType::Search(
+                    DepartmentFields::Name,
+                    Value::String("Engineering".to_string()),
+                    10,
+                    DepartmentFields::Abreviation,
+                    SortDirection::Desc,
+                    1,
+                ),
+                None,
+            )
+            .unwrap();
+        let found: Vec<LogTableKey> = found.into_iter().map(|(k, _e)| k).collect();
+        assert_eq!(found, vec![keys[1], ece]);
+
+        assert!(LogTable::<User>::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_logtable_torn_tail() {
+        let path = temp_path("logtable_torn_tail");
+        let _ = fs::remove_file(&path);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        department_table.insert(department("Physics", "PHYS"));
+        let good_len = fs::metadata(&path).unwrap().len();
+        department_table.insert(department("Mechanical Engineering", "ME"));
+        drop(department_table);
+
+        // Stopping in the middle of writing the last record leaves only part of it
+        let full_len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(full_len - 3)
+            .unwrap();
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS"]);
+        assert_eq!(fs::metadata(&path).unwrap().len(), good_len);
+
+        // and so does a record with garbage in it
+        department_table.insert(department("Civil Engineering", "CE"));
+        drop(department_table);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[4, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8])
+            .unwrap();
+        drop(file);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS", "CE"]);
+
+        // New records go after the good ones
+        department_table.insert(department("Chemistry", "CHEM"));
+        drop(department_table);
+        let department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS", "CE", "CHEM"]);
+
+        fs::write(&path, b"not a table").unwrap();
+        assert_eq!(
+            LogTable::<Department>::open(&path).err(),
+            Some("Not a LogTable file".to_string())
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_logtable_compact() {
+        let path = temp_path("logtable_compact");
+        let _ = fs::remove_file(&path);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        let keys = department_table
+            .insert_many(vec![
+                department("Physics", "PHYS"),
+                department("Mechanical Engineering", "ME"),
+                department("Civil Engineering", "CE"),
+            ])
+            .unwrap();
+        for i in 0..20 {
+            department_table
+                .update(keys[0], department(&format!("Physics {}", i), "PHYS"))
+                .unwrap();
+        }
+        department_table.remove(keys[2]).unwrap();
+        drop(department_table);
+
+        let old_len = fs::metadata(&path).unwrap().len();
+        LogTable::<Department>::compact(&path).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < old_len / 4);
+
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS", "ME"]);
+        assert_eq!(department_table.lookup(keys[0]).unwrap().name, "Physics 19");
+        let key = department_table.insert(department("Chemistry", "CHEM"));
+        assert_eq!(key, LogTableKey { id: 3, valid: true });
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_logtable_corrupt_record() {
+        let path = temp_path("logtable_corrupt_record");
+        let _ = fs::remove_file(&path);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        department_table.insert(department("Physics", "PHYS"));
+        let middle = fs::metadata(&path).unwrap().len();
+        department_table.insert(department("Mechanical Engineering", "ME"));
+        department_table.insert(department("Civil Engineering", "CE"));
+        drop(department_table);
+
+        // A byte changed in a record that is not the last one is not a torn write
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[middle as usize + 12] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(
+            LogTable::<Department>::open(&path).err(),
+            Some(format!("Bad record at byte {}: Bad checksum", middle))
+        );
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        // Putting the byte back gets every record after it back
+        bytes[middle as usize + 12] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        let department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS", "ME", "CE"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_logtable_corrupt_length() {
+        let path = temp_path("logtable_corrupt_length");
+        let _ = fs::remove_file(&path);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        department_table.insert(department("Physics", "PHYS"));
+        let middle = fs::metadata(&path).unwrap().len() as usize;
+        department_table.insert(department("Mechanical Engineering", "ME"));
+        department_table.insert(department("Civil Engineering", "CE"));
+        drop(department_table);
+
+        // A length that runs past the end of the file is not a torn write if records follow it
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[middle + 3] ^= 0x40;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(
+            LogTable::<Department>::open(&path).err(),
+            Some(format!("Bad record at byte {}: Bad record length", middle))
+        );
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        bytes[middle + 3] ^= 0x40;
+        fs::write(&path, &bytes).unwrap();
+        let department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS", "ME", "CE"]);
+
+        // The length of the last record running past the end is still a torn write
+        let last = bytes.len() - 30;
+        drop(department_table);
+        fs::write(&path, &bytes[..last]).unwrap();
+        let department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        assert_eq!(abreviations(&department_table), vec!["PHYS", "ME"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_logtable_partial_search() {
+        let path = temp_path("logtable_partial_search");
+        let _ = fs::remove_file(&path);
+        let mut department_table: LogTable<Department> = LogTable::open(&path).unwrap();
+        department_table
+            .insert_many(vec![
+                department("Mechanical Engineering", "ME"),
+                department("Physics", "PHYS"),
+                department("Electrical and Computer Engineering", "ECE"),
+                department("50% Engineering", "HALF"),
+            ])
+            .unwrap();
+
+        let search = |text: &str| -> Vec<String> {
+            department_table
+                .query(
+                    QueryType::PartialSearch(
+                        DepartmentFields::Name,
+                        Value::String(text.to_string()),
+                        10,
+                        DepartmentFields::Abreviation,
+                        SortDirection::Asc,
+                        1,
+                    ),
+                    None,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|(_key, department)| department.abreviation)
+                .collect()
+        };
+        assert_eq!(search("engineering"), vec!["ECE", "HALF", "ME"]);
+        assert_eq!(search("%"), vec!["HALF"]);
+        assert!(search("Chemistry").is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
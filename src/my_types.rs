@@ -1,13 +1,46 @@
+use dialect::Dialect;
+use dialect::MySql;
 use interface;
+use interface::check_field_types;
+use interface::field_type;
+use interface::Aggregate;
+use interface::AggregateRow;
 use interface::Entry;
+use interface::FieldRows;
+use interface::Filter;
 use interface::ITryInto;
 use interface::Key;
 use interface::QueryType;
+use interface::Reference;
+use interface::Scan;
+use interface::SortDirection;
 use interface::Table;
+use interface::Transactional;
+use interface::Upserted;
+use interface::ValueType;
+use interface::Versioned;
+use interface::VERSION_CONFLICT;
 use my;
+use sql_builder::aggregate_function;
+use sql_builder::filter_condition;
+use sql_builder::Delete;
+use sql_builder::GroupSelect;
+use sql_builder::Insert;
+use sql_builder::Join;
+use sql_builder::Select;
+use sql_builder::Statement;
+use sql_builder::Update;
+use sql_builder::MAX_PARAMS;
+use sql_table;
+use sql_table::SqlTable;
+use std::fmt;
+use std::iter;
 use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
 
-pub static MAX_LIMIT: u16 = 100;
+pub use sql_builder::MAX_LIMIT;
 pub static DEFAULT_KEY: MysqlTableKey = MysqlTableKey {
     id: 0,
     valid: false,
@@ -20,7 +53,12 @@ pub struct MysqlTable<E: Entry> {
     pub db_name: String,
     pub key_name: String,
     pub pool: my::Pool, //The pool that the user is connected to at the time. Use open_mysql(...) to get a Pool
-    pub phantom: PhantomData<E>,
+    //The table never holds an E itself, so it is Send and Sync whatever E is
+    pub phantom: PhantomData<fn() -> E>,
+    //The transaction the table runs in, if any. Leave as None, begin and use_transaction_of set it
+    pub transaction: Option<MysqlTransaction>,
+    //The column that counts changes to each row, for Versioned. Should be INT NOT NULL DEFAULT 0
+    pub version_name: Option<String>,
 }
 
 impl<E: Entry> Table<E> for MysqlTable<E> {
@@ -32,270 +70,276 @@ impl<E: Entry> Table<E> for MysqlTable<E> {
 
     //Searches the tables for a key
     fn lookup(&self, key: Self::Key) -> Option<E> {
-        //Always start with opening mysql
-        //Opening mysql will never panic if done with the openmysql function
-        let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-        let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-        con.query(cmd_db).unwrap(); //Sending known command
-
-        let cmd = "SELECT * FROM ".to_string()
-            + &self.tb_name
-            + " WHERE "
-            + &self.key_name
-            + " = "
-            + &key.id.to_string();
-        let vec_result: Result<Vec<Vec<my::Value>>, my::Error> =
-            con.prep_exec(cmd, ()).map(|result| {
-                result
-                    .map(|x| match x {
-                        Err(_e) => return Err("Bad Query".to_string()),
-                        Ok(y) => Ok(y),
-                    })
-                    .map(|row| {
-                        //Iterates through each row
-                        my::Row::unwrap(row.unwrap()) //Unwraps after checking its okay
-                                                      //Returns a vector of my::Values for each row in the same order as DB
-                    })
-                    .collect()
-            });
-        let vec_result_okay = match vec_result {
-            Ok(x) => x,
-            Err(_) => return None, //Converts my::Error to string
-        };
-        let this_result = &vec_result_okay[0]; //Saves the desired entry to a seperate vec
-                                               //Change the my::Value to interface::Value, start by declaring needed variables
-        let mut the_result = Vec::new();
-        let mut i: usize = 0;
-        let mut good_value: bool = true;
-        let _err_string = "Failed to convert mySQL Value".to_string();
-        //Iterate through this_result and convert each myValue to iValue
-        while i < this_result.len() {
-            let temp = myvalue_to_ivalue(&this_result[i]);
-            //Check the result for an error
-            match temp {
-                Ok(x) => the_result.push(x),
-                Err(_) => good_value = false,
-            };
-            i = i + 1;
-        }
-        // Make a return based on the presence of an error
-        match &good_value {
-            true => Some(Entry::from_fields(&the_result[1..]).unwrap()), //unwraps after checking it's okay, so it won't panic
-            false => None,
+        //SELECT key, fields FROM tb_name WHERE key_name = key
+        let select = self
+            .sql()
+            .select()
+            .where_equals(&self.key_name, interface::Value::Integer(key.id));
+        match self.select_entries(select) {
+            Ok(mut result_vec) => result_vec.pop().map(|(_key, entry)| entry),
+            Err(_) => None,
         }
     }
-    //Inserts a new row into the table and returns a key
-    //Uses QueryResult.last_insert_id to get a key back
     fn insert(&mut self, entry: E) -> Self::Key {
-        //Always start with opening mysql
-        //Opening mysql will never panic if done with the openmysql function
-        //INSERT INTO tb_name (fields ) VALUES (values (NULL for auto increment));
-        let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-        let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-        con.query(cmd_db).unwrap(); //Sending known command
-
-        let entry_vec = entry.get_fields(); //Get the data as a string, must be ordered in the same way as fields
-        let entry_vec_string: Vec<String> =
-            entry_vec.iter().map(|x| ivalue_to_mystring(x)).collect();
-        let entry_string = entry_vec_string.join(", "); //Creates one big string from the string vec
-                                                        //Repeat entry string but for the values
-        let fields = E::get_field_names();
-        let field_iter = fields.iter(); //Cuts off the ID
-        let mut value_vec = Vec::new();
-        for i in field_iter {
-            value_vec.push(i.to_string());
-        }
-        let values = value_vec.join(", ");
-        //Generate the command with mySQL syntax and the 2 previous strings
-        let cmd = &("INSERT INTO ".to_string()
-            + &self.tb_name
-            + " ("
-            + &self.key_name
-            + ", "
-            + &values
-            + ") VALUES (NULL, "
-            + &entry_string
-            + ")");
-
-        let qr = con.query(cmd).is_ok(); //Send the prepared statement defined earlier and return a bool if it is okay
-                                         //Get last entry in that table, Because we know what exactly what is sent, the unwraps won't panic
-        let this_key: Vec<MysqlTableKey> = con
-            .prep_exec("SELECT LAST_INSERT_ID()", ())
-            .map(|result| {
-                result
-                    .map(|x| x.unwrap())
-                    .map(|row| {
-                        let id = my::from_row(row);
-                        MysqlTableKey {
-                            id: id,
-                            valid: true,
-                        }
-                    })
-                    .collect()
-            })
-            .unwrap(); //Sending known command, will not panic
-                       //Return a value based on good results
-        if qr {
-            this_key[0]
-        } else {
-            MysqlTableKey {
-                id: 0,
-                valid: false,
-            } //Returns 0 and an indicator of a bad key
-        }
+        interface::SharedTable::insert(self, entry)
     }
     fn search(
         &self,
         field_name: E::FieldNames,
         field_value: interface::Value,
     ) -> Result<Vec<(Self::Key, E)>, String> {
-        //SELECT * FROM tb_name WHERE field_name = field_value
-        //Always start with opening mysql
-        //Opening mysql will never panic if the pool is done with the openmysql function
-        let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-        let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-        con.query(cmd_db).unwrap(); //Sending known command
-
-        let cmd = "SELECT * FROM ".to_string()
-            + &self.tb_name
-            + " WHERE "
-            + &field_name.to_string()
-            + " = "
-            + &field_value.to_string();
-        let vec_result: Result<Result<Vec<(MysqlTableKey, E)>, String>, my::Error> =
-            con.prep_exec(cmd, ()).map(|result| {
-                result
-                    .map(|x| match x {
-                        Err(_e) => return Err("Bad Query".to_string()),
-                        Ok(y) => Ok(y),
-                    })
-                    .map(|row| {
-                        let vec_data = my::Row::unwrap(row.unwrap()); //Unwraps after checking its okay
-                                                                      //Returns a vector of my::Values for each row
-                        let iter_data = vec_data.iter();
-                        let ivec: Vec<interface::Value> = iter_data
-                            .map(|r| {
-                                myvalue_to_ivalue(r).unwrap() //Changes each my::Value to interface::Value
-                                                              //Panics if the database stores data not recognized by myvalue_to_ivalue
-                            })
-                            .collect();
-                        let this_entry_result = E::from_fields(&ivec[1..]);
-                        let this_entry = match this_entry_result {
-                            Err(string) => return Err(string),
-                            Ok(x) => x,
-                        }; //Only goes through with the let if the result is okay
-                        let this_key = MysqlTableKey {
-                            id: ivec[0].to_owned().itry_into().unwrap(),
-                            //Panics if the database's first column isn't the key, which it *should* be
-                            valid: true,
-                        };
-                        Ok((this_key, this_entry))
-                    })
-                    .collect()
-            });
-        match vec_result {
-            Ok(x) => x,
-            Err(y) => Err(y.to_string()),
-        }
+        //SELECT key, fields FROM tb_name WHERE field_name = field_value
+        self.select_entries(
+            self.sql()
+                .select()
+                .where_equals(&field_name.to_string(), field_value),
+        )
     }
     fn update(&mut self, key: Self::Key, entry: E) -> Result<(), String> {
-        //UPDATE tb_name SET field 1= entry 1, field 2 = entry 2, ... WHERE id = key
-        //Always start with opening mysql
-        //Opening mysql will never panic if done with the openmysql function
-        let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-        let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-        con.query(cmd_db).unwrap(); //Sending known command
-
-        //Create string for field x = entry x|
-        let fields = E::get_field_names();
-        let field_iter = fields.iter();
-        let entry_vec = entry.get_fields(); //Get the data as a string, must be ordered in the same way as fields
-        let mut entry_iter = entry_vec.iter().map(|x| ivalue_to_mystring(x)); //Converts the values into a string iterator
-        let mut set_vec: Vec<String> = Vec::new(); // String that will hold each field x = entry x
-        for i in field_iter {
-            set_vec.push(i.to_string() + " = " + &entry_iter.next().unwrap());
-            //Will not panic since ivalue_to_mystring will always return a valid string
-        }
-        let set = set_vec[..].join(", "); //Combines the set_vec (as a slice) into one string seperated by ,
-                                          //Create string for cmd and send
-        let cmd = "UPDATE ".to_string()
-            + &self.tb_name
-            + " SET "
-            + &set
-            + " WHERE "
-            + &self.key_name
-            + " = "
-            + &key.id.to_string();
-        let qr = con.query(cmd); //Send cmd and see if it is good
-
-        match qr {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e.to_string()), //Converts my::Error to a string
-        }
+        interface::SharedTable::update(self, key, entry)
     }
 
     fn remove(&mut self, key: Self::Key) -> Result<(), String> {
-        //DELETE FROM tb_name WHERE key_name = key
-        //Always start with opening mysql
-        //Opening mysql will never panic if done with the openmysql function
-        let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-        let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-        con.query(cmd_db).unwrap(); //Sending known command
-
-        let cmd = "DELETE FROM ".to_string()
-            + &self.tb_name
-            + " WHERE "
-            + &self.key_name
-            + " = "
-            + &key.id.to_string();
-        let qr = con.query(cmd);
-
-        match qr {
-            Ok(_) => Ok(()),
-            Err(_) => {
-                Err("There was a problem deleting the user, please consult sysadmin".to_string())
-            }
-        }
+        interface::SharedTable::remove(self, key)
     }
     fn contains(&self, key: Self::Key) -> bool {
         //Same as lookup but returns a bool if the query result returns anything
-        //Always start with opening mysql
-        //Opening mysql will never panic if done with the openmysql function
-        let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-        let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-        con.query(cmd_db).unwrap(); //Sending known command
-                                    //Create command to execute in mySQL
-        let cmd = "SELECT * FROM ".to_string()
-            + &self.tb_name
-            + " WHERE "
-            + &self.key_name
-            + " = "
-            + &key.id.to_string();
-        let vec_result: Result<Vec<Vec<my::Value>>, my::Error> =
-            con.prep_exec(cmd, ()).map(|result| {
-                result
-                    .map(|x| match x {
-                        Ok(y) => Ok(y),
-                        Err(_) => return Err("Bad Query".to_string()),
-                    })
-                    .map(|row| {
-                        //Iterates through each row, will panic if schema is not followed
-                        let vec_data = my::Row::unwrap(row.unwrap()); //Unwraps after checking its okay
-                                                                      //Returns a vector of my::Values for each row
-                        vec_data //The order of the vector depends on the order inside the mySQL database
-                    })
-                    .collect()
-            });
-        match &vec_result {
-            Ok(x) => x,
-            Err(_) => return false,
+        match self.select_entries(
+            self.sql()
+                .select()
+                .where_equals(&self.key_name, interface::Value::Integer(key.id)),
+        ) {
+            Ok(result_vec) => !result_vec.is_empty(),
+            Err(_) => false,
+        }
+    }
+    //A statement that fails would leave the rows before it inserted without giving back their
+    //keys, so every statement runs in one transaction, unless there is one open already
+    fn insert_many(&mut self, entries: Vec<E>) -> Result<Vec<Self::Key>, String> {
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.in_transaction() {
+            self.insert_entries(entries)
+        } else {
+            self.transaction(|table| table.insert_entries(entries))
+        }
+    }
+    //Runs every UPDATE on the same connection
+    fn update_many(&mut self, updates: Vec<(Self::Key, E)>) -> Result<(), String> {
+        let mut con = self.open()?;
+        for (key, entry) in updates {
+            let result = con.prep_exec(self.sql().update_statement(key.id, &entry))?;
+            if rows_matched(&result.info(), result.affected_rows()) == 0 {
+                return Err("Key not in table".to_string());
+            }
+        }
+        Ok(())
+    }
+    //SELECT key, fields FROM db_name.tb_name WHERE filter
+    //Runs on a connection of its own, which goes back to the pool when the iterator is dropped.
+    //Inside a transaction the rows are read all at once instead, since the connection of the
+    //transaction is needed for the other statements
+    fn scan<'a>(&'a self, filter: Filter<E>) -> Scan<'a, Self::Key, E> {
+        let mut select = self.sql().select();
+        if let Some(condition) = filter_condition(&filter) {
+            select = select.condition(condition);
+        }
+        if self.in_transaction() {
+            return match self.select_entries(select) {
+                Ok(result_vec) => Box::new(result_vec.into_iter().map(Ok)),
+                Err(e) => Box::new(iter::once(Err(e))),
+            };
+        }
+        //The pool could give any connection, so the database is named instead of using USE
+        let statement = select.database(&self.db_name).build(&MySql);
+        match self
+            .pool
+            .prep_exec(statement.sql, to_params(&statement.params))
+        {
+            Ok(result) => Box::new(result.map(row_to_entry)),
+            Err(e) => Box::new(iter::once(Err(e.to_string()))),
+        }
+    }
+    //UPDATE tb_name SET field = value, ... WHERE filter
+    //MySQL only counts the rows where a value actually changed
+    fn update_where(
+        &mut self,
+        filter: Filter<E>,
+        assignments: Vec<(E::FieldNames, interface::Value)>,
+    ) -> Result<u64, String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let mut update = self.sql().versioned(Update::new(&self.tb_name));
+        for (field, value) in assignments {
+            update = update.set(&field.to_string(), value);
+        }
+        if let Some(condition) = filter_condition(&filter) {
+            update = update.condition(condition);
+        }
+        self.execute(update.build(&MySql))
+    }
+    //UPDATE tb_name SET field = value, ... WHERE key_name = key
+    fn patch(
+        &mut self,
+        key: Self::Key,
+        assignments: Vec<(E::FieldNames, interface::Value)>,
+    ) -> Result<(), String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let mut update = self.sql().versioned(Update::new(&self.tb_name));
+        for (field, value) in assignments {
+            update = update.set(&field.to_string(), value);
+        }
+        let update = update.where_equals(&self.key_name, interface::Value::Integer(key.id));
+        match self.execute_matched(update.build(&MySql)) {
+            Ok(0) => Err("Key not in table".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove_where(&mut self, filter: Filter<E>) -> Result<u64, String> {
+        //DELETE FROM tb_name WHERE filter
+        let mut delete = Delete::new(&self.tb_name);
+        if let Some(condition) = filter_condition(&filter) {
+            delete = delete.condition(condition);
+        }
+        self.execute(delete.build(&MySql))
+    }
+    //INSERT ... ON DUPLICATE KEY UPDATE, which needs a UNIQUE index on the match fields.
+    //MySQL counts 1 changed row for an insert, and 2 for an update (0 if nothing was different)
+    fn upsert(
+        &mut self,
+        match_fields: &[E::FieldNames],
+        entry: E,
+    ) -> Result<(Self::Key, Upserted), String> {
+        if match_fields.is_empty() {
+            return Err("Need at least one field to match on".to_string());
+        }
+        let match_columns: Vec<String> =
+            match_fields.iter().map(|field| field.to_string()).collect();
+        let mut insert = Insert::new(&self.tb_name);
+        for (field, value) in E::get_field_names().iter().zip(entry.get_fields()) {
+            insert = insert.value(&field.to_string(), value);
+        }
+        let mut insert = insert
+            .on_conflict_update(&match_columns)
+            .returning(&self.key_name);
+        if let Some(ref version_name) = self.version_name {
+            insert = insert.increment(version_name);
+        }
+        let mut con = self.open()?;
+        let result = con.prep_exec(insert.build(&MySql))?;
+        let id = result.last_insert_id();
+        if id == 0 || id > i32::MAX as u64 {
+            return Err("Could not get the key of the upserted row".to_string());
+        }
+        let upserted = if result.affected_rows() == 1 {
+            Upserted::Inserted
+        } else {
+            Upserted::Updated
         };
-        if vec_result.unwrap().len() != 0 {
-            //Unwraps after checking its okay
-            true
+        Ok((
+            MysqlTableKey {
+                id: id as i32,
+                valid: true,
+            },
+            upserted,
+        ))
+    }
+    fn remove_many(&mut self, keys: Vec<Self::Key>) -> Result<(), String> {
+        //DELETE FROM tb_name WHERE key_name IN (keys)
+        let mut ids: Vec<i32> = keys.iter().map(|key| key.id).collect();
+        ids.sort();
+        ids.dedup();
+        let mut removed = 0;
+        let mut con = self.open()?;
+        for chunk in ids.chunks(MAX_PARAMS) {
+            let delete = Delete::new(&self.tb_name).where_in(
+                &self.key_name,
+                chunk
+                    .iter()
+                    .map(|id| interface::Value::Integer(*id))
+                    .collect(),
+            );
+            removed += con.prep_exec(delete.build(&MySql))?.affected_rows();
+        }
+        if removed == ids.len() as u64 {
+            Ok(())
         } else {
-            false
+            Err("Key not in table".to_string())
+        }
+    }
+    //SELECT group fields, aggregates FROM tb_name WHERE filter GROUP BY group fields
+    //With the usual collations MySQL ignores case when it compares strings, so strings that only
+    //differ in case end up in the same group, unlike in the tables that work in memory
+    fn aggregate(
+        &self,
+        aggregates: &[Aggregate<E>],
+        group_by: &[E::FieldNames],
+        filter: Filter<E>,
+    ) -> Result<Vec<AggregateRow>, String> {
+        //The type of each column that comes back, the group fields first
+        let mut value_types = Vec::new();
+        for field in group_by {
+            value_types.push(field_type::<E>(*field)?);
+        }
+        for aggregate in aggregates {
+            value_types.push(aggregate.value_type()?);
+        }
+        let group_columns: Vec<String> = group_by.iter().map(|field| field.to_string()).collect();
+        let functions = aggregates.iter().map(aggregate_function).collect();
+        let mut select = GroupSelect::new(&self.tb_name, &group_columns, functions);
+        if let Some(condition) = filter_condition(&filter) {
+            select = select.condition(condition);
+        }
+        let mut con = self.open()?;
+        let result = con.prep_exec(select.build(&MySql))?;
+        let mut rows = Vec::new();
+        for row in result {
+            let values = my::Row::unwrap(row.map_err(|e| e.to_string())?);
+            if values.len() != value_types.len() {
+                return Err("Wrong number of columns".to_string());
+            }
+            let mut ivec = Vec::new();
+            for (value, value_type) in values.iter().zip(&value_types) {
+                ivec.push(myvalue_to_type(value, *value_type)?);
+            }
+            let aggregate_values = ivec.split_off(group_by.len());
+            rows.push((ivec, aggregate_values));
+        }
+        Ok(rows)
+    }
+    //SELECT DISTINCT field FROM tb_name WHERE filter ORDER BY field
+    fn distinct(
+        &self,
+        field: E::FieldNames,
+        filter: Filter<E>,
+    ) -> Result<Vec<interface::Value>, String> {
+        let value_type = field_type::<E>(field)?;
+        let columns = vec![field.to_string()];
+        let mut select = Select::new(&self.tb_name, &columns)
+            .distinct()
+            .order_by(&columns[0], SortDirection::Asc);
+        if let Some(condition) = filter_condition(&filter) {
+            select = select.condition(condition);
         }
+        let mut con = self.open()?;
+        let result = con.prep_exec(select.build(&MySql))?;
+        let mut values = Vec::new();
+        for row in result {
+            let mut row = my::Row::unwrap(row.map_err(|e| e.to_string())?);
+            match row.pop() {
+                Some(value) if row.is_empty() => values.push(myvalue_to_type(&value, value_type)?),
+                _ => return Err("Wrong number of columns".to_string()),
+            }
+        }
+        Ok(values)
     }
     fn query(
         &self,
@@ -304,340 +348,58 @@ impl<E: Entry> Table<E> for MysqlTable<E> {
     ) -> Result<Vec<(Self::Key, E)>, String> {
         // Uses query type to decide wihch function to use and get the neccessary data
         //Because of the need for Self, the key is taken seperately, but isn't always needed
-        match &q {
-            QueryType::Lookup => {
-                //Key required
-                if key == None {
-                    return Err("Must have a key".to_string());
-                }
-                let result = self.lookup(key.unwrap()); //Unwraps after checking its okay
-                match result {
-                    Some(this_key) => {
-                        let mut result_vec: Vec<(Self::Key, E)> = Vec::new();
-                        result_vec.push((key.unwrap(), this_key)); //Unwraps after checking its okay
-                        Ok(result_vec)
-                    }
-                    None => Err("Invalid key".to_string()),
-                }
-            }
-            QueryType::Search(field, val, lim, sort_field, sort_dir, pg) => {
-                //No key required
-                //SELECT * FROM tb_name WHERE field_name = field_value
-                //Always start with opening mysql
-                //Opening mysql will never panic if the pool is done with the openmysql function
-                let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-                let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-                con.query(cmd_db).unwrap(); //Sending known command
-                                            //Establish Limit
-                let mut limit = lim;
-                if limit > &MAX_LIMIT {
-                    limit = &MAX_LIMIT;
-                }
-                let start_limit = limit * (pg - 1);
-                //Sort and limit string
-                let sort_string: String;
-                match &sort_dir {
-                    interface::SortDirection::Asc => sort_string = " ASC ".to_string(),
-                    interface::SortDirection::Desc => sort_string = " DESC ".to_string(),
-                }
-                let lim_cmd = " ORDER BY ".to_string()
-                    + &sort_field.to_string()
-                    + &sort_string
-                    + "LIMIT "
-                    + &start_limit.to_string()
-                    + ", "
-                    + &limit.to_string();
-
-                let cmd = "SELECT * FROM ".to_string()
-                    + &self.tb_name
-                    + " WHERE "
-                    + &field.to_owned().to_string()
-                    + " = "
-                    + &ivalue_to_mystring(&val)
-                    + &lim_cmd;
-
-                let vec_result: Result<Result<Vec<(MysqlTableKey, E)>, String>, my::Error> =
-                    con.prep_exec(cmd, ()).map(|result| {
-                        result
-                            .map(|x| match x {
-                                Ok(y) => Ok(y),
-                                Err(_) => return Err("Bad Query".to_string()),
-                            })
-                            .map(|row| {
-                                //Iterates through each row, panics if the schema is not followed
-                                let vec_data = my::Row::unwrap(row.unwrap()); //Unwraps after checking its okay
-                                                                              //Returns a vector of my::Values for each row
-                                let iter_data = vec_data.iter();
-                                let ivec: Vec<interface::Value> = iter_data
-                                    .map(|r| {
-                                        myvalue_to_ivalue(r).unwrap() //Changes each my::Value to interface::Value
-                                                                      //Panics if the database stores invalid data
-                                    })
-                                    .collect();
-                                let this_entry_result = E::from_fields(&ivec[1..]);
-                                let this_entry = match this_entry_result {
-                                    Err(string) => return Err(string),
-                                    Ok(x) => x,
-                                };
-                                let this_key = MysqlTableKey {
-                                    id: ivec[0].to_owned().itry_into().unwrap(),
-                                    //Panics if the database's first column isn't the key, which it *should* be
-                                    valid: true,
-                                };
-                                Ok((this_key, this_entry))
-                            })
-                            .collect()
-                    });
-                match vec_result {
-                    Ok(x) => x,
-                    Err(y) => Err(y.to_string()),
-                }
-            }
-            interface::QueryType::GetAll(lim, sort_field, sort_dir, pg) => {
-                //No key required
-                //Return all of the given table, but does require a limit
-                //Limit is checked against MAX_LIMIT
-
-                //Always start with opening mysql
-                //Opening mysql will never panic if done with the openmysql function
-                let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-                let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-                con.query(cmd_db).unwrap(); //Sending known command
-                                            //Establish limit
-                let mut limit = lim;
-                if limit > &MAX_LIMIT {
-                    limit = &MAX_LIMIT;
-                }
-                let start_limit = limit * (pg - 1);
-                //Sort and limit string
-                let sort_string: String;
-                match &sort_dir {
-                    interface::SortDirection::Asc => sort_string = " ASC ".to_string(),
-                    interface::SortDirection::Desc => sort_string = " DESC ".to_string(),
-                }
-                let lim_cmd = " ORDER BY ".to_string()
-                    + &sort_field.to_string()
-                    + &sort_string
-                    + "LIMIT "
-                    + &start_limit.to_string()
-                    + ", "
-                    + &limit.to_string();
-                //Create command to send to mySQL
-                let cmd = "SELECT * FROM ".to_string() + &self.tb_name + &lim_cmd;
-                let vec_result: Result<Result<Vec<(MysqlTableKey, E)>, String>, my::Error> =
-                    con.prep_exec(cmd, ()).map(|result| {
-                        result
-                            .map(|x| match x {
-                                Err(_e) => return Err("Bad Query".to_string()),
-                                Ok(y) => Ok(y),
-                            })
-                            .map(|row| {
-                                let vec_data = my::Row::unwrap(row.unwrap()); //Unwraps after checking its okay
-                                                                              //Returns a vector of my::Values for each row
-                                let iter_data = vec_data.iter();
-                                let ivec: Vec<interface::Value> = iter_data
-                                    .map(|r| {
-                                        myvalue_to_ivalue(r).unwrap() //Changes each my::Value to interface::Value
-                                                                      //Panics if the database stores invalid data
-                                    })
-                                    .collect();
-                                let this_entry_result = E::from_fields(&ivec[1..]);
-                                match this_entry_result {
-                                    Err(string) => return Err(string),
-                                    Ok(_) => (),
-                                }
-                                let this_entry = this_entry_result.unwrap(); //Unwraps after checking its okay
-                                let this_key = MysqlTableKey {
-                                    id: ivec[0].to_owned().itry_into().unwrap(),
-                                    valid: true,
-                                };
-                                Ok((this_key, this_entry))
-                            })
-                            .collect()
-                    });
-                match vec_result {
-                    Ok(x) => x,
-                    Err(y) => Err(y.to_string()),
-                }
-            }
-            QueryType::PartialSearch(field, val, lim, sort_field, sort_dir, pg) => {
-                //SELECT * FROM tb_name WHERE field_name LIKE *field_value*
-                //Always start with opening mysql
-                //Opening mysql will never panic if the pool is done with the openmysql function
-                let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-                let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-                con.query(cmd_db).unwrap(); //Sending known command
-
-                //Change the value to a proper search term.
-                //Proper searches need '% ... %'
-                let mut search_val: String;
-                //This is just a modified ivalue_to_mystring
-                match val {
-                    //Change bool to an if true => 1
-                    interface::Value::Boolean(_i8) => {
-                        search_val = "'%".to_string() + &val.to_owned().to_string() + "%'"
-                    }
-                    interface::Value::Integer(_i32) => {
-                        search_val = "'%".to_string() + &val.to_owned().to_string() + "%'"
-                    }
-                    interface::Value::Float(_f32) => {
-                        search_val = "'%".to_string() + &val.to_owned().to_string() + "%'"
-                    }
-                    //Strings need quotes around them. This assumes that all other characters have already been escaped
-                    interface::Value::String(_string) => {
-                        let temp: Vec<String> = val
-                            .to_owned()
-                            .to_string()
-                            .split('\'')
-                            .map({
-                                |x| x.to_string() //Creates a gap wherever there was a ' (deletes the ')
-                            })
-                            .collect();
-                        search_val = "'%".to_string() + &temp.join("\\\'") + "%'" //Adds a \' in between each gap and adds the %
-                    }
-                }
-                let mut limit = lim;
-                if limit > &MAX_LIMIT {
-                    limit = &MAX_LIMIT;
-                }
-                let start_limit = limit * (pg - 1);
-                //Sort and limit string
-                let sort_string = match &sort_dir {
-                    interface::SortDirection::Asc => " ASC ".to_string(),
-                    interface::SortDirection::Desc => " DESC ".to_string(),
-                };
-                let lim_cmd = " ORDER BY ".to_string()
-                    + &sort_field.to_string()
-                    + &sort_string
-                    + "LIMIT "
-                    + &start_limit.to_string()
-                    + ", "
-                    + &limit.to_string();
-                //Make and send the command
-                let cmd = "SELECT * FROM ".to_string()
-                    + &self.tb_name
-                    + " WHERE "
-                    + &field.to_owned().to_string()
-                    + " LIKE "
-                    + &search_val
-                    + &lim_cmd;
-                let vec_result: Result<Result<Vec<(MysqlTableKey, E)>, String>, my::Error> =
-                    con.prep_exec(cmd, ()).map(|result| {
-                        result
-                            .map(|x| match x {
-                                Err(_e) => return Err("Bad Query".to_string()),
-                                Ok(y) => Ok(y),
-                            })
-                            .map(|row| {
-                                let vec_data = my::Row::unwrap(row.unwrap()); //Unwraps after checking its okay
-                                                                              //Returns a vector of my::Values for each row
-                                let iter_data = vec_data.iter();
-                                let ivec: Vec<interface::Value> = iter_data
-                                    .map(|r| {
-                                        myvalue_to_ivalue(r).unwrap() //Changes each my::Value to interface::Value
-                                                                      //Panics if the database stores invalid data
-                                    })
-                                    .collect();
-                                let this_entry_result = E::from_fields(&ivec[1..]);
-                                match this_entry_result {
-                                    Err(string) => return Err(string),
-                                    Ok(_) => (),
-                                }
-                                let this_entry = this_entry_result.unwrap(); //Unwraps after checking its okay
-                                let this_key = MysqlTableKey {
-                                    id: ivec[0].to_owned().itry_into().unwrap(),
-                                    valid: true,
-                                };
-
-                                Ok((this_key, this_entry))
-                            })
-                            .collect()
-                    });
-                match vec_result {
-                    Ok(x) => x,
-                    Err(y) => Err(y.to_string()), //Converts the my::Error into a string
-                }
+        if let QueryType::Lookup = q {
+            //Key required
+            let key = match key {
+                Some(key) => key,
+                None => return Err("Must have a key".to_string()),
+            };
+            return match self.lookup(key) {
+                Some(this_entry) => Ok(vec![(key, this_entry)]),
+                None => Err("Invalid key".to_string()),
+            };
+        }
+        let sql = self.sql();
+        self.select_entries(sql.query_select(q, key.map(|key| key.id), sql.select())?)
+    }
+    //SELECT key, fields FROM tb_name WHERE ..., with only the given fields instead of all of them
+    fn query_fields(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+        fields: &[E::FieldNames],
+    ) -> Result<FieldRows<Self::Key>, String> {
+        let mut columns = vec![self.key_name.clone()];
+        let mut value_types = Vec::new();
+        for field in fields {
+            columns.push(field.to_string());
+            value_types.push(field_type::<E>(*field)?);
+        }
+        let lookup = matches!(q, QueryType::Lookup);
+        let select = Select::new(&self.tb_name, &columns);
+        let select = self.sql().query_select(q, key.map(|key| key.id), select)?;
+        let mut con = self.open()?;
+        let result = con.prep_exec(select.build(&MySql))?;
+        let mut rows = Vec::new();
+        for row in result {
+            let values = my::Row::unwrap(row.map_err(|e| e.to_string())?);
+            if values.len() != columns.len() {
+                return Err("Wrong number of columns".to_string());
             }
-            QueryType::MultiSearch(field_vec, val_vec, lim, sort_field, sort_dir, pg) => {
-                //No key required
-                //SELECT * FROM tb_name WHERE field_name[0] = field_value[0] AND field_name[1] = field_value[1]
-                //Always start with opening mysql
-                //Opening mysql will never panic if the pool is done with the openmysql function
-                let mut con = self.pool.get_conn().unwrap(); //Open connection to mySQL
-                let cmd_db = "USE ".to_owned() + &self.db_name; //Open the proper database
-                con.query(cmd_db).unwrap(); //Sending known command
-
-                let mut limit = lim;
-                if limit > &MAX_LIMIT {
-                    limit = &MAX_LIMIT;
-                }
-                let start_limit = limit * (pg - 1);
-                //Sort and limit string
-                let sort_string: String;
-                match &sort_dir {
-                    interface::SortDirection::Asc => sort_string = " ASC ".to_string(),
-                    interface::SortDirection::Desc => sort_string = " DESC ".to_string(),
-                }
-                let lim_cmd = " ORDER BY ".to_string()
-                    + &sort_field.to_string()
-                    + &sort_string
-                    + "LIMIT "
-                    + &start_limit.to_string()
-                    + ", "
-                    + &limit.to_string();
-                if field_vec.len() != val_vec.len() {
-                    return Err("Field and Value vectors do not pair".to_string());
-                }
-                //Put together the search
-                let mut search_vec = Vec::new();
-                let mut i = 0;
-                while i < field_vec.len() {
-                    search_vec
-                        .push(field_vec[i].to_string() + " = " + &ivalue_to_mystring(&val_vec[i]));
-                    i = i + 1;
-                }
-                let search_cmd = search_vec.join(" AND ");
-                let cmd = "SELECT * FROM ".to_string()
-                    + &self.tb_name
-                    + " WHERE "
-                    + &search_cmd
-                    + &lim_cmd;
-                let vec_result: Result<Result<Vec<(MysqlTableKey, E)>, String>, my::Error> =
-                    con.prep_exec(cmd, ()).map(|result| {
-                        result
-                            .map(|x| match x {
-                                Err(_e) => return Err("Bad Query".to_string()),
-                                Ok(y) => Ok(y),
-                            })
-                            .map(|row| {
-                                let vec_data = my::Row::unwrap(row.unwrap()); //Unwraps after checking its okay
-                                                                              //Returns a vector of my::Values for each row
-                                let iter_data = vec_data.iter();
-                                let ivec: Vec<interface::Value> = iter_data
-                                    .map(|r| {
-                                        myvalue_to_ivalue(r).unwrap() //Changes each my::Value to interface::Value
-                                                                      //Panics if the database stores invalid data
-                                    })
-                                    .collect();
-                                let this_entry_result = E::from_fields(&ivec[1..]);
-                                let this_entry = match this_entry_result {
-                                    Err(string) => return Err(string),
-                                    Ok(x) => x,
-                                };
-                                let this_key = MysqlTableKey {
-                                    id: ivec[0].to_owned().itry_into().unwrap(),
-                                    valid: true,
-                                };
-                                Ok((this_key, this_entry))
-                            })
-                            .collect()
-                    });
-                match vec_result {
-                    Ok(x) => x,
-                    Err(y) => Err(y.to_string()), //Converts my::Error to a string
-                }
+            let id: i32 = match myvalue_to_ivalue(&values[0]).and_then(|id| id.itry_into()) {
+                Ok(id) => id,
+                Err(_) => return Err("Key column is not an integer".to_string()),
+            };
+            let mut ivec = Vec::new();
+            for (value, value_type) in values[1..].iter().zip(&value_types) {
+                ivec.push(myvalue_to_type(value, *value_type)?);
             }
+            rows.push((MysqlTableKey { id, valid: true }, ivec));
         }
+        if lookup && rows.is_empty() {
+            return Err("Invalid key".to_string());
+        }
+        Ok(rows)
     }
 }
 
@@ -649,42 +411,517 @@ impl<E: Entry> MysqlTable<E> {
             tb_name: "".to_string(),
             db_name: "".to_string(),
             key_name: "".to_string(),
-            pool: pool,
+            pool,
             phantom: PhantomData,
+            transaction: None,
+            version_name: None,
+        }
+    }
+
+    /// Runs this table in the transaction that another table started with begin, so changes to
+    /// both tables are committed or rolled back together. Only the table that called begin can
+    /// commit or roll back, commit and rollback on this table give an Err. Once the transaction
+    /// ends this table goes back to running on its own.
+    pub fn use_transaction_of<F: Entry>(&mut self, other: &MysqlTable<F>) -> Result<(), String> {
+        match other.transaction {
+            Some(ref transaction) => {
+                self.transaction = Some(MysqlTransaction {
+                    shared: transaction.shared.clone(),
+                    owner: false,
+                });
+                Ok(())
+            }
+            None => Err("The other table has no open transaction".to_string()),
         }
     }
+
+    /// Reads every entry that matches the filter along with the entry of other that it refers to,
+    /// or None if other has no row with that key. Runs as one LEFT JOIN, so both tables have to
+    /// be on the same server.
+    pub fn join<F: Entry>(
+        &self,
+        other: &MysqlTable<F>,
+        reference: &Reference<E, F>,
+        filter: Filter<E>,
+    ) -> Result<Vec<(MysqlTableKey, E, Option<F>)>, String> {
+        //SELECT a.key, a.fields, b.key, b.fields FROM tb_name AS a LEFT JOIN other AS b
+        //ON a.reference = b.key WHERE filter
+        let columns = self.sql().columns();
+        let mut join = Join::new(
+            &self.tb_name,
+            &columns,
+            &other.tb_name,
+            &other.sql().columns(),
+        )
+        .other_database(&other.db_name)
+        .on(&reference.field().to_string(), &other.key_name);
+        if let Some(condition) = filter_condition(&filter) {
+            join = join.condition(condition);
+        }
+        let mut con = self.open()?;
+        let result = con.prep_exec(join.build(&MySql))?;
+        let column_count = columns.len();
+        let mut rows = Vec::new();
+        for row in result {
+            let mut values = my::Row::unwrap(row.map_err(|e| e.to_string())?);
+            let other_values = values.split_off(column_count.min(values.len()));
+            let (key, entry) = values_to_entry(values)?;
+            //Every column of other is NULL when no row of it matched
+            let found = match other_values.first() {
+                Some(my::Value::NULL) => None,
+                _ => Some(values_to_entry::<F>(other_values)?.1),
+            };
+            rows.push((key, entry, found));
+        }
+        Ok(rows)
+    }
+
+    //Sends the entries in as few multi-row INSERTs as the parameter limit allows, on one connection.
+    //The keys of a multi-row INSERT are only known when consecutive_keys says they are, otherwise
+    //the rows are inserted one at a time to get each key from last_insert_id
+    fn insert_entries(&self, entries: Vec<E>) -> Result<Vec<MysqlTableKey>, String> {
+        let columns: Vec<String> = E::get_field_names()
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        let chunk_size = (MAX_PARAMS / columns.len().max(1)).max(1);
+        let mut keys = Vec::new();
+        let mut con = self.open()?;
+        if !consecutive_keys(&mut con)? {
+            for entry in entries {
+                //INSERT INTO tb_name (fields) VALUES (values)
+                let insert = Insert::new(&self.tb_name)
+                    .columns(&columns)
+                    .row(entry.get_fields());
+                let id = con.prep_exec(insert.build(&MySql))?.last_insert_id();
+                if id == 0 || id > i32::MAX as u64 {
+                    return Err("Could not get keys for the inserted rows".to_string());
+                }
+                keys.push(MysqlTableKey {
+                    id: id as i32,
+                    valid: true,
+                });
+            }
+            return Ok(keys);
+        }
+        for chunk in entries.chunks(chunk_size) {
+            //INSERT INTO tb_name (fields) VALUES (values), (values), ...
+            let mut insert = Insert::new(&self.tb_name).columns(&columns);
+            for entry in chunk {
+                insert = insert.row(entry.get_fields());
+            }
+            let result = con.prep_exec(insert.build(&MySql))?;
+            let first_id = result.last_insert_id();
+            let last_id = first_id + chunk.len() as u64 - 1;
+            if result.affected_rows() != chunk.len() as u64
+                || first_id == 0
+                || last_id > i32::MAX as u64
+            {
+                return Err("Could not get keys for the inserted rows".to_string());
+            }
+            for id in first_id..=last_id {
+                keys.push(MysqlTableKey {
+                    id: id as i32,
+                    valid: true,
+                });
+            }
+        }
+        Ok(keys)
+    }
+
+    //Gets the connection of the open transaction, or one from the pool if there is none.
+    //Either way the connection is already using the table's database
+    fn open(&self) -> Result<Con<'_>, String> {
+        let mut guard = None;
+        if let Some(ref transaction) = self.transaction {
+            guard = Some(transaction.shared.lock().map_err(|e| e.to_string())?);
+        }
+        let mut con = match guard {
+            //The transaction might have been committed or rolled back by another table
+            Some(guard) if guard.is_some() => Con::Transaction(guard),
+            _ => Con::Pooled(Box::new(self.pool.get_conn().map_err(|e| e.to_string())?)), //Open connection to mySQL
+        };
+        let cmd_db = "USE ".to_string() + &MySql.quote_identifier(&self.db_name); //Open the proper database
+        con.query(cmd_db)?;
+        Ok(con)
+    }
+
+    //Whether the table is running in a transaction that has not been committed or rolled back yet
+    fn in_transaction(&self) -> bool {
+        match self.transaction {
+            Some(ref transaction) => match transaction.shared.lock() {
+                Ok(guard) => guard.is_some(),
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
+    //Takes the open transaction out of the table so it can be committed or rolled back
+    fn take_transaction(&mut self) -> Result<my::Transaction<'static>, String> {
+        if let Some(ref transaction) = self.transaction {
+            if !transaction.owner {
+                return Err("Only the table that called begin can end the transaction".to_string());
+            }
+        }
+        let transaction = match self.transaction.take() {
+            Some(transaction) => transaction,
+            None => return Err("No transaction is open".to_string()),
+        };
+        let mut guard = transaction.shared.lock().map_err(|e| e.to_string())?;
+        match guard.take() {
+            Some(my_transaction) => Ok(my_transaction),
+            None => Err("No transaction is open".to_string()),
+        }
+    }
+
+    //The statements that are the same for every SQL database
+    fn sql(&self) -> SqlTable<'_, E> {
+        SqlTable::new(
+            &MySql,
+            &self.tb_name,
+            &self.key_name,
+            self.version_name.as_deref(),
+        )
+    }
+
+    //Runs a SELECT from sql().select() and decodes every row that comes back
+    fn select_entries(&self, select: Select) -> Result<Vec<(MysqlTableKey, E)>, String> {
+        let mut con = self.open()?;
+        let result = con.prep_exec(select.build(&MySql))?;
+        result.map(row_to_entry).collect()
+    }
+
+    //Runs a statement that does not return rows and gives back the number of rows it changed
+    fn execute(&self, statement: Statement) -> Result<u64, String> {
+        let mut con = self.open()?;
+        let result = con.prep_exec(statement)?;
+        Ok(result.affected_rows())
+    }
+
+    //Runs an UPDATE and gives back the number of rows it matched, changed or not
+    fn execute_matched(&self, statement: Statement) -> Result<u64, String> {
+        let mut con = self.open()?;
+        let result = con.prep_exec(statement)?;
+        Ok(rows_matched(&result.info(), result.affected_rows()))
+    }
+}
+
+/**
+ *  Transactions hold on to one connection from the pool until they are committed or rolled back.
+ *  Every statement the table runs in between goes through that connection.
+*/
+impl<E: Entry> Transactional<E> for MysqlTable<E> {
+    fn begin(&mut self) -> Result<(), String> {
+        //A table that joined another table's transaction can start its own once that one ends
+        if self.in_transaction() {
+            return Err("A transaction is already open".to_string());
+        }
+        let my_transaction = self
+            .pool
+            .start_transaction(false, None, None)
+            .map_err(|e| e.to_string())?;
+        self.transaction = Some(MysqlTransaction {
+            shared: Arc::new(Mutex::new(Some(my_transaction))),
+            owner: true,
+        });
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), String> {
+        self.take_transaction()?.commit().map_err(|e| e.to_string())
+    }
+
+    fn rollback(&mut self) -> Result<(), String> {
+        self.take_transaction()?
+            .rollback()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/**
+ *  Every statement gets its own connection from the pool, or goes through the connection of the
+ *  open transaction, so nothing about the table changes when it is used.
+*/
+impl<E: Entry> interface::SharedTable<E> for MysqlTable<E> {
+    type Key = MysqlTableKey;
+
+    //Inserts a new row into the table and returns a key
+    //Uses QueryResult.last_insert_id to get a key back
+    fn insert(&self, entry: E) -> Self::Key {
+        //INSERT INTO tb_name (fields) VALUES (values), the key is left out so it auto increments
+        let mut insert = Insert::new(&self.tb_name);
+        for (field, value) in E::get_field_names().iter().zip(entry.get_fields()) {
+            insert = insert.value(&field.to_string(), value);
+        }
+        let this_id = self.open().and_then(|mut con| {
+            let result = con.prep_exec(insert.build(&MySql))?;
+            Ok(result.last_insert_id())
+        });
+        //Return a value based on good results
+        match this_id {
+            Ok(id) if id > 0 && id <= i32::MAX as u64 => MysqlTableKey {
+                id: id as i32,
+                valid: true,
+            },
+            _ => DEFAULT_KEY, //Returns 0 and an indicator of a bad key
+        }
+    }
+
+    fn lookup(&self, key: Self::Key) -> Option<E> {
+        Table::lookup(self, key)
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: interface::Value,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        Table::search(self, field_name, field_value)
+    }
+
+    fn update(&self, key: Self::Key, entry: E) -> Result<(), String> {
+        match self.execute_matched(self.sql().update_statement(key.id, &entry)) {
+            Ok(0) => Err("Key not in table".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove(&self, key: Self::Key) -> Result<(), String> {
+        //DELETE FROM tb_name WHERE key_name = key
+        let delete = Delete::new(&self.tb_name)
+            .where_equals(&self.key_name, interface::Value::Integer(key.id));
+        match self.execute(delete.build(&MySql)) {
+            Ok(0) => Err("Key not in table".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn contains(&self, key: Self::Key) -> bool {
+        Table::contains(self, key)
+    }
+
+    fn query(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        Table::query(self, q, key)
+    }
+}
+
+/**
+ *  Needs version_name to be set to a column that starts at 0. Every change the table makes to a
+ *  row adds one to it, but changes made to the database some other way do not.
+*/
+impl<E: Entry> Versioned<E> for MysqlTable<E> {
+    fn lookup_version(&self, key: Self::Key) -> Option<(E, u64)> {
+        //SELECT key, fields, version_name FROM tb_name WHERE key_name = key
+        let mut columns = self.sql().columns();
+        columns.push(self.version_name.clone()?);
+        let select = Select::new(&self.tb_name, &columns)
+            .where_equals(&self.key_name, interface::Value::Integer(key.id));
+        let mut con = self.open().ok()?;
+        let mut result = con.prep_exec(select.build(&MySql)).ok()?;
+        match result.next().map(row_to_versioned_entry) {
+            Some(Ok((_key, entry, version))) => Some((entry, version)),
+            _ => None,
+        }
+    }
+
+    fn update_if_version(&mut self, key: Self::Key, version: u64, entry: E) -> Result<u64, String> {
+        //UPDATE tb_name SET fields, version_name + 1 WHERE key_name = key AND version_name = version
+        let version_name = match self.version_name {
+            Some(ref version_name) => version_name.clone(),
+            None => return Err("The table has no version column".to_string()),
+        };
+        if version > i32::MAX as u64 {
+            return Err(VERSION_CONFLICT.to_string());
+        }
+        let update = self
+            .sql()
+            .entry_update(&entry)
+            .where_equals(&self.key_name, interface::Value::Integer(key.id))
+            .where_equals(&version_name, interface::Value::Integer(version as i32));
+        //The version always changes, so MySQL counts the row even if nothing else is different
+        match self.execute(update.build(&MySql))? {
+            0 if self.contains(key) => Err(VERSION_CONFLICT.to_string()),
+            0 => Err("Key not in table".to_string()),
+            _ => Ok(version + 1),
+        }
+    }
+}
+
+/**
+ *  An open transaction that one or more MysqlTables run their statements in. It is empty once
+ *  the table that started it commits or rolls back. Dropping it while it is still open rolls back.
+*/
+#[derive(Clone)]
+pub struct MysqlTransaction {
+    shared: Arc<Mutex<Option<my::Transaction<'static>>>>,
+    //Only the table that called begin can commit or roll back, use_transaction_of gives the others
+    //a copy with this set to false
+    owner: bool,
+}
+
+impl fmt::Debug for MysqlTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MysqlTransaction")
+    }
+}
+
+//A connection for a table to run statements on
+enum Con<'a> {
+    Pooled(Box<my::PooledConn>),
+    Transaction(MutexGuard<'a, Option<my::Transaction<'static>>>),
+}
+
+impl<'a> Con<'a> {
+    //Runs a statement without any parameters
+    fn query(&mut self, sql: String) -> Result<my::QueryResult<'_>, String> {
+        let result = match self {
+            Con::Pooled(con) => con.query(sql),
+            Con::Transaction(guard) => match guard.as_mut() {
+                Some(my_transaction) => my_transaction.query(sql),
+                None => return Err("The transaction is already finished".to_string()),
+            },
+        };
+        result.map_err(|e| e.to_string()) //Converts my::Error to a string
+    }
+
+    //Runs a statement with its parameters bound to the placeholders
+    fn prep_exec(&mut self, statement: Statement) -> Result<my::QueryResult<'_>, String> {
+        let params = to_params(&statement.params);
+        let result = match self {
+            Con::Pooled(con) => con.prep_exec(statement.sql, params),
+            Con::Transaction(guard) => match guard.as_mut() {
+                Some(my_transaction) => my_transaction.prep_exec(statement.sql, params),
+                None => return Err("The transaction is already finished".to_string()),
+            },
+        };
+        result.map_err(|e| e.to_string()) //Converts my::Error to a string
+    }
 }
 //Generic functions for mySQL
+//Turns one row from the table into a key and an entry
+//The first column must be the key, and the rest must be in the same order as E::get_field_names()
+fn row_to_entry<E: Entry>(row: Result<my::Row, my::Error>) -> Result<(MysqlTableKey, E), String> {
+    match row {
+        Ok(row) => values_to_entry(my::Row::unwrap(row)),
+        Err(e) => Err(e.to_string()), //Converts my::Error to a string
+    }
+}
+//Same as row_to_entry, for a row with the version column after the fields
+fn row_to_versioned_entry<E: Entry>(
+    row: Result<my::Row, my::Error>,
+) -> Result<(MysqlTableKey, E, u64), String> {
+    let values = match row {
+        Ok(row) => my::Row::unwrap(row),
+        Err(e) => return Err(e.to_string()),
+    };
+    let (id, entry, version) = sql_table::values_to_versioned_entry(to_ivalues(&values)?)?;
+    Ok((MysqlTableKey { id, valid: true }, entry, version))
+}
+
+fn values_to_entry<E: Entry>(values: Vec<my::Value>) -> Result<(MysqlTableKey, E), String> {
+    let (id, this_entry) = sql_table::values_to_entry(to_ivalues(&values)?)?;
+    Ok((MysqlTableKey { id, valid: true }, this_entry))
+}
+fn to_ivalues(values: &[my::Value]) -> Result<Vec<interface::Value>, String> {
+    values.iter().map(myvalue_to_ivalue).collect()
+}
 fn myvalue_to_ivalue(start: &my::Value) -> Result<interface::Value, String> {
-    let _temp: interface::Value;
+    let err_string = "Failed to convert mySQL Value".to_string();
     match start {
         //my::Value::TinyInt(_i64) 	=> Ok(interface::Value::Boolean	(my::from_value(start.to_owned()))),
-        my::Value::Int(_i64) => Ok(interface::Value::Integer(my::from_value(start.to_owned()))),
-        my::Value::Float(_f64) => Ok(interface::Value::Float(my::from_value(start.to_owned()))),
-        my::Value::Bytes(_vec) => Ok(interface::Value::String(my::from_value(start.to_owned()))),
-        my::Value::UInt(_u64) => Ok(interface::Value::Boolean(my::from_value(start.to_owned()))),
-        _ => Err("Failed to convert mySQL Value".to_string()),
+        my::Value::Int(_i64) => my::from_value_opt(start.to_owned())
+            .map(interface::Value::Integer)
+            .map_err(|_| err_string),
+        my::Value::Float(_f64) => my::from_value_opt(start.to_owned())
+            .map(interface::Value::Float)
+            .map_err(|_| err_string),
+        my::Value::Bytes(_vec) => my::from_value_opt(start.to_owned())
+            .map(interface::Value::String)
+            .map_err(|_| err_string),
+        my::Value::UInt(_u64) => my::from_value_opt(start.to_owned())
+            .map(interface::Value::Boolean)
+            .map_err(|_| err_string),
+        _ => Err(err_string),
     }
 }
-fn ivalue_to_mystring(data: &interface::Value) -> String {
-    match data {
-        interface::Value::Boolean(_i8) => data.to_owned().to_string(),
-        interface::Value::Integer(_i32) => data.to_owned().to_string(),
-        interface::Value::Float(_f32) => data.to_owned().to_string(),
-        interface::Value::Boolean(_bool) => (*_bool as u64).to_string(),
-        //Strings need quotes around them. This assumes that all other characters have already been escaped
-        interface::Value::String(_string) => {
-            let temp: Vec<String> = data
-                .to_owned()
-                .to_string()
-                .split('\'')
-                .map({
-                    |x| x.trim().to_string() //Creates a gap wherever there was a ' (deletes the ')
-                })
-                .collect();
-            "'".to_string() + &temp.join("\\\'") + "'" //Adds a \' in between each gap
+//Converts a value MySQL worked out, like a SUM or an AVG, to the type it should be. MySQL picks
+//its own type for these, like DECIMAL for the sum of INTs, so they go by the type they should be
+fn myvalue_to_type(start: &my::Value, value_type: ValueType) -> Result<interface::Value, String> {
+    let err_string = "Failed to convert mySQL Value".to_string();
+    let integer = match start {
+        my::Value::Int(i) => Some(*i),
+        my::Value::UInt(u) if *u <= i64::MAX as u64 => Some(*u as i64),
+        _ => None,
+    };
+    match (start, integer, value_type) {
+        //DECIMAL comes back as text
+        (my::Value::Bytes(bytes), _, _) => String::from_utf8(bytes.clone())
+            .ok()
+            .and_then(|text| interface::Value::from_text(&text, value_type))
+            .ok_or(err_string),
+        (_, Some(i), ValueType::Integer) if i == i64::from(i as i32) => {
+            Ok(interface::Value::Integer(i as i32))
+        }
+        (_, Some(i), ValueType::Float) => Ok(interface::Value::Float(i as f32)),
+        (_, Some(i), ValueType::Boolean) => Ok(interface::Value::Boolean(i != 0)),
+        (my::Value::Float(f), _, ValueType::Float) => Ok(interface::Value::Float(*f as f32)),
+        _ => Err(err_string),
+    }
+}
+//MySQL gives the rows of a multi-row INSERT consecutive keys starting at last_insert_id only when
+//auto_increment_increment is 1 and innodb_autoinc_lock_mode is 0 or 1. With lock mode 2 the keys
+//of other inserts running at the same time can end up in between, even inside a transaction
+fn consecutive_keys(con: &mut Con) -> Result<bool, String> {
+    let mut result =
+        con.query("SELECT @@auto_increment_increment, @@innodb_autoinc_lock_mode".to_string())?;
+    match result.next() {
+        Some(row) => Ok(settings_give_consecutive_keys(my::Row::unwrap(
+            row.map_err(|e| e.to_string())?,
+        ))),
+        None => Ok(false),
+    }
+}
+//Checks the auto_increment_increment and innodb_autoinc_lock_mode read by consecutive_keys
+fn settings_give_consecutive_keys(settings: Vec<my::Value>) -> bool {
+    let settings: Vec<Option<u64>> = settings
+        .into_iter()
+        .map(|value| my::from_value_opt(value).ok())
+        .collect();
+    matches!(settings.as_slice(), [Some(1), Some(0)] | [Some(1), Some(1)])
+}
+//MySQL only counts a row as affected by an UPDATE if a value actually changed, so 0 does not mean
+//the key is missing. The info of the result has the rows the WHERE matched, as
+//"Rows matched: 1  Changed: 0  Warnings: 0". Other statements have no info, and keep affected_rows
+fn rows_matched(info: &[u8], affected_rows: u64) -> u64 {
+    let info = String::from_utf8_lossy(info);
+    let mut words = info.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "matched:" {
+            if let Some(Ok(matched)) = words.next().map(str::parse) {
+                return matched;
+            }
         }
     }
+    affected_rows
+}
+//Converts the values bound to a statement into the parameters the mysql crate sends
+fn to_params(values: &[interface::Value]) -> Vec<my::Value> {
+    values.iter().map(ivalue_to_myvalue).collect()
+}
+fn ivalue_to_myvalue(data: &interface::Value) -> my::Value {
+    match data {
+        interface::Value::Integer(temp) => my::Value::from(*temp),
+        interface::Value::Float(temp) => my::Value::from(*temp),
+        interface::Value::String(temp) => my::Value::from(temp.clone()),
+        interface::Value::Boolean(temp) => my::Value::from(*temp),
+    }
 }
 //Opens a pooled connection to mySQL and returns the pool used to acess it
 //This only works when the database is on the same machine that it's being executed on
@@ -710,3 +947,164 @@ pub struct MysqlTableKey {
 }
 
 impl<E: Entry> Key<E> for MysqlTableKey {}
+
+#[cfg(test)]
+mod tests {
+
+    use interface::Value;
+    use interface::ValueType;
+    use my;
+    use my_types::myvalue_to_ivalue;
+    use my_types::myvalue_to_type;
+    use my_types::rows_matched;
+    use my_types::settings_give_consecutive_keys;
+    use my_types::values_to_entry;
+    use my_types::MysqlTable;
+    use my_types::MysqlTableKey;
+    use tests::Department;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_values_to_entry() {
+        let row = vec![
+            my::Value::Int(7),
+            my::Value::Bytes(b"Electrical and Computer Engineering".to_vec()),
+            my::Value::Bytes(b"ECE".to_vec()),
+        ];
+
+        let (key, department): (MysqlTableKey, Department) = values_to_entry(row).unwrap();
+
+        assert_eq!(key, MysqlTableKey { id: 7, valid: true });
+        assert_eq!(
+            department.name,
+            "Electrical and Computer Engineering".to_string()
+        );
+        assert_eq!(department.abreviation, "ECE".to_string());
+    }
+
+    #[test]
+    fn test_values_to_entry_empty_row() {
+        let result: Result<(MysqlTableKey, Department), String> = values_to_entry(Vec::new());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_values_to_entry_missing_column() {
+        let row = vec![my::Value::Int(7), my::Value::Bytes(b"ECE".to_vec())];
+
+        let result: Result<(MysqlTableKey, Department), String> = values_to_entry(row);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_values_to_entry_bad_key() {
+        let row = vec![
+            my::Value::Bytes(b"seven".to_vec()),
+            my::Value::Bytes(b"Electrical and Computer Engineering".to_vec()),
+            my::Value::Bytes(b"ECE".to_vec()),
+        ];
+
+        let result: Result<(MysqlTableKey, Department), String> = values_to_entry(row);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_values_to_entry_null_column() {
+        let row = vec![
+            my::Value::Int(7),
+            my::Value::NULL,
+            my::Value::Bytes(b"ECE".to_vec()),
+        ];
+
+        let result: Result<(MysqlTableKey, Department), String> = values_to_entry(row);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_myvalue_to_ivalue() {
+        assert_eq!(
+            myvalue_to_ivalue(&my::Value::Int(42)),
+            Ok(Value::Integer(42))
+        );
+        assert_eq!(
+            myvalue_to_ivalue(&my::Value::Bytes(b"hello".to_vec())),
+            Ok(Value::String("hello".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_myvalue_to_ivalue_out_of_range() {
+        assert!(myvalue_to_ivalue(&my::Value::Int(i64::MAX)).is_err());
+        assert!(myvalue_to_ivalue(&my::Value::UInt(7)).is_err());
+    }
+
+    #[test]
+    fn test_myvalue_to_type() {
+        assert_eq!(
+            myvalue_to_type(&my::Value::Bytes(b"12".to_vec()), ValueType::Integer),
+            Ok(Value::Integer(12))
+        );
+        assert_eq!(
+            myvalue_to_type(&my::Value::Bytes(b"3.5000".to_vec()), ValueType::Float),
+            Ok(Value::Float(3.5))
+        );
+        assert_eq!(
+            myvalue_to_type(&my::Value::Int(1), ValueType::Boolean),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            myvalue_to_type(&my::Value::Float(2.0), ValueType::Float),
+            Ok(Value::Float(2.0))
+        );
+        assert!(myvalue_to_type(&my::Value::Int(i64::MAX), ValueType::Integer).is_err());
+        assert!(myvalue_to_type(&my::Value::NULL, ValueType::Integer).is_err());
+    }
+
+    #[test]
+    fn test_rows_matched() {
+        assert_eq!(
+            rows_matched(b"Rows matched: 1  Changed: 0  Warnings: 0", 0),
+            1
+        );
+        assert_eq!(
+            rows_matched(b"Rows matched: 0  Changed: 0  Warnings: 0", 0),
+            0
+        );
+        assert_eq!(rows_matched(b"", 3), 3);
+    }
+
+    #[test]
+    fn test_settings_give_consecutive_keys() {
+        assert!(settings_give_consecutive_keys(vec![
+            my::Value::Bytes(b"1".to_vec()),
+            my::Value::Bytes(b"1".to_vec()),
+        ]));
+        assert!(settings_give_consecutive_keys(vec![
+            my::Value::UInt(1),
+            my::Value::UInt(0),
+        ]));
+        //Keys go up by 2, or other inserts can take keys in between
+        assert!(!settings_give_consecutive_keys(vec![
+            my::Value::UInt(2),
+            my::Value::UInt(1),
+        ]));
+        assert!(!settings_give_consecutive_keys(vec![
+            my::Value::UInt(1),
+            my::Value::UInt(2),
+        ]));
+        assert!(!settings_give_consecutive_keys(vec![
+            my::Value::UInt(1),
+            my::Value::NULL,
+        ]));
+    }
+
+    #[test]
+    fn test_mysql_table_is_send_sync() {
+        assert_send_sync::<MysqlTable<Department>>();
+    }
+}
@@ -1,17 +1,23 @@
 #[cfg(test)]
-mod mysql_test {
+mod tests {
     extern crate mysql as my;
     extern crate rpassword;
+    use async_table;
     use interface;
     use interface::Entry;
     use interface::ITryInto;
     use interface::Table;
+    use interface::Transactional;
+    use interface::Upserted;
+    use interface::Versioned;
+    use interface::VERSION_CONFLICT;
     use my_types;
     use std::fmt;
     use std::fmt::Display;
     use std::io;
     use std::marker::PhantomData;
     use std::str::FromStr;
+    use tokio::runtime;
 
     //The following is an example of how to use the my_types to both send and recieve data from mySQL.
     //Because the tables rely on follwing the schema very closely, here is the schema for this example
@@ -35,12 +41,14 @@ mod mysql_test {
     | firstname | varchar(32) | NO   |     | NULL    |                |
     | lastname  | varchar(32) | NO   |     | NULL    |                |
     | email     | varchar(64) | NO   |     | NULL    |                |
-    | bannerID  | bigint(20)  | NO   |     | NULL    |                |
+    | bannerID  | bigint(20)  | NO   | UNI | NULL    |                |
+    | version   | int(11)     | NO   |     | 0       |                |
     +-----------+-------------+------+-----+---------+----------------+
 
     */
 
     //Struct to hold the row data
+    #[allow(non_snake_case)]
     #[derive(Clone, PartialEq, Eq)]
     struct User {
         firstname: String,
@@ -49,6 +57,7 @@ mod mysql_test {
         bannerID: i32,
     }
 
+    #[allow(non_camel_case_types)]
     #[derive(PartialEq, Clone, Copy, Debug)]
     enum UserFields {
         firstname,
@@ -116,12 +125,20 @@ mod mysql_test {
                 UserFields::bannerID,
             ]
         }
+        fn get_field_types() -> Vec<interface::ValueType> {
+            vec![
+                interface::ValueType::String,
+                interface::ValueType::String,
+                interface::ValueType::String,
+                interface::ValueType::Integer,
+            ]
+        }
         fn get_fields(&self) -> Vec<interface::Value> {
             vec![
                 interface::Value::String(self.firstname.clone()),
                 interface::Value::String(self.lastname.clone()),
                 interface::Value::String(self.email.clone()),
-                interface::Value::Integer(self.bannerID.clone()),
+                interface::Value::Integer(self.bannerID),
             ]
         }
         fn get_field(&self, field_name: Self::FieldNames) -> Option<interface::Value> {
@@ -129,8 +146,7 @@ mod mysql_test {
                 UserFields::firstname => Some(interface::Value::String(self.firstname.clone())),
                 UserFields::lastname => Some(interface::Value::String(self.lastname.clone())),
                 UserFields::email => Some(interface::Value::String(self.email.clone())),
-                UserFields::bannerID => Some(interface::Value::Integer(self.bannerID.clone())),
-                _ => None,
+                UserFields::bannerID => Some(interface::Value::Integer(self.bannerID)),
             }
         }
     }
@@ -152,8 +168,10 @@ mod mysql_test {
             tb_name: "User".to_string(),
             db_name: "dbTest".to_string(),
             key_name: "userID".to_string(),
-            pool: pool,
+            pool,
             phantom: PhantomData,
+            transaction: None,
+            version_name: Some("version".to_string()),
         };
 
         //Create a student to send to the database
@@ -171,7 +189,7 @@ mod mysql_test {
             bannerID: 916181533,
         };
         //Testing basic functions
-        let nick_key: my_types::MysqlTableKey = Some(user_table.insert(nick_kz)).unwrap();
+        let nick_key: my_types::MysqlTableKey = user_table.insert(nick_kz);
         assert!(nick_key.valid);
 
         let nick_bool = user_table.contains(nick_key);
@@ -180,13 +198,13 @@ mod mysql_test {
         let nick_2 = user_table.lookup(nick_key).unwrap();
         assert_eq!(nick_2.firstname, "Nick");
 
-        let _nick_up_check = user_table.update(nick_key, nick_update).unwrap();
+        user_table.update(nick_key, nick_update).unwrap();
 
         // 												Create a generic value containing the string 'Nick'
         let nick_3 = user_table
             .search(
                 UserFields::firstname,
-                interface::Value::String("\'Nicholas\'".to_string()),
+                interface::Value::String("Nicholas".to_string()),
             )
             .unwrap()[0]
             .to_owned()
@@ -247,7 +265,198 @@ mod mysql_test {
             .unwrap();
         assert_eq!(q_multi[0].1.firstname, "Nick");
 
-        let _nick_del = user_table.remove(nick_key).unwrap(); //Delete Nick from db so it doesn't get clogged
-    }
+        //Testing projections, only the given fields come back
+        let q_fields = user_table
+            .query_fields(
+                interface::QueryType::Lookup,
+                Some(nick_key),
+                &[UserFields::email, UserFields::firstname],
+            )
+            .unwrap();
+        assert_eq!(
+            q_fields[0].1[1],
+            interface::Value::String("Nicholas".to_string())
+        );
+
+        user_table.remove(nick_key).unwrap(); //Delete Nick from db so it doesn't get clogged
+
+        //Testing transactions, nothing from a failed transaction should be left behind
+        let rolled_back: Result<my_types::MysqlTableKey, String> =
+            user_table.transaction(|table| {
+                let temp_key = table.insert(User {
+                    firstname: "Temp".to_string(),
+                    lastname: "User".to_string(),
+                    email: "temp@rowan.edu".to_string(),
+                    bannerID: 1,
+                });
+                table.remove(nick_key)?; //Nick was already removed, so this fails
+                Ok(temp_key)
+            });
+        assert!(rolled_back.is_err());
+        let temp_users = user_table
+            .search(
+                UserFields::firstname,
+                interface::Value::String("Temp".to_string()),
+            )
+            .unwrap();
+        assert!(temp_users.is_empty());
+
+        //Testing batches, the keys come back in the same order as the users
+        let batch_keys = user_table
+            .insert_many(vec![
+                User {
+                    firstname: "Batch".to_string(),
+                    lastname: "One".to_string(),
+                    email: "one@rowan.edu".to_string(),
+                    bannerID: 1,
+                },
+                User {
+                    firstname: "Batch".to_string(),
+                    lastname: "Two".to_string(),
+                    email: "two@rowan.edu".to_string(),
+                    bannerID: 2,
+                },
+            ])
+            .unwrap();
+        assert_eq!(batch_keys.len(), 2);
+        assert_eq!(user_table.lookup(batch_keys[1]).unwrap().lastname, "Two");
+        user_table.remove_many(batch_keys).unwrap();
+        let batch_users = user_table
+            .search(
+                UserFields::firstname,
+                interface::Value::String("Batch".to_string()),
+            )
+            .unwrap();
+        assert!(batch_users.is_empty());
+
+        //A batch that fails partway leaves none of its users behind
+        let duplicate = |lastname: &str| User {
+            firstname: "Duplicate".to_string(),
+            lastname: lastname.to_string(),
+            email: "duplicate@rowan.edu".to_string(),
+            bannerID: 3,
+        };
+        assert!(user_table
+            .insert_many(vec![duplicate("One"), duplicate("Two")])
+            .is_err());
+        let duplicate_users = user_table
+            .search(
+                UserFields::firstname,
+                interface::Value::String("Duplicate".to_string()),
+            )
+            .unwrap();
+        assert!(duplicate_users.is_empty());
+
+        //Testing scan, which streams every row with no page limit
+        let scanned: Result<Vec<_>, String> = user_table.iter().collect();
+        assert!(scanned.is_ok());
+        let nicks = user_table
+            .scan(interface::Filter::Equals(
+                UserFields::firstname,
+                interface::Value::String("Nicholas".to_string()),
+            ))
+            .count();
+        assert_eq!(nicks, 0); //Nick was removed above
+
+        //Testing AsyncTable, which runs each call on its own connection from the pool
+        let runtime = runtime::Builder::new_current_thread().build().unwrap();
+        let _guard = runtime.enter();
+        let async_table = async_table::SharedBlockingTable::new(user_table.clone());
+        let async_key = runtime
+            .block_on(async_table::AsyncTable::insert(
+                &async_table,
+                User {
+                    firstname: "Async".to_string(),
+                    lastname: "User".to_string(),
+                    email: "async@rowan.edu".to_string(),
+                    bannerID: 2,
+                },
+            ))
+            .unwrap();
+        let async_user = runtime
+            .block_on(async_table::AsyncTable::lookup(&async_table, async_key))
+            .unwrap();
+        assert_eq!(async_user.unwrap().firstname, "Async");
+        runtime
+            .block_on(async_table::AsyncTable::remove(&async_table, async_key))
+            .unwrap();
+
+        //Testing upsert, which matches on the unique bannerID column
+        let tim = User {
+            firstname: "Tim".to_string(),
+            lastname: "Hollabaugh".to_string(),
+            email: "hollabaugh@rowan.edu".to_string(),
+            bannerID: 3,
+        };
+        let (tim_key, tim_upserted) = user_table
+            .upsert(&[UserFields::bannerID], tim.clone())
+            .unwrap();
+        assert_eq!(tim_upserted, Upserted::Inserted);
+        let (found_key, found_upserted) = user_table
+            .upsert(
+                &[UserFields::bannerID],
+                User {
+                    email: "hollabaugh2@rowan.edu".to_string(),
+                    ..tim
+                },
+            )
+            .unwrap();
+        assert_eq!(found_upserted, Upserted::Updated);
+        assert_eq!(found_key, tim_key);
+        assert_eq!(
+            user_table.lookup(tim_key).unwrap().email,
+            "hollabaugh2@rowan.edu"
+        );
 
+        //Testing patch, only the named field changes
+        user_table
+            .patch(
+                tim_key,
+                vec![(
+                    UserFields::firstname,
+                    interface::Value::String("Timothy".to_string()),
+                )],
+            )
+            .unwrap();
+        let timothy = user_table.lookup(tim_key).unwrap();
+        assert_eq!(timothy.firstname, "Timothy");
+        assert_eq!(timothy.email, "hollabaugh2@rowan.edu");
+        assert!(user_table
+            .patch(
+                tim_key,
+                vec![(
+                    UserFields::bannerID,
+                    interface::Value::String("3".to_string()),
+                )],
+            )
+            .is_err());
+
+        //Testing versions, an update from an old version fails
+        let (timothy, version) = user_table.lookup_version(tim_key).unwrap();
+        assert_eq!(
+            user_table.update_if_version(tim_key, version, timothy.clone()),
+            Ok(version + 1)
+        );
+        assert_eq!(
+            user_table.update_if_version(tim_key, version, timothy),
+            Err(VERSION_CONFLICT.to_string())
+        );
+        user_table.remove(tim_key).unwrap();
+
+        //A key that is gone is an error, the same as PgTable and VecTable
+        assert_eq!(
+            user_table.patch(
+                tim_key,
+                vec![(
+                    UserFields::firstname,
+                    interface::Value::String("Tim".to_string()),
+                )],
+            ),
+            Err("Key not in table".to_string())
+        );
+        assert_eq!(
+            user_table.remove(tim_key),
+            Err("Key not in table".to_string())
+        );
+    }
 }
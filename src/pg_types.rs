@@ -0,0 +1,858 @@
+use dialect::Postgres;
+use interface;
+use interface::check_field_types;
+use interface::Entry;
+use interface::Filter;
+use interface::ITryInto;
+use interface::Key;
+use interface::QueryType;
+use interface::Scan;
+use interface::Table;
+use interface::Transactional;
+use interface::Upserted;
+use interface::Versioned;
+use interface::VERSION_CONFLICT;
+use pg;
+use pg::types::ToSql;
+use pg::types::Type;
+use r2d2;
+use r2d2_postgres::PostgresConnectionManager;
+use sql_builder::filter_condition;
+use sql_builder::Delete;
+use sql_builder::Insert;
+use sql_builder::Select;
+use sql_builder::Statement;
+use sql_builder::Update;
+use sql_builder::MAX_PARAMS;
+use sql_table;
+use sql_table::SqlTable;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::vec;
+
+pub static DEFAULT_KEY: PgTableKey = PgTableKey {
+    id: 0,
+    valid: false,
+};
+
+//The pool that PgTables get their connections from. Use open_postgres(...) to get one
+pub type PgPool = r2d2::Pool<PostgresConnectionManager<pg::NoTls>>;
+type PgConnection = r2d2::PooledConnection<PostgresConnectionManager<pg::NoTls>>;
+
+/**
+ *  A table in a PostgreSQL database. The key column should be a SERIAL or IDENTITY column so
+ *  PostgreSQL picks the key for new rows, and the rest of the columns should be named after the
+ *  fields of the entry.
+*/
+#[derive(Debug, Clone)]
+pub struct PgTable<E: Entry> {
+    //names are based on the postgres names
+    pub tb_name: String,
+    pub key_name: String,
+    pub pool: PgPool,
+    //The table never holds an E itself, so it is Send and Sync whatever E is
+    pub phantom: PhantomData<fn() -> E>,
+    //The transaction the table runs in, if any. Leave as None, begin and use_transaction_of set it
+    pub transaction: Option<PgTransaction>,
+    //The column that counts changes to each row, for Versioned. Should be INTEGER NOT NULL DEFAULT 0
+    pub version_name: Option<String>,
+}
+
+impl<E: Entry> Table<E> for PgTable<E> {
+    type Key = PgTableKey;
+
+    fn lookup(&self, key: Self::Key) -> Option<E> {
+        //SELECT key, fields FROM tb_name WHERE key_name = key
+        let select = self
+            .sql()
+            .select()
+            .where_equals(&self.key_name, interface::Value::Integer(key.id));
+        match self.select_entries(select) {
+            Ok(mut result_vec) => result_vec.pop().map(|(_key, entry)| entry),
+            Err(_) => None,
+        }
+    }
+
+    fn insert(&mut self, entry: E) -> Self::Key {
+        interface::SharedTable::insert(self, entry)
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: interface::Value,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        //SELECT key, fields FROM tb_name WHERE field_name = field_value
+        self.select_entries(
+            self.sql()
+                .select()
+                .where_equals(&field_name.to_string(), field_value),
+        )
+    }
+
+    fn update(&mut self, key: Self::Key, entry: E) -> Result<(), String> {
+        interface::SharedTable::update(self, key, entry)
+    }
+
+    fn remove(&mut self, key: Self::Key) -> Result<(), String> {
+        interface::SharedTable::remove(self, key)
+    }
+
+    //Sends the entries in as few multi-row INSERTs as the parameter limit allows, on one connection.
+    //RETURNING gives the keys back in the same order as the rows
+    fn insert_many(&mut self, entries: Vec<E>) -> Result<Vec<Self::Key>, String> {
+        let columns: Vec<String> = E::get_field_names()
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        let chunk_size = (MAX_PARAMS / columns.len().max(1)).max(1);
+        let mut keys = Vec::new();
+        if entries.is_empty() {
+            return Ok(keys);
+        }
+        let mut con = self.open()?;
+        for chunk in entries.chunks(chunk_size) {
+            //INSERT INTO tb_name (fields) VALUES (values), (values), ... RETURNING key_name
+            let mut insert = Insert::new(&self.tb_name).columns(&columns);
+            for entry in chunk {
+                insert = insert.row(entry.get_fields());
+            }
+            let insert = insert.returning(&self.key_name);
+            for row in con.query_rows(insert.build(&Postgres))? {
+                let id = pgvalue_to_ivalue(&row, 0)?.itry_into()?;
+                keys.push(PgTableKey { id, valid: true });
+            }
+        }
+        Ok(keys)
+    }
+
+    //Runs every UPDATE on the same connection
+    fn update_many(&mut self, updates: Vec<(Self::Key, E)>) -> Result<(), String> {
+        let mut con = self.open()?;
+        for (key, entry) in updates {
+            if con.execute(self.sql().update_statement(key.id, &entry))? == 0 {
+                return Err("Key not in table".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    //SELECT key, fields FROM tb_name WHERE filter, read through a cursor on a connection of its own.
+    //Inside a transaction the rows are read all at once instead, since the connection of the
+    //transaction is needed for the other statements
+    fn scan<'a>(&'a self, filter: Filter<E>) -> Scan<'a, Self::Key, E> {
+        let mut select = self.sql().select();
+        if let Some(condition) = filter_condition(&filter) {
+            select = select.condition(condition);
+        }
+        if self.in_transaction() {
+            return match self.select_entries(select) {
+                Ok(result_vec) => Box::new(result_vec.into_iter().map(Ok)),
+                Err(e) => Box::new(iter::once(Err(e))),
+            };
+        }
+        match PgScan::open(&self.pool, select.build(&Postgres)) {
+            Ok(scan) => Box::new(scan),
+            Err(e) => Box::new(iter::once(Err(e))),
+        }
+    }
+
+    //UPDATE tb_name SET field = value, ... WHERE filter
+    //Counts every row the filter matched
+    fn update_where(
+        &mut self,
+        filter: Filter<E>,
+        assignments: Vec<(E::FieldNames, interface::Value)>,
+    ) -> Result<u64, String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let mut update = self.sql().versioned(Update::new(&self.tb_name));
+        for (field, value) in assignments {
+            update = update.set(&field.to_string(), value);
+        }
+        if let Some(condition) = filter_condition(&filter) {
+            update = update.condition(condition);
+        }
+        self.execute(update.build(&Postgres))
+    }
+
+    //UPDATE tb_name SET field = value, ... WHERE key_name = key
+    fn patch(
+        &mut self,
+        key: Self::Key,
+        assignments: Vec<(E::FieldNames, interface::Value)>,
+    ) -> Result<(), String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let mut update = self.sql().versioned(Update::new(&self.tb_name));
+        for (field, value) in assignments {
+            update = update.set(&field.to_string(), value);
+        }
+        let update = update.where_equals(&self.key_name, interface::Value::Integer(key.id));
+        match self.execute(update.build(&Postgres)) {
+            Ok(0) => Err("Key not in table".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove_where(&mut self, filter: Filter<E>) -> Result<u64, String> {
+        //DELETE FROM tb_name WHERE filter
+        let mut delete = Delete::new(&self.tb_name);
+        if let Some(condition) = filter_condition(&filter) {
+            delete = delete.condition(condition);
+        }
+        self.execute(delete.build(&Postgres))
+    }
+
+    //INSERT ... ON CONFLICT DO UPDATE, which needs a UNIQUE index on the match fields.
+    //xmax is only set on a row that already existed, so (xmax = 0) tells if the row is new
+    fn upsert(
+        &mut self,
+        match_fields: &[E::FieldNames],
+        entry: E,
+    ) -> Result<(Self::Key, Upserted), String> {
+        if match_fields.is_empty() {
+            return Err("Need at least one field to match on".to_string());
+        }
+        let match_columns: Vec<String> =
+            match_fields.iter().map(|field| field.to_string()).collect();
+        let mut insert = Insert::new(&self.tb_name);
+        for (field, value) in E::get_field_names().iter().zip(entry.get_fields()) {
+            insert = insert.value(&field.to_string(), value);
+        }
+        let mut insert = insert
+            .on_conflict_update(&match_columns)
+            .returning(&self.key_name)
+            .also_returning("(xmax = 0)");
+        if let Some(ref version_name) = self.version_name {
+            insert = insert.increment(version_name);
+        }
+        let rows = self.query_rows(insert.build(&Postgres))?;
+        let row = match rows.first() {
+            Some(row) => row,
+            None => return Err("Upsert did not return a key".to_string()),
+        };
+        let id = pgvalue_to_ivalue(row, 0)?.itry_into()?;
+        let inserted: bool = pgvalue_to_ivalue(row, 1)?.itry_into()?;
+        let upserted = if inserted {
+            Upserted::Inserted
+        } else {
+            Upserted::Updated
+        };
+        Ok((PgTableKey { id, valid: true }, upserted))
+    }
+
+    fn remove_many(&mut self, keys: Vec<Self::Key>) -> Result<(), String> {
+        //DELETE FROM tb_name WHERE key_name IN (keys)
+        let mut ids: Vec<i32> = keys.iter().map(|key| key.id).collect();
+        ids.sort();
+        ids.dedup();
+        let mut removed = 0;
+        let mut con = self.open()?;
+        for chunk in ids.chunks(MAX_PARAMS) {
+            let delete = Delete::new(&self.tb_name).where_in(
+                &self.key_name,
+                chunk
+                    .iter()
+                    .map(|id| interface::Value::Integer(*id))
+                    .collect(),
+            );
+            removed += con.execute(delete.build(&Postgres))?;
+        }
+        if removed == ids.len() as u64 {
+            Ok(())
+        } else {
+            Err("Key not in table".to_string())
+        }
+    }
+
+    fn contains(&self, key: Self::Key) -> bool {
+        match self.select_entries(
+            self.sql()
+                .select()
+                .where_equals(&self.key_name, interface::Value::Integer(key.id)),
+        ) {
+            Ok(result_vec) => !result_vec.is_empty(),
+            Err(_) => false,
+        }
+    }
+
+    fn query(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        if let QueryType::Lookup = q {
+            let key = match key {
+                Some(key) => key,
+                None => return Err("Must have a key".to_string()),
+            };
+            return match self.lookup(key) {
+                Some(this_entry) => Ok(vec![(key, this_entry)]),
+                None => Err("Invalid key".to_string()),
+            };
+        }
+        let sql = self.sql();
+        self.select_entries(sql.query_select(q, key.map(|key| key.id), sql.select())?)
+    }
+}
+
+impl<E: Entry> PgTable<E> {
+    pub fn new(pool: PgPool) -> PgTable<E> {
+        PgTable {
+            tb_name: "".to_string(),
+            key_name: "".to_string(),
+            pool,
+            phantom: PhantomData,
+            transaction: None,
+            version_name: None,
+        }
+    }
+
+    /// Runs this table in the transaction that another table started with begin, so changes to
+    /// both tables are committed or rolled back together. Only the table that called begin can
+    /// commit or roll back, commit and rollback on this table give an Err. Once the transaction
+    /// ends this table goes back to running on its own.
+    pub fn use_transaction_of<F: Entry>(&mut self, other: &PgTable<F>) -> Result<(), String> {
+        match other.transaction {
+            Some(ref transaction) => {
+                self.transaction = Some(PgTransaction {
+                    shared: transaction.shared.clone(),
+                    owner: false,
+                });
+                Ok(())
+            }
+            None => Err("The other table has no open transaction".to_string()),
+        }
+    }
+
+    //Gets the connection of the open transaction, or one from the pool if there is none
+    fn open(&self) -> Result<Con<'_>, String> {
+        if let Some(ref transaction) = self.transaction {
+            let guard = transaction.shared.lock().map_err(|e| e.to_string())?;
+            //The transaction might have been committed or rolled back by another table
+            if guard.is_some() {
+                return Ok(Con::Transaction(guard));
+            }
+        }
+        match self.pool.get() {
+            Ok(client) => Ok(Con::Pooled(Box::new(client))),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    //Whether the table is running in a transaction that has not been committed or rolled back yet
+    fn in_transaction(&self) -> bool {
+        match self.transaction {
+            Some(ref transaction) => match transaction.shared.lock() {
+                Ok(guard) => guard.is_some(),
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
+    //Takes the open transaction out of the table and ends it with COMMIT or ROLLBACK
+    fn finish_transaction(&mut self, sql: &str) -> Result<(), String> {
+        if let Some(ref transaction) = self.transaction {
+            if !transaction.owner {
+                return Err("Only the table that called begin can end the transaction".to_string());
+            }
+        }
+        let transaction = match self.transaction.take() {
+            Some(transaction) => transaction,
+            None => return Err("No transaction is open".to_string()),
+        };
+        let mut guard = transaction.shared.lock().map_err(|e| e.to_string())?;
+        match guard.take() {
+            Some(open_transaction) => open_transaction.finish(sql),
+            None => Err("No transaction is open".to_string()),
+        }
+    }
+
+    //The statements that are the same for every SQL database
+    fn sql(&self) -> SqlTable<'_, E> {
+        SqlTable::new(
+            &Postgres,
+            &self.tb_name,
+            &self.key_name,
+            self.version_name.as_deref(),
+        )
+    }
+
+    //Runs a SELECT from sql().select() and decodes every row that comes back
+    fn select_entries(&self, select: Select) -> Result<Vec<(PgTableKey, E)>, String> {
+        let rows = self.query_rows(select.build(&Postgres))?;
+        rows.iter().map(row_to_entry).collect()
+    }
+
+    //Runs a statement that returns rows
+    fn query_rows(&self, statement: Statement) -> Result<Vec<pg::Row>, String> {
+        self.open()?.query_rows(statement)
+    }
+
+    //Runs a statement that does not return rows and gives back the number of rows it changed
+    fn execute(&self, statement: Statement) -> Result<u64, String> {
+        self.open()?.execute(statement)
+    }
+}
+
+/**
+ *  Transactions hold on to one connection from the pool until they are committed or rolled back.
+ *  Every statement the table runs in between goes through that connection.
+*/
+impl<E: Entry> Transactional<E> for PgTable<E> {
+    fn begin(&mut self) -> Result<(), String> {
+        //A table that joined another table's transaction can start its own once that one ends
+        if self.in_transaction() {
+            return Err("A transaction is already open".to_string());
+        }
+        let mut client = self.pool.get().map_err(|e| e.to_string())?;
+        client.batch_execute("BEGIN").map_err(|e| e.to_string())?;
+        let open_transaction = OpenTransaction {
+            client,
+            finished: false,
+        };
+        self.transaction = Some(PgTransaction {
+            shared: Arc::new(Mutex::new(Some(open_transaction))),
+            owner: true,
+        });
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), String> {
+        self.finish_transaction("COMMIT")
+    }
+
+    fn rollback(&mut self) -> Result<(), String> {
+        self.finish_transaction("ROLLBACK")
+    }
+}
+
+/**
+ *  Every statement gets its own connection from the pool, or goes through the connection of the
+ *  open transaction, so nothing about the table changes when it is used.
+*/
+impl<E: Entry> interface::SharedTable<E> for PgTable<E> {
+    type Key = PgTableKey;
+
+    //Inserts a new row and uses RETURNING to get the key postgres made for it
+    fn insert(&self, entry: E) -> Self::Key {
+        //INSERT INTO tb_name (fields) VALUES (values) RETURNING key_name
+        let mut insert = Insert::new(&self.tb_name);
+        for (field, value) in E::get_field_names().iter().zip(entry.get_fields()) {
+            insert = insert.value(&field.to_string(), value);
+        }
+        let insert = insert.returning(&self.key_name);
+        let this_id =
+            self.query_rows(insert.build(&Postgres))
+                .and_then(|rows| match rows.first() {
+                    Some(row) => pgvalue_to_ivalue(row, 0)?.itry_into(),
+                    None => Err("Insert did not return a key".to_string()),
+                });
+        match this_id {
+            Ok(id) => PgTableKey { id, valid: true },
+            Err(_) => DEFAULT_KEY, //Returns 0 and an indicator of a bad key
+        }
+    }
+
+    fn lookup(&self, key: Self::Key) -> Option<E> {
+        Table::lookup(self, key)
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: interface::Value,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        Table::search(self, field_name, field_value)
+    }
+
+    fn update(&self, key: Self::Key, entry: E) -> Result<(), String> {
+        match self.execute(self.sql().update_statement(key.id, &entry)) {
+            Ok(0) => Err("Key not in table".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove(&self, key: Self::Key) -> Result<(), String> {
+        //DELETE FROM tb_name WHERE key_name = key
+        let delete = Delete::new(&self.tb_name)
+            .where_equals(&self.key_name, interface::Value::Integer(key.id));
+        match self.execute(delete.build(&Postgres)) {
+            Ok(0) => Err("Key not in table".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn contains(&self, key: Self::Key) -> bool {
+        Table::contains(self, key)
+    }
+
+    fn query(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        Table::query(self, q, key)
+    }
+}
+
+/**
+ *  Needs version_name to be set to a column that starts at 0. Every change the table makes to a
+ *  row adds one to it, but changes made to the database some other way do not.
+*/
+impl<E: Entry> Versioned<E> for PgTable<E> {
+    fn lookup_version(&self, key: Self::Key) -> Option<(E, u64)> {
+        //SELECT key, fields, version_name FROM tb_name WHERE key_name = key
+        let mut columns = self.sql().columns();
+        columns.push(self.version_name.clone()?);
+        let select = Select::new(&self.tb_name, &columns)
+            .where_equals(&self.key_name, interface::Value::Integer(key.id));
+        let rows = self.query_rows(select.build(&Postgres)).ok()?;
+        match rows.first().map(row_to_versioned_entry) {
+            Some(Ok((_key, entry, version))) => Some((entry, version)),
+            _ => None,
+        }
+    }
+
+    fn update_if_version(&mut self, key: Self::Key, version: u64, entry: E) -> Result<u64, String> {
+        //UPDATE tb_name SET fields, version_name + 1 WHERE key_name = key AND version_name = version
+        let version_name = match self.version_name {
+            Some(ref version_name) => version_name.clone(),
+            None => return Err("The table has no version column".to_string()),
+        };
+        if version > i32::MAX as u64 {
+            return Err(VERSION_CONFLICT.to_string());
+        }
+        let update = self
+            .sql()
+            .entry_update(&entry)
+            .where_equals(&self.key_name, interface::Value::Integer(key.id))
+            .where_equals(&version_name, interface::Value::Integer(version as i32));
+        match self.execute(update.build(&Postgres))? {
+            0 if self.contains(key) => Err(VERSION_CONFLICT.to_string()),
+            0 => Err("Key not in table".to_string()),
+            _ => Ok(version + 1),
+        }
+    }
+}
+
+/**
+ *  An open transaction that one or more PgTables run their statements in. It is empty once the
+ *  table that started it commits or rolls back. Dropping it while it is still open rolls back.
+*/
+#[derive(Clone)]
+pub struct PgTransaction {
+    shared: Arc<Mutex<Option<OpenTransaction>>>,
+    //Only the table that called begin can commit or roll back, use_transaction_of gives the others
+    //a copy with this set to false
+    owner: bool,
+}
+
+impl fmt::Debug for PgTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PgTransaction")
+    }
+}
+
+//A connection with BEGIN run on it. It is rolled back if it is dropped before finish is called,
+//so it never goes back to the pool in the middle of a transaction
+struct OpenTransaction {
+    client: PgConnection,
+    finished: bool,
+}
+
+impl OpenTransaction {
+    fn finish(mut self, sql: &str) -> Result<(), String> {
+        self.finished = true;
+        self.client.batch_execute(sql).map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for OpenTransaction {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.client.batch_execute("ROLLBACK");
+        }
+    }
+}
+
+//A connection for a table to run statements on
+enum Con<'a> {
+    Pooled(Box<PgConnection>),
+    Transaction(MutexGuard<'a, Option<OpenTransaction>>),
+}
+
+impl<'a> Con<'a> {
+    fn client(&mut self) -> Result<&mut pg::Client, String> {
+        match self {
+            Con::Pooled(client) => Ok(client),
+            Con::Transaction(guard) => match guard.as_mut() {
+                Some(open_transaction) => Ok(&mut open_transaction.client),
+                None => Err("The transaction is already finished".to_string()),
+            },
+        }
+    }
+
+    //Runs a statement that returns rows
+    fn query_rows(&mut self, statement: Statement) -> Result<Vec<pg::Row>, String> {
+        query_rows(self.client()?, statement)
+    }
+
+    //Runs a statement that does not return rows and gives back the number of rows it changed
+    fn execute(&mut self, statement: Statement) -> Result<u64, String> {
+        execute(self.client()?, statement)
+    }
+}
+
+fn query_rows(client: &mut pg::Client, statement: Statement) -> Result<Vec<pg::Row>, String> {
+    let prepared = client.prepare(&statement.sql).map_err(|e| e.to_string())?;
+    let params = to_params(&statement.params, prepared.params())?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p.as_ref()).collect();
+    client
+        .query(&prepared, &param_refs)
+        .map_err(|e| e.to_string())
+}
+
+fn execute(client: &mut pg::Client, statement: Statement) -> Result<u64, String> {
+    let prepared = client.prepare(&statement.sql).map_err(|e| e.to_string())?;
+    let params = to_params(&statement.params, prepared.params())?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p.as_ref()).collect();
+    client
+        .execute(&prepared, &param_refs)
+        .map_err(|e| e.to_string())
+}
+
+//How many rows a scan fetches from its cursor at a time
+static SCAN_BATCH: usize = 1000;
+
+/**
+ *  The rows of a scan, fetched from a cursor a batch at a time so they are never all in memory.
+ *  The cursor needs a transaction, which is ended once the last row is fetched or the scan is
+ *  dropped.
+*/
+struct PgScan<E: Entry> {
+    client: PgConnection,
+    rows: vec::IntoIter<pg::Row>,
+    finished: bool,
+    phantom: PhantomData<E>,
+}
+
+impl<E: Entry> PgScan<E> {
+    fn open(pool: &PgPool, select: Statement) -> Result<PgScan<E>, String> {
+        let mut scan = PgScan {
+            client: pool.get().map_err(|e| e.to_string())?,
+            rows: Vec::new().into_iter(),
+            finished: false,
+            phantom: PhantomData,
+        };
+        scan.client
+            .batch_execute("BEGIN")
+            .map_err(|e| e.to_string())?;
+        let declare = Statement {
+            sql: "DECLARE database_lib_scan NO SCROLL CURSOR FOR ".to_string() + &select.sql,
+            params: select.params,
+        };
+        execute(&mut scan.client, declare)?;
+        Ok(scan)
+    }
+
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            let _ = self.client.batch_execute("COMMIT");
+        }
+    }
+}
+
+impl<E: Entry> Iterator for PgScan<E> {
+    type Item = Result<(PgTableKey, E), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.rows.next() {
+                return Some(row_to_entry(&row));
+            }
+            if self.finished {
+                return None;
+            }
+            let fetch = "FETCH ".to_string() + &SCAN_BATCH.to_string() + " FROM database_lib_scan";
+            match self.client.query(fetch.as_str(), &[]) {
+                Ok(rows) => {
+                    if rows.len() < SCAN_BATCH {
+                        self.finish();
+                    }
+                    self.rows = rows.into_iter();
+                }
+                Err(e) => {
+                    self.finish();
+                    return Some(Err(e.to_string()));
+                }
+            }
+        }
+    }
+}
+
+impl<E: Entry> Drop for PgScan<E> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+//Turns one row from the table into a key and an entry
+//The first column must be the key, and the rest must be in the same order as E::get_field_names()
+fn row_to_entry<E: Entry>(row: &pg::Row) -> Result<(PgTableKey, E), String> {
+    let (id, this_entry) = sql_table::values_to_entry(row_values(row)?)?;
+    Ok((PgTableKey { id, valid: true }, this_entry))
+}
+
+//Same as row_to_entry, for a row with the version column after the fields
+fn row_to_versioned_entry<E: Entry>(row: &pg::Row) -> Result<(PgTableKey, E, u64), String> {
+    let (id, entry, version) = sql_table::values_to_versioned_entry(row_values(row)?)?;
+    Ok((PgTableKey { id, valid: true }, entry, version))
+}
+
+//Reads every column of a row
+fn row_values(row: &pg::Row) -> Result<Vec<interface::Value>, String> {
+    (0..row.len()).map(|i| pgvalue_to_ivalue(row, i)).collect()
+}
+
+//Reads one column of a row, based on the column's postgres type
+fn pgvalue_to_ivalue(row: &pg::Row, i: usize) -> Result<interface::Value, String> {
+    let err_string = "Failed to convert postgres Value".to_string();
+    let ty = row.columns()[i].type_().clone();
+    if ty == Type::INT2 {
+        row.try_get::<_, i16>(i)
+            .map(|v| interface::Value::Integer(i32::from(v)))
+            .map_err(|_| err_string)
+    } else if ty == Type::INT4 {
+        row.try_get::<_, i32>(i)
+            .map(interface::Value::Integer)
+            .map_err(|_| err_string)
+    } else if ty == Type::INT8 {
+        match row.try_get::<_, i64>(i).map(i32::try_from) {
+            Ok(Ok(v)) => Ok(interface::Value::Integer(v)),
+            _ => Err(err_string),
+        }
+    } else if ty == Type::FLOAT4 {
+        row.try_get::<_, f32>(i)
+            .map(interface::Value::Float)
+            .map_err(|_| err_string)
+    } else if ty == Type::FLOAT8 {
+        row.try_get::<_, f64>(i)
+            .map(|v| interface::Value::Float(v as f32))
+            .map_err(|_| err_string)
+    } else if ty == Type::BOOL {
+        row.try_get::<_, bool>(i)
+            .map(interface::Value::Boolean)
+            .map_err(|_| err_string)
+    } else if ty == Type::TEXT || ty == Type::VARCHAR || ty == Type::BPCHAR || ty == Type::NAME {
+        row.try_get::<_, String>(i)
+            .map(interface::Value::String)
+            .map_err(|_| err_string)
+    } else {
+        Err(err_string)
+    }
+}
+
+//Converts the values bound to a statement into the rust types postgres expects for each
+//placeholder. Postgres will not turn an i32 into a BIGINT on its own, so it is done here.
+fn to_params(
+    values: &[interface::Value],
+    types: &[Type],
+) -> Result<Vec<Box<dyn ToSql + Sync>>, String> {
+    let mut params: Vec<Box<dyn ToSql + Sync>> = Vec::new();
+    for (value, ty) in values.iter().zip(types) {
+        params.push(ivalue_to_pgvalue(value, ty)?);
+    }
+    Ok(params)
+}
+fn ivalue_to_pgvalue(data: &interface::Value, ty: &Type) -> Result<Box<dyn ToSql + Sync>, String> {
+    let err_string =
+        "Value ".to_string() + &data.to_string() + " does not fit a " + ty.name() + " column";
+    match data {
+        interface::Value::Integer(temp) => {
+            if *ty == Type::INT2 {
+                i16::try_from(*temp)
+                    .map(|v| Box::new(v) as Box<dyn ToSql + Sync>)
+                    .map_err(|_| err_string)
+            } else if *ty == Type::INT4 {
+                Ok(Box::new(*temp))
+            } else if *ty == Type::INT8 {
+                Ok(Box::new(i64::from(*temp)))
+            } else {
+                Err(err_string)
+            }
+        }
+        interface::Value::Float(temp) => {
+            if *ty == Type::FLOAT4 {
+                Ok(Box::new(*temp))
+            } else if *ty == Type::FLOAT8 {
+                Ok(Box::new(f64::from(*temp)))
+            } else {
+                Err(err_string)
+            }
+        }
+        interface::Value::String(temp) => {
+            if *ty == Type::TEXT || *ty == Type::VARCHAR || *ty == Type::BPCHAR || *ty == Type::NAME
+            {
+                Ok(Box::new(temp.clone()))
+            } else {
+                Err(err_string)
+            }
+        }
+        interface::Value::Boolean(temp) => {
+            if *ty == Type::BOOL {
+                Ok(Box::new(*temp))
+            } else {
+                Err(err_string)
+            }
+        }
+    }
+}
+
+//Opens a pool of connections to PostgreSQL and returns the pool used to access it
+//The params are a postgres connection string, like "host=localhost user=postgres dbname=people"
+pub fn open_postgres(params: &str) -> Result<PgPool, String> {
+    let config: pg::Config = params.parse().map_err(|e: pg::Error| e.to_string())?;
+    let manager = PostgresConnectionManager::new(config, pg::NoTls);
+    match r2d2::Pool::new(manager) {
+        Ok(k) => Ok(k),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct PgTableKey {
+    pub id: i32,
+    pub valid: bool,
+}
+
+impl<E: Entry> Key<E> for PgTableKey {}
+
+#[cfg(test)]
+mod tests {
+
+    use pg_types::PgTable;
+    use tests::Department;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_pg_table_is_send_sync() {
+        assert_send_sync::<PgTable<Department>>();
+    }
+}
@@ -0,0 +1,431 @@
+#[cfg(test)]
+mod tests {
+    use async_table;
+    use interface;
+    use interface::Filter;
+    use interface::QueryType;
+    use interface::SortDirection;
+    use interface::Table;
+    use interface::Transactional;
+    use interface::Upserted;
+    use interface::Value;
+    use interface::Versioned;
+    use interface::VERSION_CONFLICT;
+    use pg_types;
+    use std::env;
+    use std::marker::PhantomData;
+    use std::sync::Arc;
+    use std::thread;
+    use tests::Department;
+    use tests::DepartmentFields;
+    use tokio::runtime;
+
+    //Runs against a postgres server started on this machine, for example with
+    //  initdb -D /tmp/pgdata -A trust -U postgres
+    //  pg_ctl -D /tmp/pgdata -o "-h localhost" start
+    //The connection string can be changed with the DATABASE_LIB_POSTGRES environment variable.
+    //The test makes its own table, so the schema below does not need to exist beforehand
+    /*
+    Columns in Department
+    +--------------+---------+----------------------------------+
+    | Column       | Type    | Default                          |
+    +--------------+---------+----------------------------------+
+    | departmentID | integer | generated always as identity     |
+    | Name         | text    |                                  |
+    | Abreviation  | text    | unique                           |
+    | version      | integer | 0                                |
+    +--------------+---------+----------------------------------+
+    */
+
+    fn open_department_table() -> pg_types::PgTable<Department> {
+        let params = env::var("DATABASE_LIB_POSTGRES")
+            .unwrap_or_else(|_| "host=localhost user=postgres dbname=postgres".to_string());
+        let pool = pg_types::open_postgres(&params).unwrap();
+
+        let mut client = pool.get().unwrap();
+        client
+            .batch_execute(
+                "DROP TABLE IF EXISTS \"Department\";
+                 CREATE TABLE \"Department\" (
+                     \"departmentID\" INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+                     \"Name\" TEXT NOT NULL,
+                     \"Abreviation\" VARCHAR(16) NOT NULL UNIQUE,
+                     \"version\" INTEGER NOT NULL DEFAULT 0
+                 );",
+            )
+            .unwrap();
+
+        pg_types::PgTable {
+            tb_name: "Department".to_string(),
+            key_name: "departmentID".to_string(),
+            pool,
+            phantom: PhantomData,
+            transaction: None,
+            version_name: Some("version".to_string()),
+        }
+    }
+
+    fn department(name: &str, abreviation: &str) -> Department {
+        Department {
+            name: name.to_string(),
+            abreviation: abreviation.to_string(),
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn full_postgres_test() {
+        let mut department_table = open_department_table();
+
+        //Testing basic functions
+        let ece_key =
+            department_table.insert(department("Electrical and Computer Engineering", "ECE"));
+        let me_key = department_table.insert(department("Mechanical Engineering", "ME"));
+        let bme_key = department_table.insert(department("Biomedical Engineering", "BME"));
+        assert!(ece_key.valid);
+        assert!(me_key.valid);
+        assert!(bme_key.valid);
+        assert_ne!(ece_key, me_key);
+
+        assert!(department_table.contains(ece_key));
+        assert_eq!(department_table.lookup(me_key).unwrap().abreviation, "ME");
+
+        department_table
+            .update(
+                ece_key,
+                department("Electrical and Computer Engineering Department", "ECE"),
+            )
+            .unwrap();
+        let (found_key, found_ece) = department_table
+            .search(
+                DepartmentFields::Abreviation,
+                Value::String("ECE".to_string()),
+            )
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert_eq!(found_key, ece_key);
+        assert_eq!(
+            found_ece.name,
+            "Electrical and Computer Engineering Department"
+        );
+
+        //Testing query
+        let q_lookup = department_table
+            .query(QueryType::Lookup, Some(bme_key))
+            .unwrap();
+        assert_eq!(q_lookup[0].1.abreviation, "BME");
+
+        let q_page_1 = department_table
+            .query(
+                QueryType::GetAll(2, DepartmentFields::Abreviation, SortDirection::Asc, 1),
+                None,
+            )
+            .unwrap();
+        let q_page_2 = department_table
+            .query(
+                QueryType::GetAll(2, DepartmentFields::Abreviation, SortDirection::Asc, 2),
+                None,
+            )
+            .unwrap();
+        assert_eq!(q_page_1.len(), 2);
+        assert_eq!(q_page_1[0].0, bme_key);
+        assert_eq!(q_page_1[1].0, ece_key);
+        assert_eq!(q_page_2.len(), 1);
+        assert_eq!(q_page_2[0].0, me_key);
+
+        //ILIKE ignores case, and the % in the search is matched literally
+        let q_parsearch = department_table
+            .query(
+                QueryType::PartialSearch(
+                    DepartmentFields::Name,
+                    Value::String("engineering".to_string()),
+                    10,
+                    DepartmentFields::Name,
+                    SortDirection::Desc,
+                    1,
+                ),
+                None,
+            )
+            .unwrap();
+        assert_eq!(q_parsearch.len(), 3);
+        assert_eq!(q_parsearch[0].0, me_key);
+        let q_percent = department_table
+            .query(
+                QueryType::PartialSearch(
+                    DepartmentFields::Name,
+                    Value::String("%".to_string()),
+                    10,
+                    DepartmentFields::Name,
+                    SortDirection::Asc,
+                    1,
+                ),
+                None,
+            )
+            .unwrap();
+        assert!(q_percent.is_empty());
+
+        let q_multi = department_table
+            .query(
+                QueryType::MultiSearch(
+                    vec![DepartmentFields::Name, DepartmentFields::Abreviation],
+                    vec![
+                        Value::String("Mechanical Engineering".to_string()),
+                        Value::String("ME".to_string()),
+                    ],
+                    10,
+                    DepartmentFields::Name,
+                    SortDirection::Asc,
+                    1,
+                ),
+                None,
+            )
+            .unwrap();
+        assert_eq!(q_multi.len(), 1);
+        assert_eq!(q_multi[0].0, me_key);
+
+        department_table.remove(me_key).unwrap();
+        assert!(!department_table.contains(me_key));
+        assert!(department_table.remove(me_key).is_err());
+        assert!(department_table.lookup(me_key).is_none());
+
+        //Testing transactions
+        let result: Result<(), String> = department_table.transaction(|table| {
+            table.insert(department("Chemical Engineering", "ChE"));
+            table.remove(bme_key)?;
+            table.remove(bme_key)
+        });
+        assert!(result.is_err());
+        assert!(department_table.contains(bme_key));
+        assert!(department_table
+            .search(
+                DepartmentFields::Abreviation,
+                Value::String("ChE".to_string())
+            )
+            .unwrap()
+            .is_empty());
+
+        //A second table joins the first one's transaction, and the changes are only seen
+        //outside of it after the commit
+        let outside_table = department_table.clone();
+        let mut joined_table = department_table.clone();
+        department_table.begin().unwrap();
+        assert!(department_table.begin().is_err());
+        joined_table.use_transaction_of(&department_table).unwrap();
+        let che_key = joined_table.insert(department("Chemical Engineering", "ChE"));
+        assert!(department_table.contains(che_key));
+        assert!(!outside_table.contains(che_key));
+        assert_eq!(
+            joined_table.commit(),
+            Err("Only the table that called begin can end the transaction".to_string())
+        );
+        assert!(joined_table.rollback().is_err());
+        assert!(joined_table.contains(che_key));
+        department_table.commit().unwrap();
+        assert!(outside_table.contains(che_key));
+        assert!(department_table.commit().is_err());
+        joined_table.remove(che_key).unwrap();
+        assert!(!outside_table.contains(che_key));
+        joined_table.begin().unwrap();
+        joined_table.rollback().unwrap();
+
+        //Testing batches
+        let keys = department_table
+            .insert_many(vec![
+                department("Chemical Engineering", "ChE"),
+                department("Civil Engineering", "CE"),
+                department("Computer Science", "CS"),
+            ])
+            .unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(department_table.lookup(keys[1]).unwrap().abreviation, "CE");
+        assert_eq!(department_table.lookup(keys[2]).unwrap().abreviation, "CS");
+
+        department_table
+            .update_many(vec![
+                (
+                    keys[0],
+                    department("Chemical Engineering Department", "ChE"),
+                ),
+                (keys[1], department("Civil Engineering Department", "CE")),
+            ])
+            .unwrap();
+        assert_eq!(
+            department_table.lookup(keys[1]).unwrap().name,
+            "Civil Engineering Department"
+        );
+
+        department_table
+            .remove_many(vec![keys[0], keys[1]])
+            .unwrap();
+        assert!(!department_table.contains(keys[0]));
+        assert!(department_table.contains(keys[2]));
+        assert!(department_table
+            .remove_many(vec![keys[1], keys[2]])
+            .is_err());
+        assert!(!department_table.contains(keys[2]));
+
+        //Testing upsert, which matches on the unique Abreviation column
+        let (ee_key, ee_upserted) = department_table
+            .upsert(
+                &[DepartmentFields::Abreviation],
+                department("Environmental Engineering", "EnvE"),
+            )
+            .unwrap();
+        assert_eq!(ee_upserted, Upserted::Inserted);
+        let (found_key, found_upserted) = department_table
+            .upsert(
+                &[DepartmentFields::Abreviation],
+                department("Environmental Engineering Department", "EnvE"),
+            )
+            .unwrap();
+        assert_eq!(found_upserted, Upserted::Updated);
+        assert_eq!(found_key, ee_key);
+        assert_eq!(
+            department_table.lookup(ee_key).unwrap().name,
+            "Environmental Engineering Department"
+        );
+
+        //Testing update_where and remove_where
+        let engineering = Filter::Like(DepartmentFields::Name, "%engineering%".to_string());
+        let updated = department_table
+            .update_where(
+                engineering.clone(),
+                vec![(
+                    DepartmentFields::Name,
+                    Value::String("Engineering".to_string()),
+                )],
+            )
+            .unwrap();
+        assert_eq!(updated, 3);
+        assert_eq!(
+            department_table.lookup(bme_key).unwrap().name,
+            "Engineering"
+        );
+        let removed = department_table
+            .remove_where(Filter::And(vec![
+                engineering,
+                Filter::NotEquals(
+                    DepartmentFields::Abreviation,
+                    Value::String("BME".to_string()),
+                ),
+            ]))
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert!(department_table.contains(bme_key));
+        assert!(!department_table.contains(ece_key));
+
+        //Testing patch, only the named field changes
+        department_table
+            .patch(
+                bme_key,
+                vec![(
+                    DepartmentFields::Name,
+                    Value::String("Biomedical Engineering".to_string()),
+                )],
+            )
+            .unwrap();
+        let bme = department_table.lookup(bme_key).unwrap();
+        assert_eq!(bme.name, "Biomedical Engineering");
+        assert_eq!(bme.abreviation, "BME");
+        assert!(department_table
+            .patch(bme_key, vec![(DepartmentFields::Name, Value::Integer(4))])
+            .is_err());
+        assert!(department_table
+            .patch(
+                ece_key,
+                vec![(DepartmentFields::Name, Value::String("ECE".to_string()))],
+            )
+            .is_err());
+
+        //Testing versions, an update from an old version fails
+        let (bme, version) = department_table.lookup_version(bme_key).unwrap();
+        assert_eq!(version, 2); //Changed by update_where and patch
+        assert_eq!(
+            department_table.update_if_version(bme_key, version, bme.clone()),
+            Ok(3)
+        );
+        assert_eq!(
+            department_table.update_if_version(bme_key, version, bme.clone()),
+            Err(VERSION_CONFLICT.to_string())
+        );
+        department_table
+            .upsert(&[DepartmentFields::Abreviation], bme.clone())
+            .unwrap();
+        assert_eq!(department_table.lookup_version(bme_key).unwrap().1, 4);
+        assert!(department_table.update_if_version(ece_key, 0, bme).is_err());
+
+        //Testing scan, which reads more rows than fit in one fetch from the cursor
+        let many: Vec<Department> = (0..2500)
+            .map(|i| {
+                department(
+                    &("Department ".to_string() + &i.to_string()),
+                    &i.to_string(),
+                )
+            })
+            .collect();
+        let many_keys = department_table.insert_many(many).unwrap();
+        let scanned: Result<Vec<_>, String> = department_table.iter().collect();
+        assert_eq!(scanned.unwrap().len(), 2501);
+        let mut numbered = department_table.scan(Filter::Like(
+            DepartmentFields::Name,
+            "department 1%".to_string(),
+        ));
+        assert_eq!(numbered.next().unwrap().unwrap().0, many_keys[1]);
+        drop(numbered); //Stops partway, which ends the transaction of the cursor
+        let in_transaction: Result<usize, String> = department_table.transaction(|table| {
+            table.remove(many_keys[0])?;
+            Ok(table.iter().count())
+        });
+        assert_eq!(in_transaction, Ok(2500));
+        department_table
+            .remove_many(many_keys[1..].to_vec())
+            .unwrap();
+        assert_eq!(department_table.iter().count(), 1);
+
+        //Testing AsyncTable, both inserts run at the same time on their own connections
+        let runtime = runtime::Builder::new_current_thread().build().unwrap();
+        let _guard = runtime.enter();
+        let async_table = async_table::SharedBlockingTable::new(department_table.clone());
+        let cs_future =
+            async_table::AsyncTable::insert(&async_table, department("Computer Science", "CS"));
+        let math_future =
+            async_table::AsyncTable::insert(&async_table, department("Mathematics", "MATH"));
+        let cs_key = runtime.block_on(cs_future).unwrap();
+        let math_key = runtime.block_on(math_future).unwrap();
+        let cs = runtime
+            .block_on(async_table::AsyncTable::lookup(&async_table, cs_key))
+            .unwrap();
+        assert_eq!(cs.unwrap().abreviation, "CS");
+        runtime
+            .block_on(async_table::AsyncTable::remove(&async_table, math_key))
+            .unwrap();
+        assert!(!department_table.contains(math_key));
+
+        //Testing SharedTable, threads use the same table without a lock around it
+        let shared_table = Arc::new(department_table.clone());
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let shared_table = shared_table.clone();
+                thread::spawn(move || {
+                    let abreviation = "T".to_string() + &t.to_string();
+                    let key = interface::SharedTable::insert(
+                        &*shared_table,
+                        department("Thread Department", &abreviation),
+                    );
+                    assert!(key.valid);
+                    interface::SharedTable::remove(&*shared_table, key).unwrap();
+                    key
+                })
+            })
+            .collect();
+        let thread_keys: Vec<pg_types::PgTableKey> =
+            handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(thread_keys.len(), 4);
+        assert!(thread_keys
+            .iter()
+            .all(|key| !department_table.contains(*key)));
+
+        assert_eq!(department_table.remove_where(Filter::All).unwrap(), 2);
+    }
+}
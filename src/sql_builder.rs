@@ -0,0 +1,1248 @@
+//!
+//! Builds the text of SQL statements along with the values bound to their placeholders.
+//! Nothing in here talks to a database, so every statement can be checked without a server.
+//! Anything that changes between databases is left to the Dialect the statement is built with.
+//!
+
+use dialect::Dialect;
+use dialect::InsertedKey;
+use interface::Aggregate;
+use interface::Entry;
+use interface::Filter;
+use interface::SortDirection;
+use interface::Value;
+use interface::ValueType;
+
+//The most rows a single page of a query can hold
+pub static MAX_LIMIT: u16 = 100;
+
+//The most parameters one statement can have. Both MySQL and PostgreSQL count them with a u16
+pub static MAX_PARAMS: usize = 65535;
+
+/**
+ *  A finished SQL statement. Each placeholder in `sql` is bound to the value at the same position
+ *  in `params`.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+/**
+ *  One condition in a WHERE clause. All of the conditions given to a statement are joined with AND
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    //column = value
+    Equals(String, Value),
+    //column <> value
+    NotEquals(String, Value),
+    //column < value
+    LessThan(String, Value),
+    //column <= value
+    LessOrEqual(String, Value),
+    //column > value
+    GreaterThan(String, Value),
+    //column >= value
+    GreaterOrEqual(String, Value),
+    //column LIKE pattern, ignoring case. The pattern is sent as is, see Dialect::escape_like
+    Like(String, String),
+    //column IN (values). Never matches anything when there are no values
+    In(String, Vec<Value>),
+    //(condition AND condition ...). Always matches when there are no conditions
+    And(Vec<Condition>),
+    //(condition OR condition ...). Never matches anything when there are no conditions
+    Or(Vec<Condition>),
+    //NOT (condition)
+    Not(Box<Condition>),
+}
+
+/// Turns a Filter into the Condition for it, with the field names as the column names. Gives None
+/// for Filter::All, which does not need a WHERE clause.
+pub fn filter_condition<E: Entry>(filter: &Filter<E>) -> Option<Condition> {
+    let condition = match filter {
+        Filter::All => return None,
+        Filter::Equals(field, value) => Condition::Equals(field.to_string(), value.clone()),
+        Filter::NotEquals(field, value) => Condition::NotEquals(field.to_string(), value.clone()),
+        Filter::LessThan(field, value) => Condition::LessThan(field.to_string(), value.clone()),
+        Filter::LessOrEqual(field, value) => {
+            Condition::LessOrEqual(field.to_string(), value.clone())
+        }
+        Filter::GreaterThan(field, value) => {
+            Condition::GreaterThan(field.to_string(), value.clone())
+        }
+        Filter::GreaterOrEqual(field, value) => {
+            Condition::GreaterOrEqual(field.to_string(), value.clone())
+        }
+        Filter::Like(field, pattern) => Condition::Like(field.to_string(), pattern.clone()),
+        Filter::And(filters) => Condition::And(
+            filters
+                .iter()
+                .filter_map(|filter| filter_condition(filter))
+                .collect(),
+        ),
+        //All inside an OR makes the whole thing match
+        Filter::Or(filters) => match filters
+            .iter()
+            .map(|filter| filter_condition(filter))
+            .collect::<Option<Vec<Condition>>>()
+        {
+            Some(conditions) => Condition::Or(conditions),
+            None => return None,
+        },
+        Filter::Not(filter) => match filter_condition(filter) {
+            Some(condition) => Condition::Not(Box::new(condition)),
+            None => Condition::Or(Vec::new()),
+        },
+    };
+    Some(condition)
+}
+
+/**
+ *  SELECT [DISTINCT] columns FROM table WHERE ... ORDER BY ... LIMIT ... OFFSET ...
+*/
+pub struct Select {
+    table: String,
+    database: Option<String>,
+    distinct: bool,
+    columns: Vec<String>,
+    conditions: Vec<Condition>,
+    order: Option<(String, SortDirection)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl Select {
+    pub fn new(table: &str, columns: &[String]) -> Select {
+        Select {
+            table: table.to_string(),
+            database: None,
+            distinct: false,
+            columns: columns.to_vec(),
+            conditions: Vec::new(),
+            order: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Names the database the table is in, for a connection that is not using that database
+    pub fn database(mut self, database: &str) -> Select {
+        self.database = Some(database.to_string());
+        self
+    }
+
+    /// Leaves out rows that are the same as one already in the result
+    pub fn distinct(mut self) -> Select {
+        self.distinct = true;
+        self
+    }
+
+    pub fn condition(mut self, condition: Condition) -> Select {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn where_equals(self, column: &str, value: Value) -> Select {
+        self.condition(Condition::Equals(column.to_string(), value))
+    }
+
+    pub fn where_like(self, column: &str, pattern: &str) -> Select {
+        self.condition(Condition::Like(column.to_string(), pattern.to_string()))
+    }
+
+    pub fn where_in(self, column: &str, values: Vec<Value>) -> Select {
+        self.condition(Condition::In(column.to_string(), values))
+    }
+
+    pub fn order_by(mut self, column: &str, direction: SortDirection) -> Select {
+        self.order = Some((column.to_string(), direction));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Select {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Select {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sorts and limits the select to one page of a query. The limit is checked against MAX_LIMIT
+    /// and pages start at 1.
+    pub fn page(self, sort_column: &str, direction: SortDirection, lim: u16, pg: u16) -> Select {
+        let limit = u64::from(lim.min(MAX_LIMIT));
+        let start_limit = limit * u64::from(pg.saturating_sub(1));
+        self.order_by(sort_column, direction)
+            .limit(limit)
+            .offset(start_limit)
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| dialect.quote_identifier(c))
+            .collect();
+        let mut sql = "SELECT ".to_string();
+        if self.distinct {
+            sql += "DISTINCT ";
+        }
+        sql = sql + &columns.join(", ") + " FROM ";
+        if let Some(ref database) = self.database {
+            sql = sql + &dialect.quote_identifier(database) + ".";
+        }
+        sql += &dialect.quote_identifier(&self.table);
+        let mut params = Vec::new();
+        push_where(dialect, None, &self.conditions, &mut sql, &mut params);
+        if let Some((ref column, ref direction)) = self.order {
+            sql = sql + " ORDER BY " + &dialect.quote_identifier(column);
+            sql += match direction {
+                SortDirection::Asc => " ASC",
+                SortDirection::Desc => " DESC",
+            };
+        }
+        sql += &dialect.limit_offset(self.limit, self.offset);
+        Statement { sql, params }
+    }
+}
+
+/**
+ *  An aggregate function in a SELECT
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Function {
+    //COUNT(*)
+    Count,
+    //SUM(column)
+    Sum(String),
+    //MIN(column)
+    Min(String),
+    //MAX(column)
+    Max(String),
+    //AVG(column)
+    Avg(String),
+}
+
+/// Turns an Aggregate into the Function for it, with the field name as the column name
+pub fn aggregate_function<E: Entry>(aggregate: &Aggregate<E>) -> Function {
+    match aggregate {
+        Aggregate::Count => Function::Count,
+        Aggregate::Sum(field) => Function::Sum(field.to_string()),
+        Aggregate::Min(field) => Function::Min(field.to_string()),
+        Aggregate::Max(field) => Function::Max(field.to_string()),
+        Aggregate::Avg(field) => Function::Avg(field.to_string()),
+    }
+}
+
+/**
+ *  SELECT group columns, functions FROM table WHERE ... GROUP BY group columns HAVING COUNT(*) > 0
+ *  ORDER BY group columns
+ *  The HAVING leaves out the one row of NULLs a database gives when there are no group columns
+ *  and nothing matches.
+*/
+pub struct GroupSelect {
+    table: String,
+    group_columns: Vec<String>,
+    functions: Vec<Function>,
+    conditions: Vec<Condition>,
+}
+
+impl GroupSelect {
+    pub fn new(table: &str, group_columns: &[String], functions: Vec<Function>) -> GroupSelect {
+        GroupSelect {
+            table: table.to_string(),
+            group_columns: group_columns.to_vec(),
+            functions,
+            conditions: Vec::new(),
+        }
+    }
+
+    pub fn condition(mut self, condition: Condition) -> GroupSelect {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let group_columns: Vec<String> = self
+            .group_columns
+            .iter()
+            .map(|c| dialect.quote_identifier(c))
+            .collect();
+        let mut columns = group_columns.clone();
+        for function in &self.functions {
+            columns.push(match function {
+                Function::Count => "COUNT(*)".to_string(),
+                Function::Sum(column) => format!("SUM({})", dialect.quote_identifier(column)),
+                Function::Min(column) => format!("MIN({})", dialect.quote_identifier(column)),
+                Function::Max(column) => format!("MAX({})", dialect.quote_identifier(column)),
+                Function::Avg(column) => format!("AVG({})", dialect.quote_identifier(column)),
+            });
+        }
+        let mut sql = "SELECT ".to_string()
+            + &columns.join(", ")
+            + " FROM "
+            + &dialect.quote_identifier(&self.table);
+        let mut params = Vec::new();
+        push_where(dialect, None, &self.conditions, &mut sql, &mut params);
+        if !group_columns.is_empty() {
+            sql = sql + " GROUP BY " + &group_columns.join(", ");
+        }
+        sql += " HAVING COUNT(*) > 0";
+        if !group_columns.is_empty() {
+            sql = sql + " ORDER BY " + &group_columns.join(", ");
+        }
+        Statement { sql, params }
+    }
+}
+
+/**
+ *  SELECT columns, other columns FROM table AS a LEFT JOIN other table AS b
+ *  ON a.reference column = b.key column WHERE ...
+ *  Every row of the table comes back once for each row of the other table it matches, or once
+ *  with NULL for all of the other columns if it matches none. The conditions are on the columns
+ *  of the table.
+*/
+pub struct Join {
+    table: String,
+    columns: Vec<String>,
+    other_database: Option<String>,
+    other_table: String,
+    other_columns: Vec<String>,
+    on: Option<(String, String)>,
+    conditions: Vec<Condition>,
+}
+
+impl Join {
+    pub fn new(
+        table: &str,
+        columns: &[String],
+        other_table: &str,
+        other_columns: &[String],
+    ) -> Join {
+        Join {
+            table: table.to_string(),
+            columns: columns.to_vec(),
+            other_database: None,
+            other_table: other_table.to_string(),
+            other_columns: other_columns.to_vec(),
+            on: None,
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Names the database the other table is in, for when it is not in the one being used
+    pub fn other_database(mut self, database: &str) -> Join {
+        self.other_database = Some(database.to_string());
+        self
+    }
+
+    /// Matches rows where reference_column of the table is the same as key_column of the other
+    /// table. Without it every row matches every other row.
+    pub fn on(mut self, reference_column: &str, key_column: &str) -> Join {
+        self.on = Some((reference_column.to_string(), key_column.to_string()));
+        self
+    }
+
+    pub fn condition(mut self, condition: Condition) -> Join {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        //The tables are called a and b, so a table can be joined to itself
+        let mut columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| quote_column(dialect, Some("a"), c))
+            .collect();
+        for column in &self.other_columns {
+            columns.push(quote_column(dialect, Some("b"), column));
+        }
+        let mut sql = "SELECT ".to_string()
+            + &columns.join(", ")
+            + " FROM "
+            + &dialect.quote_identifier(&self.table)
+            + " AS "
+            + &dialect.quote_identifier("a")
+            + " LEFT JOIN ";
+        if let Some(ref database) = self.other_database {
+            sql = sql + &dialect.quote_identifier(database) + ".";
+        }
+        sql = sql
+            + &dialect.quote_identifier(&self.other_table)
+            + " AS "
+            + &dialect.quote_identifier("b")
+            + " ON ";
+        match self.on {
+            Some((ref reference_column, ref key_column)) => {
+                sql = sql
+                    + &quote_column(dialect, Some("a"), reference_column)
+                    + " = "
+                    + &quote_column(dialect, Some("b"), key_column);
+            }
+            None => sql += "1 = 1",
+        }
+        let mut params = Vec::new();
+        push_where(dialect, Some("a"), &self.conditions, &mut sql, &mut params);
+        Statement { sql, params }
+    }
+}
+
+/**
+ *  INSERT INTO table (columns) VALUES (values), (values), ...
+ *  value() fills in the columns and the first row, and row() adds more rows after it.
+*/
+pub struct Insert {
+    table: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    key_column: Option<String>,
+    also_returning: Vec<String>,
+    match_columns: Option<Vec<String>>,
+    increments: Vec<String>,
+}
+
+impl Insert {
+    pub fn new(table: &str) -> Insert {
+        Insert {
+            table: table.to_string(),
+            columns: Vec::new(),
+            rows: Vec::new(),
+            key_column: None,
+            also_returning: Vec::new(),
+            match_columns: None,
+            increments: Vec::new(),
+        }
+    }
+
+    pub fn value(mut self, column: &str, value: Value) -> Insert {
+        self.columns.push(column.to_string());
+        if self.rows.is_empty() {
+            self.rows.push(Vec::new());
+        }
+        self.rows[0].push(value);
+        self
+    }
+
+    /// Sets the columns without adding a row, for when every row is added with row()
+    pub fn columns(mut self, columns: &[String]) -> Insert {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Adds another row, with one value for each column in the same order as the columns
+    pub fn row(mut self, values: Vec<Value>) -> Insert {
+        self.rows.push(values);
+        self
+    }
+
+    /// The column the database fills in with the new key. Dialects that send the key back with
+    /// RETURNING get a RETURNING clause for it, the rest have to use their InsertedKey query.
+    pub fn returning(mut self, key_column: &str) -> Insert {
+        self.key_column = Some(key_column.to_string());
+        self
+    }
+
+    /// Adds an SQL expression to send back after the key, for dialects that use RETURNING. The
+    /// expression is written into the statement as it is, so it must never come from a user.
+    pub fn also_returning(mut self, expression: &str) -> Insert {
+        self.also_returning.push(expression.to_string());
+        self
+    }
+
+    /// Turns the insert into an upsert. A row that has the same match_columns as a row already in
+    /// the table updates every other column of that row instead of being inserted. Needs
+    /// returning() to be set as well, see Dialect::on_conflict_update.
+    pub fn on_conflict_update(mut self, match_columns: &[String]) -> Insert {
+        self.match_columns = Some(match_columns.to_vec());
+        self
+    }
+
+    /// Adds one to a column of the row that on_conflict_update() updates, such as a version column
+    pub fn increment(mut self, column: &str) -> Insert {
+        self.increments.push(column.to_string());
+        self
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| dialect.quote_identifier(c))
+            .collect();
+        let mut params = Vec::new();
+        let mut row_vec = Vec::new();
+        for row in &self.rows {
+            let mut placeholders = Vec::new();
+            for value in row {
+                params.push(value.clone());
+                placeholders.push(dialect.placeholder(params.len()));
+            }
+            row_vec.push("(".to_string() + &placeholders.join(", ") + ")");
+        }
+        let mut sql = "INSERT INTO ".to_string()
+            + &dialect.quote_identifier(&self.table)
+            + " ("
+            + &columns.join(", ")
+            + ") VALUES "
+            + &row_vec.join(", ");
+        if let Some(ref match_columns) = self.match_columns {
+            let update_columns: Vec<String> = self
+                .columns
+                .iter()
+                .filter(|c| !match_columns.contains(c))
+                .cloned()
+                .collect();
+            let key_column = self.key_column.as_ref().map_or("", |k| k.as_str());
+            sql += &dialect.on_conflict_update(match_columns, &update_columns, key_column);
+            for column in &self.increments {
+                sql += &(", ".to_string() + &increment_sql(dialect, &self.table, column));
+            }
+        }
+        if let Some(ref key_column) = self.key_column {
+            if dialect.inserted_key() == InsertedKey::Returning {
+                sql = sql + " RETURNING " + &dialect.quote_identifier(key_column);
+                for expression in &self.also_returning {
+                    sql = sql + ", " + expression;
+                }
+            }
+        }
+        Statement { sql, params }
+    }
+}
+
+/**
+ *  UPDATE table SET column = value, ... WHERE ...
+*/
+pub struct Update {
+    table: String,
+    assignments: Vec<(String, Value)>,
+    increments: Vec<String>,
+    conditions: Vec<Condition>,
+}
+
+impl Update {
+    pub fn new(table: &str) -> Update {
+        Update {
+            table: table.to_string(),
+            assignments: Vec::new(),
+            increments: Vec::new(),
+            conditions: Vec::new(),
+        }
+    }
+
+    pub fn set(mut self, column: &str, value: Value) -> Update {
+        self.assignments.push((column.to_string(), value));
+        self
+    }
+
+    /// Adds one to a column as part of the update, such as a version column
+    pub fn increment(mut self, column: &str) -> Update {
+        self.increments.push(column.to_string());
+        self
+    }
+
+    pub fn condition(mut self, condition: Condition) -> Update {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn where_equals(self, column: &str, value: Value) -> Update {
+        self.condition(Condition::Equals(column.to_string(), value))
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let mut params = Vec::new();
+        let mut set_vec = Vec::new();
+        for (column, value) in &self.assignments {
+            params.push(value.clone());
+            set_vec.push(
+                dialect.quote_identifier(column) + " = " + &dialect.placeholder(params.len()),
+            );
+        }
+        for column in &self.increments {
+            set_vec.push(increment_sql(dialect, &self.table, column));
+        }
+        let mut sql = "UPDATE ".to_string()
+            + &dialect.quote_identifier(&self.table)
+            + " SET "
+            + &set_vec.join(", ");
+        push_where(dialect, None, &self.conditions, &mut sql, &mut params);
+        Statement { sql, params }
+    }
+}
+
+//column = table.column + 1, with the table named so it means the row already in the table in an upsert
+fn increment_sql(dialect: &dyn Dialect, table: &str, column: &str) -> String {
+    dialect.quote_identifier(column)
+        + " = "
+        + &dialect.quote_identifier(table)
+        + "."
+        + &dialect.quote_identifier(column)
+        + " + 1"
+}
+
+/**
+ *  DELETE FROM table WHERE ...
+*/
+pub struct Delete {
+    table: String,
+    conditions: Vec<Condition>,
+}
+
+impl Delete {
+    pub fn new(table: &str) -> Delete {
+        Delete {
+            table: table.to_string(),
+            conditions: Vec::new(),
+        }
+    }
+
+    pub fn condition(mut self, condition: Condition) -> Delete {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn where_equals(self, column: &str, value: Value) -> Delete {
+        self.condition(Condition::Equals(column.to_string(), value))
+    }
+
+    pub fn where_in(self, column: &str, values: Vec<Value>) -> Delete {
+        self.condition(Condition::In(column.to_string(), values))
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let mut params = Vec::new();
+        let mut sql = "DELETE FROM ".to_string() + &dialect.quote_identifier(&self.table);
+        push_where(dialect, None, &self.conditions, &mut sql, &mut params);
+        Statement { sql, params }
+    }
+}
+
+/**
+ *  CREATE TABLE IF NOT EXISTS table (key, columns)
+ *  Makes a table in the layout the SQL tables expect, with the key column first.
+*/
+pub struct CreateTable {
+    table: String,
+    key_column: String,
+    columns: Vec<(String, ValueType)>,
+}
+
+impl CreateTable {
+    pub fn new(table: &str, key_column: &str) -> CreateTable {
+        CreateTable {
+            table: table.to_string(),
+            key_column: key_column.to_string(),
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn column(mut self, column: &str, value_type: ValueType) -> CreateTable {
+        self.columns.push((column.to_string(), value_type));
+        self
+    }
+
+    pub fn build(&self, dialect: &dyn Dialect) -> Statement {
+        let mut column_vec =
+            vec![dialect.quote_identifier(&self.key_column) + " " + dialect.key_type_name()];
+        for (column, value_type) in &self.columns {
+            column_vec.push(
+                dialect.quote_identifier(column)
+                    + " "
+                    + dialect.type_name(*value_type)
+                    + " NOT NULL",
+            );
+        }
+        let sql = "CREATE TABLE IF NOT EXISTS ".to_string()
+            + &dialect.quote_identifier(&self.table)
+            + " ("
+            + &column_vec.join(", ")
+            + ")";
+        Statement {
+            sql,
+            params: Vec::new(),
+        }
+    }
+}
+
+//Adds the WHERE clause for the conditions, if there are any. When a table is given, the columns
+//are named as columns of that table, for statements with more than one table in them
+fn push_where(
+    dialect: &dyn Dialect,
+    table: Option<&str>,
+    conditions: &[Condition],
+    sql: &mut String,
+    params: &mut Vec<Value>,
+) {
+    if conditions.is_empty() {
+        return;
+    }
+    let where_vec: Vec<String> = conditions
+        .iter()
+        .map(|condition| render_condition(dialect, table, condition, params))
+        .collect();
+    *sql += " WHERE ";
+    *sql += &where_vec.join(" AND ");
+}
+
+//Writes out one condition, adding the values it needs to params
+fn render_condition(
+    dialect: &dyn Dialect,
+    table: Option<&str>,
+    condition: &Condition,
+    params: &mut Vec<Value>,
+) -> String {
+    //column op placeholder
+    let mut compare = |column: &str, op: &str, value: &Value| {
+        params.push(value.clone());
+        quote_column(dialect, table, column) + " " + op + " " + &dialect.placeholder(params.len())
+    };
+    match condition {
+        Condition::Equals(column, value) => compare(column, "=", value),
+        Condition::NotEquals(column, value) => compare(column, "<>", value),
+        Condition::LessThan(column, value) => compare(column, "<", value),
+        Condition::LessOrEqual(column, value) => compare(column, "<=", value),
+        Condition::GreaterThan(column, value) => compare(column, ">", value),
+        Condition::GreaterOrEqual(column, value) => compare(column, ">=", value),
+        Condition::Like(column, pattern) => {
+            params.push(Value::String(pattern.clone()));
+            dialect.like(
+                &quote_column(dialect, table, column),
+                &dialect.placeholder(params.len()),
+            )
+        }
+        Condition::In(column, values) => {
+            if values.is_empty() {
+                //IN () is not valid SQL, but it would match nothing anyway
+                return "1 = 0".to_string();
+            }
+            let mut placeholders = Vec::new();
+            for value in values {
+                params.push(value.clone());
+                placeholders.push(dialect.placeholder(params.len()));
+            }
+            quote_column(dialect, table, column) + " IN (" + &placeholders.join(", ") + ")"
+        }
+        Condition::And(conditions) => {
+            if conditions.is_empty() {
+                return "1 = 1".to_string();
+            }
+            let and_vec: Vec<String> = conditions
+                .iter()
+                .map(|condition| render_condition(dialect, table, condition, params))
+                .collect();
+            "(".to_string() + &and_vec.join(" AND ") + ")"
+        }
+        Condition::Or(conditions) => {
+            if conditions.is_empty() {
+                return "1 = 0".to_string();
+            }
+            let or_vec: Vec<String> = conditions
+                .iter()
+                .map(|condition| render_condition(dialect, table, condition, params))
+                .collect();
+            "(".to_string() + &or_vec.join(" OR ") + ")"
+        }
+        Condition::Not(condition) => {
+            "NOT (".to_string() + &render_condition(dialect, table, condition, params) + ")"
+        }
+    }
+}
+
+//A column, with the table it is in in front of it if one is given
+fn quote_column(dialect: &dyn Dialect, table: Option<&str>, column: &str) -> String {
+    match table {
+        Some(table) => dialect.quote_identifier(table) + "." + &dialect.quote_identifier(column),
+        None => dialect.quote_identifier(column),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use dialect::MySql;
+    use dialect::Postgres;
+    use dialect::Sqlite;
+    use interface::Aggregate;
+    use interface::Filter;
+    use interface::SortDirection;
+    use interface::Value;
+    use interface::ValueType;
+    use sql_builder::aggregate_function;
+    use sql_builder::filter_condition;
+    use sql_builder::Condition;
+    use sql_builder::CreateTable;
+    use sql_builder::Delete;
+    use sql_builder::Function;
+    use sql_builder::GroupSelect;
+    use sql_builder::Insert;
+    use sql_builder::Join;
+    use sql_builder::Select;
+    use sql_builder::Update;
+    use tests::Course;
+    use tests::CourseFields;
+    use tests::Department;
+    use tests::DepartmentFields;
+
+    fn columns() -> Vec<String> {
+        vec!["userID".to_string(), "firstname".to_string()]
+    }
+
+    #[test]
+    fn test_select_all() {
+        let statement = Select::new("User", &columns()).build(&MySql);
+
+        assert_eq!(statement.sql, "SELECT `userID`, `firstname` FROM `User`");
+        assert!(statement.params.is_empty());
+    }
+
+    #[test]
+    fn test_select_where_order_limit() {
+        let statement = Select::new("User", &columns())
+            .where_equals("firstname", Value::String("Nick".to_string()))
+            .where_like("email", "%@rowan.edu")
+            .order_by("firstname", SortDirection::Desc)
+            .limit(10)
+            .offset(20)
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `userID`, `firstname` FROM `User` WHERE `firstname` = ? AND `email` LIKE ? \
+             ORDER BY `firstname` DESC LIMIT 20, 10"
+        );
+        assert_eq!(
+            statement.params,
+            vec![
+                Value::String("Nick".to_string()),
+                Value::String("%@rowan.edu".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_database() {
+        let statement = Select::new("User", &columns())
+            .database("dbTest")
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `userID`, `firstname` FROM `dbTest`.`User`"
+        );
+    }
+
+    #[test]
+    fn test_select_offset_without_limit() {
+        let statement = Select::new("User", &columns()).offset(5).build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `userID`, `firstname` FROM `User` LIMIT 5, 18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn test_insert() {
+        let statement = Insert::new("User")
+            .value("firstname", Value::String("Nick".to_string()))
+            .value("bannerID", Value::Integer(916181533))
+            .returning("userID")
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "INSERT INTO `User` (`firstname`, `bannerID`) VALUES (?, ?)"
+        );
+        assert_eq!(
+            statement.params,
+            vec![Value::String("Nick".to_string()), Value::Integer(916181533),]
+        );
+    }
+
+    #[test]
+    fn test_insert_rows() {
+        let statement = Insert::new("User")
+            .columns(&columns())
+            .row(vec![Value::Integer(1), Value::String("Nick".to_string())])
+            .row(vec![Value::Integer(2), Value::String("Tim".to_string())])
+            .returning("userID")
+            .build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "INSERT INTO \"User\" (\"userID\", \"firstname\") VALUES ($1, $2), ($3, $4) \
+             RETURNING \"userID\""
+        );
+        assert_eq!(
+            statement.params,
+            vec![
+                Value::Integer(1),
+                Value::String("Nick".to_string()),
+                Value::Integer(2),
+                Value::String("Tim".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_on_conflict_update() {
+        let insert = Insert::new("User")
+            .value("firstname", Value::String("Nick".to_string()))
+            .value("bannerID", Value::Integer(916181533))
+            .on_conflict_update(&["bannerID".to_string()])
+            .returning("userID");
+
+        assert_eq!(
+            insert.build(&MySql).sql,
+            "INSERT INTO `User` (`firstname`, `bannerID`) VALUES (?, ?) ON DUPLICATE KEY UPDATE \
+             `userID` = LAST_INSERT_ID(`userID`), `firstname` = VALUES(`firstname`)"
+        );
+        assert_eq!(
+            insert.build(&Postgres).sql,
+            "INSERT INTO \"User\" (\"firstname\", \"bannerID\") VALUES ($1, $2) ON CONFLICT \
+             (\"bannerID\") DO UPDATE SET \"firstname\" = EXCLUDED.\"firstname\" RETURNING \"userID\""
+        );
+    }
+
+    #[test]
+    fn test_insert_also_returning() {
+        let insert = Insert::new("User")
+            .value("firstname", Value::String("Nick".to_string()))
+            .value("bannerID", Value::Integer(916181533))
+            .on_conflict_update(&["bannerID".to_string()])
+            .returning("userID")
+            .also_returning("(xmax = 0)");
+
+        assert_eq!(
+            insert.build(&Postgres).sql,
+            "INSERT INTO \"User\" (\"firstname\", \"bannerID\") VALUES ($1, $2) ON CONFLICT \
+             (\"bannerID\") DO UPDATE SET \"firstname\" = EXCLUDED.\"firstname\" \
+             RETURNING \"userID\", (xmax = 0)"
+        );
+        //MySQL has no RETURNING, so the expressions are left out along with the key
+        assert!(!insert.build(&MySql).sql.contains("xmax"));
+    }
+
+    #[test]
+    fn test_increment() {
+        let insert = Insert::new("User")
+            .value("firstname", Value::String("Nick".to_string()))
+            .value("bannerID", Value::Integer(916181533))
+            .on_conflict_update(&["bannerID".to_string()])
+            .increment("version")
+            .returning("userID");
+        assert_eq!(
+            insert.build(&Postgres).sql,
+            "INSERT INTO \"User\" (\"firstname\", \"bannerID\") VALUES ($1, $2) ON CONFLICT \
+             (\"bannerID\") DO UPDATE SET \"firstname\" = EXCLUDED.\"firstname\", \
+             \"version\" = \"User\".\"version\" + 1 RETURNING \"userID\""
+        );
+
+        let statement = Update::new("User")
+            .set("firstname", Value::String("Nicholas".to_string()))
+            .increment("version")
+            .where_equals("userID", Value::Integer(4))
+            .where_equals("version", Value::Integer(2))
+            .build(&MySql);
+        assert_eq!(
+            statement.sql,
+            "UPDATE `User` SET `firstname` = ?, `version` = `User`.`version` + 1 \
+             WHERE `userID` = ? AND `version` = ?"
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let statement = Update::new("User")
+            .set("firstname", Value::String("Nicholas".to_string()))
+            .set("lastname", Value::String("Kluzynski".to_string()))
+            .where_equals("userID", Value::Integer(4))
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "UPDATE `User` SET `firstname` = ?, `lastname` = ? WHERE `userID` = ?"
+        );
+        assert_eq!(
+            statement.params,
+            vec![
+                Value::String("Nicholas".to_string()),
+                Value::String("Kluzynski".to_string()),
+                Value::Integer(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delete() {
+        let statement = Delete::new("User")
+            .where_equals("userID", Value::Integer(4))
+            .build(&MySql);
+
+        assert_eq!(statement.sql, "DELETE FROM `User` WHERE `userID` = ?");
+        assert_eq!(statement.params, vec![Value::Integer(4)]);
+    }
+
+    #[test]
+    fn test_delete_in() {
+        let statement = Delete::new("User")
+            .where_in("userID", vec![Value::Integer(4), Value::Integer(7)])
+            .build(&MySql);
+
+        assert_eq!(statement.sql, "DELETE FROM `User` WHERE `userID` IN (?, ?)");
+        assert_eq!(statement.params, vec![Value::Integer(4), Value::Integer(7)]);
+
+        let empty = Select::new("User", &columns())
+            .where_in("userID", Vec::new())
+            .build(&Postgres);
+        assert_eq!(
+            empty.sql,
+            "SELECT \"userID\", \"firstname\" FROM \"User\" WHERE 1 = 0"
+        );
+        assert!(empty.params.is_empty());
+    }
+
+    #[test]
+    fn test_select_page() {
+        let statement = Select::new("User", &columns())
+            .page("firstname", SortDirection::Asc, 500, 3)
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `userID`, `firstname` FROM `User` ORDER BY `firstname` ASC LIMIT 200, 100"
+        );
+    }
+
+    #[test]
+    fn test_select_page_zero() {
+        let statement = Select::new("User", &columns())
+            .page("firstname", SortDirection::Asc, 10, 0)
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `userID`, `firstname` FROM `User` ORDER BY `firstname` ASC LIMIT 0, 10"
+        );
+    }
+
+    #[test]
+    fn test_filter_condition() {
+        let filter: Filter<Department> = Filter::And(vec![
+            Filter::Like(DepartmentFields::Name, "%engineering".to_string()),
+            Filter::All,
+            Filter::Or(vec![
+                Filter::GreaterOrEqual(
+                    DepartmentFields::Abreviation,
+                    Value::String("E".to_string()),
+                ),
+                Filter::Not(Box::new(Filter::Equals(
+                    DepartmentFields::Name,
+                    Value::String("Physics".to_string()),
+                ))),
+            ]),
+        ]);
+        let statement = Delete::new("Department")
+            .condition(filter_condition(&filter).unwrap())
+            .build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "DELETE FROM \"Department\" WHERE (\"Name\"::text ILIKE $1 AND (\"Abreviation\" >= $2 \
+             OR NOT (\"Name\" = $3)))"
+        );
+        assert_eq!(
+            statement.params,
+            vec![
+                Value::String("%engineering".to_string()),
+                Value::String("E".to_string()),
+                Value::String("Physics".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_condition_all() {
+        let all: Filter<Department> = Filter::All;
+        let or_all: Filter<Department> = Filter::Or(vec![
+            Filter::Equals(DepartmentFields::Name, Value::String("Physics".to_string())),
+            Filter::All,
+        ]);
+
+        assert!(filter_condition(&all).is_none());
+        assert!(filter_condition(&or_all).is_none());
+        assert_eq!(
+            filter_condition(&Filter::Not(Box::new(all))),
+            Some(Condition::Or(Vec::new()))
+        );
+        assert_eq!(
+            Select::new("Department", &["Name".to_string()])
+                .condition(Condition::And(Vec::new()))
+                .condition(Condition::Or(Vec::new()))
+                .build(&MySql)
+                .sql,
+            "SELECT `Name` FROM `Department` WHERE 1 = 1 AND 1 = 0"
+        );
+    }
+
+    #[test]
+    fn test_postgres_select() {
+        let statement = Select::new("User", &columns())
+            .where_equals("firstname", Value::String("Nick".to_string()))
+            .where_like("email", "%@rowan.edu")
+            .offset(5)
+            .build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT \"userID\", \"firstname\" FROM \"User\" WHERE \"firstname\" = $1 \
+             AND \"email\"::text ILIKE $2 OFFSET 5"
+        );
+    }
+
+    #[test]
+    fn test_postgres_insert_returning() {
+        let statement = Insert::new("User")
+            .value("firstname", Value::String("Nick".to_string()))
+            .value("bannerID", Value::Integer(916181533))
+            .returning("userID")
+            .build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "INSERT INTO \"User\" (\"firstname\", \"bannerID\") VALUES ($1, $2) RETURNING \"userID\""
+        );
+    }
+
+    #[test]
+    fn test_postgres_update() {
+        let statement = Update::new("User")
+            .set("firstname", Value::String("Nicholas".to_string()))
+            .where_equals("userID", Value::Integer(4))
+            .build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "UPDATE \"User\" SET \"firstname\" = $1 WHERE \"userID\" = $2"
+        );
+    }
+
+    #[test]
+    fn test_sqlite_select() {
+        let statement = Select::new("User", &columns())
+            .where_like("email", "%@rowan.edu")
+            .where_equals("firstname", Value::String("Nick".to_string()))
+            .page("firstname", SortDirection::Asc, 10, 2)
+            .build(&Sqlite);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT \"userID\", \"firstname\" FROM \"User\" WHERE \"email\" LIKE ?1 ESCAPE '\\' \
+             AND \"firstname\" = ?2 ORDER BY \"firstname\" ASC LIMIT 10 OFFSET 10"
+        );
+    }
+
+    #[test]
+    fn test_create_table() {
+        let create = CreateTable::new("User", "userID")
+            .column("firstname", ValueType::String)
+            .column("bannerID", ValueType::Integer);
+
+        assert_eq!(
+            create.build(&MySql).sql,
+            "CREATE TABLE IF NOT EXISTS `User` (`userID` INT NOT NULL AUTO_INCREMENT PRIMARY KEY, \
+             `firstname` VARCHAR(255) NOT NULL, `bannerID` INT NOT NULL)"
+        );
+        assert_eq!(
+            create.build(&Postgres).sql,
+            "CREATE TABLE IF NOT EXISTS \"User\" (\"userID\" INTEGER GENERATED BY DEFAULT AS \
+             IDENTITY PRIMARY KEY, \"firstname\" TEXT NOT NULL, \"bannerID\" INTEGER NOT NULL)"
+        );
+    }
+
+    #[test]
+    fn test_group_select() {
+        let functions = vec![
+            aggregate_function(&Aggregate::<Course>::Count),
+            aggregate_function(&Aggregate::<Course>::Avg(CourseFields::Credits)),
+        ];
+        assert_eq!(functions[1], Function::Avg("Credits".to_string()));
+
+        let statement = GroupSelect::new("Course", &["Department".to_string()], functions)
+            .condition(Condition::GreaterThan(
+                "Rating".to_string(),
+                Value::Float(2.5),
+            ))
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `Department`, COUNT(*), AVG(`Credits`) FROM `Course` WHERE `Rating` > ? \
+             GROUP BY `Department` HAVING COUNT(*) > 0 ORDER BY `Department`"
+        );
+        assert_eq!(statement.params, vec![Value::Float(2.5)]);
+    }
+
+    #[test]
+    fn test_group_select_no_groups() {
+        let functions = vec![
+            Function::Sum("Credits".to_string()),
+            Function::Min("Name".to_string()),
+            Function::Max("Name".to_string()),
+        ];
+
+        let statement = GroupSelect::new("Course", &[], functions).build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT SUM(\"Credits\"), MIN(\"Name\"), MAX(\"Name\") FROM \"Course\" \
+             HAVING COUNT(*) > 0"
+        );
+    }
+
+    #[test]
+    fn test_select_distinct() {
+        let statement = Select::new("Department", &["Abreviation".to_string()])
+            .distinct()
+            .where_like("Name", "%Engineering%")
+            .order_by("Abreviation", SortDirection::Asc)
+            .build(&MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT DISTINCT `Abreviation` FROM `Department` WHERE `Name` LIKE ? \
+             ORDER BY `Abreviation` ASC"
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        let statement = Join::new(
+            "Course",
+            &["id".to_string(), "Name".to_string()],
+            "Department",
+            &["id".to_string(), "Name".to_string()],
+        )
+        .other_database("school")
+        .on("Department", "id")
+        .condition(Condition::Or(vec![
+            Condition::Equals("Credits".to_string(), Value::Integer(3)),
+            Condition::In("id".to_string(), vec![Value::Integer(7)]),
+        ]))
+        .build(&Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT \"a\".\"id\", \"a\".\"Name\", \"b\".\"id\", \"b\".\"Name\" FROM \"Course\" \
+             AS \"a\" LEFT JOIN \"school\".\"Department\" AS \"b\" ON \"a\".\"Department\" = \
+             \"b\".\"id\" WHERE (\"a\".\"Credits\" = $1 OR \"a\".\"id\" IN ($2))"
+        );
+        assert_eq!(statement.params, vec![Value::Integer(3), Value::Integer(7)]);
+    }
+}
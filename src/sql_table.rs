@@ -0,0 +1,282 @@
+//!
+//! The parts of MysqlTable and PgTable that are the same in every SQL database: the statements
+//! for entries and queries, and turning the columns of a row back into an entry. A backend only
+//! has to send the statements and read the values out of its rows.
+//!
+
+use dialect::Dialect;
+use interface::Entry;
+use interface::ITryInto;
+use interface::QueryType;
+use interface::Value;
+use sql_builder::Select;
+use sql_builder::Statement;
+use sql_builder::Update;
+use std::marker::PhantomData;
+
+/**
+ *  The names a table of E is stored under, and the Dialect to write its statements in. Keys are
+ *  the integer ids of the key column, so each backend can wrap them in its own key type.
+*/
+pub struct SqlTable<'a, E: Entry> {
+    pub dialect: &'a dyn Dialect,
+    pub tb_name: &'a str,
+    pub key_name: &'a str,
+    pub version_name: Option<&'a str>,
+    phantom: PhantomData<fn() -> E>,
+}
+
+impl<'a, E: Entry> SqlTable<'a, E> {
+    pub fn new(
+        dialect: &'a dyn Dialect,
+        tb_name: &'a str,
+        key_name: &'a str,
+        version_name: Option<&'a str>,
+    ) -> SqlTable<'a, E> {
+        SqlTable {
+            dialect,
+            tb_name,
+            key_name,
+            version_name,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The key followed by every field, in the order values_to_entry expects
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns = vec![self.key_name.to_string()];
+        for field in E::get_field_names() {
+            columns.push(field.to_string());
+        }
+        columns
+    }
+
+    /// Starts a SELECT for the key followed by every field
+    pub fn select(&self) -> Select {
+        Select::new(self.tb_name, &self.columns())
+    }
+
+    /// Adds one to the version column as part of the update, if the table has one
+    pub fn versioned(&self, update: Update) -> Update {
+        match self.version_name {
+            Some(version_name) => update.increment(version_name),
+            None => update,
+        }
+    }
+
+    /// UPDATE tb_name SET field 1 = entry 1, field 2 = entry 2, ... without the WHERE
+    pub fn entry_update(&self, entry: &E) -> Update {
+        let mut update = self.versioned(Update::new(self.tb_name));
+        for (field, value) in E::get_field_names().iter().zip(entry.get_fields()) {
+            update = update.set(&field.to_string(), value);
+        }
+        update
+    }
+
+    /// UPDATE tb_name SET field 1 = entry 1, field 2 = entry 2, ... WHERE key_name = id
+    pub fn update_statement(&self, id: i32, entry: &E) -> Statement {
+        self.entry_update(entry)
+            .where_equals(self.key_name, Value::Integer(id))
+            .build(self.dialect)
+    }
+
+    /// Adds the WHERE clause and the paging of a query to a SELECT. Every query other than lookup
+    /// only differs by the WHERE clause, the paging is the same.
+    pub fn query_select(
+        &self,
+        q: QueryType<E>,
+        key: Option<i32>,
+        select: Select,
+    ) -> Result<Select, String> {
+        let (select, lim, sort_field, sort_dir, pg) = match q {
+            QueryType::Lookup => {
+                //SELECT key, fields FROM tb_name WHERE key_name = key
+                let id = match key {
+                    Some(id) => id,
+                    None => return Err("Must have a key".to_string()),
+                };
+                return Ok(select.where_equals(self.key_name, Value::Integer(id)));
+            }
+            QueryType::Search(field, val, lim, sort_field, sort_dir, pg) => {
+                //SELECT key, fields FROM tb_name WHERE field_name = field_value
+                let select = select.where_equals(&field.to_string(), val);
+                (select, lim, sort_field, sort_dir, pg)
+            }
+            QueryType::GetAll(lim, sort_field, sort_dir, pg) => {
+                //Return all of the given table, but does require a limit
+                (select, lim, sort_field, sort_dir, pg)
+            }
+            QueryType::PartialSearch(field, val, lim, sort_field, sort_dir, pg) => {
+                //SELECT key, fields FROM tb_name WHERE field_name LIKE %field_value%
+                //The Dialect makes the LIKE ignore case
+                let pattern = "%".to_string() + &self.dialect.escape_like(&val.to_string()) + "%";
+                let select = select.where_like(&field.to_string(), &pattern);
+                (select, lim, sort_field, sort_dir, pg)
+            }
+            QueryType::MultiSearch(field_vec, val_vec, lim, sort_field, sort_dir, pg) => {
+                //SELECT key, fields FROM tb_name WHERE field_name[0] = field_value[0] AND ...
+                if field_vec.len() != val_vec.len() {
+                    return Err("Field and Value vectors do not pair".to_string());
+                }
+                let mut select = select;
+                for (field, val) in field_vec.iter().zip(val_vec) {
+                    select = select.where_equals(&field.to_string(), val);
+                }
+                (select, lim, sort_field, sort_dir, pg)
+            }
+        };
+        Ok(select.page(&sort_field.to_string(), sort_dir, lim, pg))
+    }
+}
+
+/// Turns the columns of one row into the id of the key and an entry. The first column must be
+/// the key, and the rest must be in the same order as E::get_field_names()
+pub fn values_to_entry<E: Entry>(mut values: Vec<Value>) -> Result<(i32, E), String> {
+    let field_count = E::get_field_names().len();
+    if values.len() != field_count + 1 {
+        return Err("Wrong number of columns, expecting ".to_string()
+            + &(field_count + 1).to_string()
+            + ", found "
+            + &values.len().to_string());
+    }
+    let key_result: Result<i32, String> = values.remove(0).itry_into();
+    let id = match key_result {
+        Ok(id) => id,
+        Err(_) => return Err("Key column is not an integer".to_string()),
+    };
+    let this_entry = E::from_fields(&values)?;
+    Ok((id, this_entry))
+}
+
+/// Same as values_to_entry, for a row with the version column after the fields
+pub fn values_to_versioned_entry<E: Entry>(
+    mut values: Vec<Value>,
+) -> Result<(i32, E, u64), String> {
+    let version: i32 = match values.pop() {
+        Some(value) => value.itry_into()?,
+        None => return Err("Missing the version column".to_string()),
+    };
+    let (id, entry) = values_to_entry(values)?;
+    Ok((id, entry, version as u64))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use dialect::MySql;
+    use dialect::Postgres;
+    use interface::QueryType;
+    use interface::SortDirection;
+    use interface::Value;
+    use sql_table::values_to_entry;
+    use sql_table::values_to_versioned_entry;
+    use sql_table::SqlTable;
+    use tests::Department;
+    use tests::DepartmentFields;
+
+    fn department_table(version_name: Option<&str>) -> SqlTable<'_, Department> {
+        SqlTable::new(&Postgres, "Department", "id", version_name)
+    }
+
+    #[test]
+    fn test_query_select() {
+        let table = department_table(None);
+
+        let statement = table
+            .query_select(
+                QueryType::PartialSearch(
+                    DepartmentFields::Name,
+                    Value::String("50%".to_string()),
+                    200,
+                    DepartmentFields::Abreviation,
+                    SortDirection::Asc,
+                    2,
+                ),
+                None,
+                table.select(),
+            )
+            .unwrap()
+            .build(table.dialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT \"id\", \"Name\", \"Abreviation\" FROM \"Department\" WHERE \
+             \"Name\"::text ILIKE $1 ORDER BY \"Abreviation\" ASC LIMIT 100 OFFSET 100"
+        );
+        assert_eq!(statement.params, vec![Value::String("%50\\%%".to_string())]);
+
+        let mysql: SqlTable<Department> = SqlTable::new(&MySql, "Department", "id", None);
+        let statement = mysql
+            .query_select(QueryType::Lookup, Some(7), mysql.select())
+            .unwrap()
+            .build(mysql.dialect);
+        assert_eq!(
+            statement.sql,
+            "SELECT `id`, `Name`, `Abreviation` FROM `Department` WHERE `id` = ?"
+        );
+        assert_eq!(statement.params, vec![Value::Integer(7)]);
+
+        assert!(table
+            .query_select(QueryType::Lookup, None, table.select())
+            .is_err());
+        assert!(table
+            .query_select(
+                QueryType::MultiSearch(
+                    vec![DepartmentFields::Name],
+                    Vec::new(),
+                    10,
+                    DepartmentFields::Name,
+                    SortDirection::Asc,
+                    1,
+                ),
+                None,
+                table.select(),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_statement() {
+        let department = Department {
+            name: "Mechanical Engineering".to_string(),
+            abreviation: "ME".to_string(),
+        };
+
+        let statement = department_table(Some("version")).update_statement(3, &department);
+
+        assert_eq!(
+            statement.sql,
+            "UPDATE \"Department\" SET \"Name\" = $1, \"Abreviation\" = $2, \
+             \"version\" = \"Department\".\"version\" + 1 WHERE \"id\" = $3"
+        );
+        assert_eq!(
+            statement.params,
+            vec![
+                Value::String("Mechanical Engineering".to_string()),
+                Value::String("ME".to_string()),
+                Value::Integer(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_to_entry() {
+        let row = vec![
+            Value::Integer(7),
+            Value::String("Mechanical Engineering".to_string()),
+            Value::String("ME".to_string()),
+            Value::Integer(2),
+        ];
+
+        let (id, department, version): (i32, Department, u64) =
+            values_to_versioned_entry(row.clone()).unwrap();
+        assert_eq!(id, 7);
+        assert_eq!(department.abreviation, "ME");
+        assert_eq!(version, 2);
+
+        let result: Result<(i32, Department), String> = values_to_entry(row);
+        assert_eq!(
+            result.err(),
+            Some("Wrong number of columns, expecting 3, found 4".to_string())
+        );
+    }
+}
@@ -9,7 +9,9 @@ use std::str::FromStr;
 
 use interface::Entry;
 use interface::FieldName;
+use interface::ITryInto;
 use interface::Value;
+use interface::ValueType;
 
 /**
  *  Test entry with no keys for anything
@@ -83,6 +85,10 @@ impl Entry for Department {
         vec![DepartmentFields::Name, DepartmentFields::Abreviation]
     }
 
+    fn get_field_types() -> Vec<ValueType> {
+        vec![ValueType::String, ValueType::String]
+    }
+
     fn get_fields(&self) -> Vec<Value> {
         vec![
             Value::String(self.name.clone()),
@@ -238,6 +244,10 @@ impl Entry for User {
         vec![UserFields::FirstName, UserFields::LastName]
     }
 
+    fn get_field_types() -> Vec<ValueType> {
+        vec![ValueType::String, ValueType::String]
+    }
+
     fn get_fields(&self) -> Vec<Value> {
         vec![
             Value::String(self.first_name.clone()),
@@ -321,3 +331,123 @@ mod user_tests {
         assert_eq!(first_name, Some(Value::String("Tim".to_string())));
     }
 }
+
+/**
+ *  Test entry with numbers in it. The department is the key of a Department
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Course {
+    pub name: String,
+    pub department: i32,
+    pub credits: i32,
+    pub rating: f32,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum CourseFields {
+    Name,
+    Department,
+    Credits,
+    Rating,
+}
+
+impl FieldName for CourseFields {}
+
+impl Display for CourseFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CourseFields::Name => write!(f, "Name"),
+            CourseFields::Department => write!(f, "Department"),
+            CourseFields::Credits => write!(f, "Credits"),
+            CourseFields::Rating => write!(f, "Rating"),
+        }
+    }
+}
+
+impl FromStr for CourseFields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Name" => Ok(CourseFields::Name),
+            "Department" => Ok(CourseFields::Department),
+            "Credits" => Ok(CourseFields::Credits),
+            "Rating" => Ok(CourseFields::Rating),
+            _ => Err("Field does not exist".to_string()),
+        }
+    }
+}
+
+impl Entry for Course {
+    type FieldNames = CourseFields;
+
+    fn from_fields(values: &[Value]) -> Result<Self, String> {
+        if values.len() != 4 {
+            return Err("Expected 4 fields".to_string());
+        }
+        Ok(Course {
+            name: values[0].clone().itry_into()?,
+            department: values[1].clone().itry_into()?,
+            credits: values[2].clone().itry_into()?,
+            rating: values[3].clone().itry_into()?,
+        })
+    }
+
+    fn get_field_names() -> Vec<Self::FieldNames> {
+        vec![
+            CourseFields::Name,
+            CourseFields::Department,
+            CourseFields::Credits,
+            CourseFields::Rating,
+        ]
+    }
+
+    fn get_field_types() -> Vec<ValueType> {
+        vec![
+            ValueType::String,
+            ValueType::Integer,
+            ValueType::Integer,
+            ValueType::Float,
+        ]
+    }
+
+    fn get_fields(&self) -> Vec<Value> {
+        vec![
+            Value::String(self.name.clone()),
+            Value::Integer(self.department),
+            Value::Integer(self.credits),
+            Value::Float(self.rating),
+        ]
+    }
+
+    fn get_field(&self, field_name: CourseFields) -> Option<Value> {
+        match field_name {
+            CourseFields::Name => Some(Value::String(self.name.clone())),
+            CourseFields::Department => Some(Value::Integer(self.department)),
+            CourseFields::Credits => Some(Value::Integer(self.credits)),
+            CourseFields::Rating => Some(Value::Float(self.rating)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod course_tests {
+
+    use tests::Course;
+
+    use interface::Entry;
+    use interface::Value;
+
+    #[test]
+    fn test_course_from_fields() {
+        let course = Course {
+            name: "Circuits".to_string(),
+            department: 1,
+            credits: 3,
+            rating: 4.5,
+        };
+
+        assert_eq!(Course::from_fields(&course.get_fields()), Ok(course));
+        assert!(Course::from_fields(&[Value::String("Circuits".to_string())]).is_err());
+    }
+}
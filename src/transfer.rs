@@ -0,0 +1,734 @@
+use csv;
+use interface::Entry;
+use interface::Table;
+use interface::Value;
+use interface::ValueType;
+use serde_json;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+
+/**
+ *  What an import did. A line that could not be imported is skipped, and the rest are still
+ *  imported.
+*/
+#[derive(Debug)]
+pub struct ImportReport<K> {
+    //The keys of the entries that were inserted, in the order they were in. Empty for a dry run
+    pub keys: Vec<K>,
+    //The line number, starting at 1, and the reason for each line that was not imported
+    pub errors: Vec<(usize, String)>,
+}
+
+/// Writes every entry in a table as a line of JSON, with an object that has each field by name.
+/// Gives back how many entries were written.
+pub fn export_jsonl<E, T, W>(table: &T, writer: W) -> Result<u64, String>
+where
+    E: Entry,
+    T: Table<E>,
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    let mut count = 0;
+    for result in table.iter() {
+        let (_key, entry) = result?;
+        writeln!(writer, "{}", entry_to_json(&entry)?).map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Inserts an entry into a table for each line of JSON, in the format export_jsonl writes. Every
+/// field has to be there, with a JSON value of the right type. Blank lines are skipped.
+/// Only fails if the lines cannot be read, the lines with bad entries are in the report instead.
+pub fn import_jsonl<E, T, R>(table: &mut T, reader: R) -> Result<ImportReport<T::Key>, String>
+where
+    E: Entry,
+    T: Table<E>,
+    R: Read,
+{
+    let mut report = ImportReport {
+        keys: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match entry_from_json::<E>(&line) {
+            Ok(entry) => {
+                let key = table.insert(entry);
+                //Tables give back a key that is not in the table when an insert fails
+                if table.contains(key.clone()) {
+                    report.keys.push(key);
+                } else {
+                    report
+                        .errors
+                        .push((i + 1, "Could not insert the entry".to_string()));
+                }
+            }
+            Err(e) => report.errors.push((i + 1, e)),
+        }
+    }
+    Ok(report)
+}
+
+/// One entry as a JSON object, the way export_jsonl writes it. The fields are in the order
+/// get_field_names has them.
+pub fn entry_to_json<E: Entry>(entry: &E) -> Result<String, String> {
+    let mut json = "{".to_string();
+    for (i, (field, value)) in E::get_field_names()
+        .iter()
+        .zip(entry.get_fields())
+        .enumerate()
+    {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(&serde_json::Value::String(field.to_string()).to_string());
+        json.push(':');
+        json.push_str(&value_to_json(&value)?.to_string());
+    }
+    json.push('}');
+    Ok(json)
+}
+
+fn entry_from_json<E: Entry>(line: &str) -> Result<E, String> {
+    let json: serde_json::Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let object = match json.as_object() {
+        Some(object) => object,
+        None => return Err("Expected a JSON object".to_string()),
+    };
+
+    let field_names: Vec<String> = E::get_field_names()
+        .iter()
+        .map(|field| field.to_string())
+        .collect();
+    for name in object.keys() {
+        if !field_names.contains(name) {
+            return Err(format!("Unknown field {}", name));
+        }
+    }
+    let mut values = Vec::new();
+    for (name, value_type) in field_names.iter().zip(E::get_field_types()) {
+        match object.get(name) {
+            Some(json) => values.push(json_to_value(json, value_type).ok_or_else(|| {
+                format!("Incorrect type for {}. Should be {:?}", name, value_type)
+            })?),
+            None => return Err(format!("Missing field {}", name)),
+        }
+    }
+    E::from_fields(&values)
+}
+
+//Floats are written the way they print, so 0.1 does not come out as 0.10000000149011612
+fn value_to_json(value: &Value) -> Result<serde_json::Value, String> {
+    match value {
+        Value::Integer(i) => Ok(serde_json::Value::from(*i)),
+        Value::Float(f) => f
+            .to_string()
+            .parse()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("Cannot write {} as JSON", f)),
+        Value::String(s) => Ok(serde_json::Value::String(s.clone())),
+        Value::Boolean(b) => Ok(serde_json::Value::Bool(*b)),
+    }
+}
+
+//None if the JSON value is not the type the field needs
+fn json_to_value(json: &serde_json::Value, value_type: ValueType) -> Option<Value> {
+    match value_type {
+        ValueType::Integer => json
+            .as_i64()
+            .filter(|i| *i >= i64::from(i32::MIN) && *i <= i64::from(i32::MAX))
+            .map(|i| Value::Integer(i as i32)),
+        ValueType::Float => json.as_f64().map(|f| Value::Float(f as f32)),
+        ValueType::String => json.as_str().map(|s| Value::String(s.to_string())),
+        ValueType::Boolean => json.as_bool().map(Value::Boolean),
+    }
+}
+
+/**
+ *  How export_csv and import_csv read and write CSV. CsvOptions::new gives comma separated fields,
+ *  with double quotes around the ones that need them.
+*/
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    delimiter: u8,
+    quote: u8,
+    always_quote: bool,
+    dry_run: bool,
+}
+
+impl CsvOptions {
+    pub fn new() -> CsvOptions {
+        CsvOptions {
+            delimiter: b',',
+            quote: b'"',
+            always_quote: false,
+            dry_run: false,
+        }
+    }
+
+    //The character between fields
+    pub fn delimiter(mut self, delimiter: u8) -> CsvOptions {
+        self.delimiter = delimiter;
+        self
+    }
+
+    //The character fields with the delimiter or a new line in them are quoted with
+    pub fn quote(mut self, quote: u8) -> CsvOptions {
+        self.quote = quote;
+        self
+    }
+
+    //Quotes every field on export, instead of only the ones that need it
+    pub fn always_quote(mut self, always_quote: bool) -> CsvOptions {
+        self.always_quote = always_quote;
+        self
+    }
+
+    //Checks every row on import, without inserting any
+    pub fn dry_run(mut self, dry_run: bool) -> CsvOptions {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions::new()
+    }
+}
+
+/// Writes every entry in a table as a row of CSV, after a header row with the field names.
+/// Gives back how many entries were written.
+pub fn export_csv<E, T, W>(table: &T, writer: W, options: &CsvOptions) -> Result<u64, String>
+where
+    E: Entry,
+    T: Table<E>,
+    W: Write,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quote_style(if options.always_quote {
+            csv::QuoteStyle::Always
+        } else {
+            csv::QuoteStyle::Necessary
+        })
+        .from_writer(writer);
+    writer
+        .write_record(E::get_field_names().iter().map(|field| field.to_string()))
+        .map_err(|e| e.to_string())?;
+    let mut count = 0;
+    for result in table.iter() {
+        let (_key, entry) = result?;
+        writer
+            .write_record(entry.get_fields().iter().map(|value| value.to_string()))
+            .map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Inserts an entry into a table for each row of CSV. The header row has to name every field, in
+/// any order, and each value is read as the type of its field. Fails if the header is wrong or
+/// the rows cannot be read, the rows with bad entries are in the report instead.
+pub fn import_csv<E, T, R>(
+    table: &mut T,
+    reader: R,
+    options: &CsvOptions,
+) -> Result<ImportReport<T::Key>, String>
+where
+    E: Entry,
+    T: Table<E>,
+    R: Read,
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .from_reader(reader);
+
+    //The column each field is in
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let field_names: Vec<String> = E::get_field_names()
+        .iter()
+        .map(|field| field.to_string())
+        .collect();
+    for header in &headers {
+        if !field_names.iter().any(|name| name == header) {
+            return Err(format!("Unknown column {}", header));
+        }
+    }
+    let mut columns = Vec::new();
+    for name in &field_names {
+        match headers.iter().position(|header| header == name) {
+            Some(column) => columns.push(column),
+            None => return Err(format!("Missing column {}", name)),
+        }
+    }
+
+    let mut report = ImportReport {
+        keys: Vec::new(),
+        errors: Vec::new(),
+    };
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(ref e) if e.is_io_error() => return Err(e.to_string()),
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line() as usize);
+                report.errors.push((line, e.to_string()));
+                continue;
+            }
+        };
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        match record_to_entry::<E>(&record, &columns) {
+            Ok(_) if options.dry_run => {}
+            Ok(entry) => {
+                let key = table.insert(entry);
+                if table.contains(key.clone()) {
+                    report.keys.push(key);
+                } else {
+                    report
+                        .errors
+                        .push((line, "Could not insert the entry".to_string()));
+                }
+            }
+            Err(e) => report.errors.push((line, e)),
+        }
+    }
+    Ok(report)
+}
+
+//Reads each field from its column of a CSV row
+fn record_to_entry<E: Entry>(record: &csv::StringRecord, columns: &[usize]) -> Result<E, String> {
+    let mut values = Vec::new();
+    let fields = E::get_field_names().into_iter().zip(E::get_field_types());
+    for ((field, value_type), column) in fields.zip(columns) {
+        match Value::from_text(&record[*column], value_type) {
+            Some(value) => values.push(value),
+            None => {
+                return Err(format!(
+                    "Incorrect type for {}. Should be {:?}",
+                    field.to_string(),
+                    value_type
+                ))
+            }
+        }
+    }
+    E::from_fields(&values)
+}
+
+//How many entries copy_table inserts at a time
+static COPY_BATCH: usize = 1000;
+
+/// Inserts every entry in one table into another. Gives back how many entries were copied.
+pub fn copy_table<E, S, D>(src: &S, dst: &mut D) -> Result<u64, String>
+where
+    E: Entry,
+    S: Table<E>,
+    D: Table<E>,
+{
+    let mut count = 0;
+    let mut batch = Vec::new();
+    for result in src.iter() {
+        batch.push(result?.1);
+        if batch.len() == COPY_BATCH {
+            count += dst.insert_many(batch.split_off(0))?.len() as u64;
+        }
+    }
+    if !batch.is_empty() {
+        count += dst.insert_many(batch)?.len() as u64;
+    }
+    Ok(count)
+}
+
+/**
+ *  The changes sync_tables made to the destination table, or would make for a dry run
+*/
+#[derive(Debug)]
+pub struct SyncReport<K, E> {
+    //The entries that were only in the source
+    pub inserts: Vec<E>,
+    //The key, the entry that was in the destination, and the different entry from the source
+    pub updates: Vec<(K, E, E)>,
+    //The key and entry of each entry that was only in the destination
+    pub removes: Vec<(K, E)>,
+}
+
+impl<K, E> SyncReport<K, E> {
+    //Whether the tables were already the same
+    pub fn is_empty(&self) -> bool {
+        self.inserts.is_empty() && self.updates.is_empty() && self.removes.is_empty()
+    }
+}
+
+/// Makes a destination table have the same entries as a source table. Entries are the same entry
+/// in both tables when they have the same values for match_fields, which have to be different for
+/// every entry in a table. The destination gets inserts for the entries only in the source,
+/// updates for the ones that have other fields changed, and removes for the ones not in the
+/// source. With dry_run, the destination is left alone and the report has what would change.
+/// The changes are not made in a transaction, so a failure can leave only some of them done.
+pub fn sync_tables<E, S, D>(
+    src: &S,
+    dst: &mut D,
+    match_fields: &[E::FieldNames],
+    dry_run: bool,
+) -> Result<SyncReport<D::Key, E>, String>
+where
+    E: Entry,
+    S: Table<E>,
+    D: Table<E>,
+{
+    if match_fields.is_empty() {
+        return Err("Need at least one field to match on".to_string());
+    }
+
+    //The entries in the destination that have not been matched yet, and where each one is
+    let mut dst_entries = Vec::new();
+    let mut dst_positions = HashMap::new();
+    for result in dst.iter() {
+        let (key, entry) = result?;
+        let match_values = match_key(&entry, match_fields)?;
+        if dst_positions
+            .insert(match_values, dst_entries.len())
+            .is_some()
+        {
+            return Err("More than one entry in the destination matches".to_string());
+        }
+        dst_entries.push(Some((key, entry)));
+    }
+
+    let mut report = SyncReport {
+        inserts: Vec::new(),
+        updates: Vec::new(),
+        removes: Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    for result in src.iter() {
+        let (_key, entry) = result?;
+        let match_values = match_key(&entry, match_fields)?;
+        let matched = dst_positions
+            .get(&match_values)
+            .and_then(|position| dst_entries[*position].take());
+        if !seen.insert(match_values) {
+            return Err("More than one entry in the source matches".to_string());
+        }
+        match matched {
+            Some((key, old)) => {
+                if old.get_fields() != entry.get_fields() {
+                    report.updates.push((key, old, entry));
+                }
+            }
+            None => report.inserts.push(entry),
+        }
+    }
+    report.removes = dst_entries.into_iter().flatten().collect();
+
+    if !dry_run {
+        dst.remove_many(report.removes.iter().map(|(key, _e)| key.clone()).collect())?;
+        dst.update_many(
+            report
+                .updates
+                .iter()
+                .map(|(key, _old, new)| (key.clone(), new.clone()))
+                .collect(),
+        )?;
+        dst.insert_many(report.inserts.clone())?;
+    }
+    Ok(report)
+}
+
+//The values of match_fields for an entry, as something a HashMap can have as a key. Debug writes
+//each kind of Value differently, and every float with its exact value
+fn match_key<E: Entry>(entry: &E, match_fields: &[E::FieldNames]) -> Result<String, String> {
+    let mut values = Vec::new();
+    for field in match_fields {
+        match entry.get_field(*field) {
+            Some(value) => values.push(value),
+            None => return Err("Bad field".to_string()),
+        }
+    }
+    Ok(format!("{:?}", values))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use interface::Table;
+    use interface::Value;
+    use interface::ValueType;
+    use serde_json;
+    use tests::Department;
+    use tests::DepartmentFields;
+    use transfer::copy_table;
+    use transfer::export_csv;
+    use transfer::export_jsonl;
+    use transfer::import_csv;
+    use transfer::import_jsonl;
+    use transfer::json_to_value;
+    use transfer::sync_tables;
+    use transfer::value_to_json;
+    use transfer::CsvOptions;
+    use vec_table::VecTable;
+
+    #[test]
+    fn test_jsonl() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+        department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+        department_table.insert(Department {
+            name: "Physics \"and\" Astronomy".to_string(),
+            abreviation: "PHYS".to_string(),
+        });
+
+        let mut jsonl = Vec::new();
+        assert_eq!(export_jsonl(&department_table, &mut jsonl), Ok(2));
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        assert_eq!(
+            jsonl,
+            "{\"Name\":\"Electrical and Computer Engineering\",\"Abreviation\":\"ECE\"}\n\
+             {\"Name\":\"Physics \\\"and\\\" Astronomy\",\"Abreviation\":\"PHYS\"}\n"
+        );
+
+        let input = jsonl.clone()
+            + "\n"
+            + "{\"Name\":\"Physics\"}\n"
+            + "{\"Name\":\"Physics\",\"Abreviation\":7}\n"
+            + "{\"Name\":\"Physics\",\"Abreviation\":\"PHYS\",\"dean\":\"Smith\"}\n"
+            + "[\"Physics\",\"PHYS\"]\n"
+            + "{\"Name\":\n"
+            + "{\"Abreviation\":\"ME\",\"Name\":\"Mechanical Engineering\"}\n";
+        let mut copy: VecTable<Department> = VecTable::new();
+        let report = import_jsonl(&mut copy, input.as_bytes()).unwrap();
+        assert_eq!(report.keys.len(), 3);
+        assert_eq!(copy.lookup(report.keys[2]).unwrap().abreviation, "ME");
+        let lines: Vec<usize> = report.errors.iter().map(|(line, _e)| *line).collect();
+        assert_eq!(lines, vec![4, 5, 6, 7, 8]);
+        assert_eq!(report.errors[0].1, "Missing field Abreviation");
+        assert_eq!(
+            report.errors[1].1,
+            "Incorrect type for Abreviation. Should be String"
+        );
+        assert_eq!(report.errors[2].1, "Unknown field dean");
+
+        let mut again = Vec::new();
+        export_jsonl(&copy, &mut again).unwrap();
+        assert!(String::from_utf8(again).unwrap().starts_with(&jsonl));
+    }
+
+    #[test]
+    fn test_json_values() {
+        let values = vec![
+            Value::Integer(-12),
+            Value::Float(0.1),
+            Value::String("ECE".to_string()),
+            Value::Boolean(false),
+        ];
+        let json: Vec<String> = values
+            .iter()
+            .map(|value| value_to_json(value).unwrap().to_string())
+            .collect();
+        assert_eq!(json, vec!["-12", "0.1", "\"ECE\"", "false"]);
+        for value in values {
+            let json = value_to_json(&value).unwrap();
+            assert_eq!(json_to_value(&json, value.get_type()), Some(value));
+        }
+        assert!(value_to_json(&Value::Float(f32::NAN)).is_err());
+
+        let json: serde_json::Value = serde_json::from_str("[3000000000, 2, 2.5]").unwrap();
+        assert_eq!(json_to_value(&json[0], ValueType::Integer), None);
+        assert_eq!(
+            json_to_value(&json[1], ValueType::Float),
+            Some(Value::Float(2.0))
+        );
+        assert_eq!(json_to_value(&json[2], ValueType::Integer), None);
+        assert_eq!(json_to_value(&json[2], ValueType::String), None);
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+        department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+        department_table.insert(Department {
+            name: "Physics; \"and\" Astronomy".to_string(),
+            abreviation: "PHYS".to_string(),
+        });
+
+        let mut csv = Vec::new();
+        assert_eq!(
+            export_csv(&department_table, &mut csv, &CsvOptions::new()),
+            Ok(2)
+        );
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "Name,Abreviation\n\
+             Electrical and Computer Engineering,ECE\n\
+             \"Physics; \"\"and\"\" Astronomy\",PHYS\n"
+        );
+
+        let options = CsvOptions::new()
+            .delimiter(b';')
+            .quote(b'\'')
+            .always_quote(true);
+        let mut csv = Vec::new();
+        export_csv(&department_table, &mut csv, &options).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv,
+            "'Name';'Abreviation'\n\
+             'Electrical and Computer Engineering';'ECE'\n\
+             'Physics; \"and\" Astronomy';'PHYS'\n"
+        );
+        let mut copy: VecTable<Department> = VecTable::new();
+        let report = import_csv(&mut copy, csv.as_bytes(), &options).unwrap();
+        assert_eq!(report.keys.len(), 2);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            copy.lookup(report.keys[1]).unwrap().name,
+            "Physics; \"and\" Astronomy"
+        );
+
+        // The columns can be in any order, and bad rows are skipped
+        let input = "Abreviation,Name\n\
+                     ME,Mechanical Engineering\n\
+                     CE\n\
+                     \"BIO\",\"Biology\nand Biomedical\"\n\
+                     CHE,Chemical Engineering,Extra\n";
+        let dry_run = CsvOptions::new().dry_run(true);
+        let report = import_csv(&mut copy, input.as_bytes(), &dry_run).unwrap();
+        assert!(report.keys.is_empty());
+        let lines: Vec<usize> = report.errors.iter().map(|(line, _e)| *line).collect();
+        assert_eq!(lines, vec![3, 6]);
+        assert_eq!(copy.iter().count(), 2);
+
+        let report = import_csv(&mut copy, input.as_bytes(), &CsvOptions::new()).unwrap();
+        assert_eq!(report.keys.len(), 2);
+        assert_eq!(report.errors.len(), 2);
+        let biology = copy.lookup(report.keys[1]).unwrap();
+        assert_eq!(biology.name, "Biology\nand Biomedical");
+        assert_eq!(biology.abreviation, "BIO");
+
+        assert_eq!(
+            import_csv(&mut copy, "Name\nPhysics\n".as_bytes(), &CsvOptions::new()).err(),
+            Some("Missing column Abreviation".to_string())
+        );
+        assert_eq!(
+            import_csv(
+                &mut copy,
+                "Name,Abreviation,Dean\n".as_bytes(),
+                &CsvOptions::new()
+            )
+            .err(),
+            Some("Unknown column Dean".to_string())
+        );
+    }
+
+    fn department(name: &str, abreviation: &str) -> Department {
+        Department {
+            name: name.to_string(),
+            abreviation: abreviation.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_copy_table() {
+        let mut src: VecTable<Department> = VecTable::new();
+        src.insert_many(
+            (0..2500)
+                .map(|i| department(&format!("Department {}", i), &format!("D{}", i)))
+                .collect(),
+        )
+        .unwrap();
+        let mut dst: VecTable<Department> = VecTable::new();
+        dst.insert(department("Physics", "PHYS"));
+
+        assert_eq!(copy_table(&src, &mut dst), Ok(2500));
+        let abreviations: Vec<String> = dst
+            .iter()
+            .map(|result| result.unwrap().1.abreviation)
+            .collect();
+        assert_eq!(abreviations.len(), 2501);
+        assert_eq!(abreviations[0], "PHYS");
+        assert_eq!(abreviations[2500], "D2499");
+    }
+
+    #[test]
+    fn test_sync_tables() {
+        let mut src: VecTable<Department> = VecTable::new();
+        src.insert_many(vec![
+            department("Electrical and Computer Engineering", "ECE"),
+            department("Physics and Astronomy", "PHYS"),
+            department("Chemistry", "CHEM"),
+        ])
+        .unwrap();
+        let mut dst: VecTable<Department> = VecTable::new();
+        let dst_keys = dst
+            .insert_many(vec![
+                department("Mechanical Engineering", "ME"),
+                department("Physics", "PHYS"),
+                department("Electrical and Computer Engineering", "ECE"),
+                department("Civil Engineering", "CE"),
+            ])
+            .unwrap();
+        let match_fields = [DepartmentFields::Abreviation];
+
+        let report = sync_tables(&src, &mut dst, &match_fields, true).unwrap();
+        let inserts: Vec<&str> = report
+            .inserts
+            .iter()
+            .map(|e| e.abreviation.as_str())
+            .collect();
+        assert_eq!(inserts, vec!["CHEM"]);
+        assert_eq!(report.updates.len(), 1);
+        assert_eq!(report.updates[0].0, dst_keys[1]);
+        assert_eq!(report.updates[0].1.name, "Physics");
+        assert_eq!(report.updates[0].2.name, "Physics and Astronomy");
+        let removes: Vec<_> = report.removes.iter().map(|(key, _e)| *key).collect();
+        assert_eq!(removes, vec![dst_keys[0], dst_keys[3]]);
+        // A dry run does not change anything
+        assert_eq!(dst.iter().count(), 4);
+        assert_eq!(dst.lookup(dst_keys[1]).unwrap().name, "Physics");
+
+        let report = sync_tables(&src, &mut dst, &match_fields, false).unwrap();
+        assert!(!report.is_empty());
+        let mut names: Vec<String> = dst.iter().map(|result| result.unwrap().1.name).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "Chemistry",
+                "Electrical and Computer Engineering",
+                "Physics and Astronomy"
+            ]
+        );
+        assert!(sync_tables(&src, &mut dst, &match_fields, false)
+            .unwrap()
+            .is_empty());
+
+        src.insert(department("Chemical Engineering", "CHEM"));
+        assert_eq!(
+            sync_tables(&src, &mut dst, &match_fields, false).err(),
+            Some("More than one entry in the source matches".to_string())
+        );
+        assert!(sync_tables(&src, &mut dst, &[], true).is_err());
+    }
+}
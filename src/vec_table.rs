@@ -1,11 +1,39 @@
 use std::cmp::Ordering;
-
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::mem;
+use std::ops::Bound;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+
+use file_format;
+use file_format::Reader;
+use interface::check_field_types;
+use interface::sort_page;
 use interface::Entry;
+use interface::Filter;
 use interface::Key;
 use interface::QueryType;
+use interface::Reference;
+use interface::Scan;
+use interface::SharedTable;
+use interface::SortDirection;
 use interface::Table;
+use interface::Transactional;
+use interface::Upserted;
 use interface::Value;
-use interface::SortDirection;
+use interface::Versioned;
+use interface::VERSION_CONFLICT;
+use sql_builder::MAX_LIMIT;
 
 /**
  *  A key for a VecTable
@@ -20,17 +48,46 @@ pub struct VecTableKey {
 
 impl<E: Entry> Key<E> for VecTableKey {}
 
+impl VecTableKey {
+    /**
+     *  The number in the key, for keeping in a field that refers to the entry
+     */
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
 /**
  *  A table implemented as a vector. Inserting will add to the end of the vector, and keys are the
  *  index. Removing will probably do nothing, so the vec will keep expanding but never shrink.
- *  Intended for testing and example purposes only.
+ *  Intended for testing, examples and small single-user tools, which can keep the table in a file
+ *  with save_to and load_from.
 */
 #[derive(Default)]
 pub struct VecTable<E: Entry> {
     vector: Vec<(usize, E)>,
     next_key: usize,
+    // Where the entry for each key is in vector
+    positions: HashMap<usize, usize>,
+    // The indexes from add_index, with the place in get_field_names of the field each one is on
+    indexes: Vec<(usize, Index)>,
+    // How many times each entry has been changed. Entries that were never changed are left out
+    versions: HashMap<usize, u64>,
+    // A copy of the table from when the open transaction started
+    snapshot: Option<Snapshot<E>>,
+    // The file to save the table to after every change, from autosave_to
+    autosave: Option<PathBuf>,
+    // Why the last autosave failed, if it did
+    save_error: Option<String>,
 }
 
+// The start of every file save_to writes, and the version of the format after it
+static FILE_MAGIC: &[u8] = b"DBLIBVEC";
+static FILE_VERSION: u32 = 1;
+
+// The vector, next_key and versions of a VecTable
+type Snapshot<E> = (Vec<(usize, E)>, usize, HashMap<usize, u64>);
+
 impl<E: Entry> VecTable<E> {
     /**
      *  Gives a new `VecTable` with an empty vector
@@ -39,6 +96,498 @@ impl<E: Entry> VecTable<E> {
         VecTable {
             vector: Vec::new(),
             next_key: 0,
+            positions: HashMap::new(),
+            indexes: Vec::new(),
+            versions: HashMap::new(),
+            snapshot: None,
+            autosave: None,
+            save_error: None,
+        }
+    }
+
+    /**
+     *  Writes every entry in the table to a file, with its key and version, so load_from can give
+     *  back the same table. Indexes are not saved. The file is written next to the old one and
+     *  then put in its place, so a failed save leaves the old file alone.
+     */
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        if self.snapshot.is_some() {
+            return Err("Cannot save in the middle of a transaction".to_string());
+        }
+        let path = path.as_ref();
+
+        let mut bytes = FILE_MAGIC.to_vec();
+        file_format::write_u32(&mut bytes, FILE_VERSION);
+        file_format::write_field_names::<E>(&mut bytes);
+        file_format::write_u64(&mut bytes, self.next_key as u64);
+        file_format::write_u64(&mut bytes, self.vector.len() as u64);
+        for (id, e) in &self.vector {
+            file_format::write_u64(&mut bytes, *id as u64);
+            file_format::write_u64(&mut bytes, *self.versions.get(id).unwrap_or(&0));
+            for value in e.get_fields() {
+                file_format::write_value(&mut bytes, &value);
+            }
+        }
+
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, &bytes).map_err(|e| e.to_string())?;
+        fs::rename(&temp_path, path).map_err(|e| e.to_string())
+    }
+
+    /**
+     *  Reads a table from a file written by save_to. The entries keep their keys and versions, and
+     *  new entries get keys after the ones the table had when it was saved.
+     */
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<VecTable<E>, String> {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        let mut reader = Reader::new(&bytes);
+        if reader.read_bytes(FILE_MAGIC.len()).ok() != Some(FILE_MAGIC) {
+            return Err("Not a VecTable file".to_string());
+        }
+        let version = reader.read_u32()?;
+        if version != FILE_VERSION {
+            return Err(format!("Unknown VecTable file version {}", version));
+        }
+        reader.read_field_names::<E>()?;
+        let field_count = E::get_field_names().len();
+
+        let mut table = VecTable::new();
+        table.next_key = reader.read_u64()? as usize;
+        let entry_count = reader.read_u64()?;
+        for _ in 0..entry_count {
+            let id = reader.read_u64()? as usize;
+            let version = reader.read_u64()?;
+            let mut fields = Vec::new();
+            for _ in 0..field_count {
+                fields.push(reader.read_value()?);
+            }
+            if id >= table.next_key || table.positions.contains_key(&id) {
+                return Err(format!("Bad key {} in file", id));
+            }
+            table.push(id, E::from_fields(&fields)?);
+            if version != 0 {
+                table.versions.insert(id, version);
+            }
+        }
+        if !reader.is_done() {
+            return Err("Extra data at the end of the file".to_string());
+        }
+        Ok(table)
+    }
+
+    /**
+     *  Saves the table to a file now, and again after every change to it until stop_autosave.
+     *  Changes in a transaction are saved when it is committed. A failed save after a change does
+     *  not undo the change, so take_save_error tells why instead.
+     */
+    pub fn autosave_to<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        self.save_to(&path)?;
+        self.autosave = Some(path.as_ref().to_path_buf());
+        self.save_error = None;
+        Ok(())
+    }
+
+    /**
+     *  Stops saving the table after every change
+     */
+    pub fn stop_autosave(&mut self) {
+        self.autosave = None;
+    }
+
+    /**
+     *  Gives back why the last autosave failed, if it did, and forgets it
+     */
+    pub fn take_save_error(&mut self) -> Option<String> {
+        self.save_error.take()
+    }
+
+    /**
+     *  Gives every entry that matches the filter along with the entry of other that it refers to,
+     *  or None if other has no entry with that key. Other already keeps where each key is in a
+     *  hash map, so this goes through the matching entries of this table once and never through
+     *  other.
+     */
+    pub fn join<F: Entry>(
+        &self,
+        other: &VecTable<F>,
+        reference: &Reference<E, F>,
+        filter: Filter<E>,
+    ) -> Result<Vec<(VecTableKey, E, Option<F>)>, String> {
+        let mut rows = Vec::new();
+        for position in self.matching_positions(&filter) {
+            let (id, e) = &self.vector[position];
+            let found = match reference.id(e) {
+                Some(other_id) if other_id >= 0 => other
+                    .positions
+                    .get(&(other_id as usize))
+                    .map(|position| other.vector[*position].1.clone()),
+                _ => None,
+            };
+            rows.push((VecTableKey { id: *id }, e.clone(), found));
+        }
+        Ok(rows)
+    }
+
+    // Called after every change, to autosave it
+    fn changed(&mut self) {
+        if self.snapshot.is_some() {
+            return;
+        }
+        if let Some(ref path) = self.autosave {
+            if let Err(e) = self.save_to(path) {
+                self.save_error = Some(e);
+            }
+        }
+    }
+
+    /**
+     *  Keeps an index on a field, which search and query use to find entries by that field without
+     *  going through every entry. A field that already has an index gets the new one instead.
+     */
+    pub fn add_index(&mut self, field: E::FieldNames, kind: IndexKind) -> Result<(), String> {
+        let field_index = match E::get_field_names().iter().position(|f| *f == field) {
+            Some(field_index) => field_index,
+            None => return Err("Bad field".to_string()),
+        };
+        self.remove_index(field);
+        let mut index = match kind {
+            IndexKind::Hash => Index::Hash(HashMap::new()),
+            IndexKind::BTree => Index::BTree(BTreeMap::new()),
+        };
+        for (id, e) in &self.vector {
+            index.add(e.get_fields().swap_remove(field_index), *id);
+        }
+        self.indexes.push((field_index, index));
+        Ok(())
+    }
+
+    /**
+     *  Stops keeping the index on a field. Returns false if the field did not have one.
+     */
+    pub fn remove_index(&mut self, field: E::FieldNames) -> bool {
+        let field_index = E::get_field_names().iter().position(|f| *f == field);
+        let old_len = self.indexes.len();
+        self.indexes.retain(|(f, _index)| Some(*f) != field_index);
+        self.indexes.len() != old_len
+    }
+
+    // The index on a field, if it has one
+    fn index(&self, field: E::FieldNames) -> Option<&Index> {
+        let field_index = E::get_field_names().iter().position(|f| *f == field)?;
+        self.indexes
+            .iter()
+            .find(|(f, _index)| *f == field_index)
+            .map(|(_f, index)| index)
+    }
+
+    // Adds an entry to the end of vector and to every index
+    fn push(&mut self, id: usize, entry: E) {
+        self.positions.insert(id, self.vector.len());
+        if !self.indexes.is_empty() {
+            let fields = entry.get_fields();
+            for (field_index, index) in &mut self.indexes {
+                index.add(fields[*field_index].clone(), id);
+            }
+        }
+        self.vector.push((id, entry));
+    }
+
+    // Takes an entry out of every index
+    fn unindex(&mut self, id: usize, entry: &E) {
+        if !self.indexes.is_empty() {
+            let fields = entry.get_fields();
+            for (field_index, index) in &mut self.indexes {
+                index.remove(&fields[*field_index], id);
+            }
+        }
+    }
+
+    // Puts a new entry in place of the one at a position, and counts it as a new version
+    fn set_entry(&mut self, position: usize, entry: E) {
+        let id = self.vector[position].0;
+        let old = mem::replace(&mut self.vector[position].1, entry);
+        self.unindex(id, &old);
+        if !self.indexes.is_empty() {
+            let fields = self.vector[position].1.get_fields();
+            for (field_index, index) in &mut self.indexes {
+                index.add(fields[*field_index].clone(), id);
+            }
+        }
+        *self.versions.entry(id).or_insert(0) += 1;
+    }
+
+    // Builds positions and every index again, after entries were taken out of vector
+    fn reindex(&mut self) {
+        self.positions = self
+            .vector
+            .iter()
+            .enumerate()
+            .map(|(position, (id, _e))| (*id, position))
+            .collect();
+        for (field_index, index) in &mut self.indexes {
+            index.clear();
+            for (id, e) in &self.vector {
+                index.add(e.get_fields().swap_remove(*field_index), *id);
+            }
+        }
+    }
+
+    // The keys of the entries with a field equal to a value, in the order they were inserted
+    fn search_ids(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> Result<Vec<usize>, String> {
+        if let Some(index) = self.index(field_name) {
+            return Ok(index
+                .get(&field_value)
+                .map_or(Vec::new(), |ids| ids.iter().cloned().collect()));
+        }
+
+        let mut ids = Vec::new();
+        for (id, e) in &self.vector {
+            match e.get_field(field_name) {
+                Some(value) => {
+                    if value == field_value {
+                        ids.push(*id)
+                    }
+                }
+                None => return Err("Error converting vec in vectable".to_string()),
+            }
+        }
+        Ok(ids)
+    }
+
+    // The keys of the entries that could match a filter, in the order they were inserted, found
+    // with the indexes. An equals looks its value up in either kind of index, and a comparison
+    // takes a range of a BTree index. None if no index helps, and every entry has to be checked.
+    // Only part of an And is looked up, so the entries still have to be checked with the filter
+    fn filter_ids(&self, filter: &Filter<E>) -> Option<BTreeSet<usize>> {
+        let (field, value) = match filter {
+            Filter::Equals(field, value) => {
+                let ids = self.index(*field)?.get(value);
+                return Some(ids.cloned().unwrap_or_default());
+            }
+            Filter::And(filters) => {
+                return filters
+                    .iter()
+                    .filter_map(|filter| self.filter_ids(filter))
+                    .min_by_key(|ids| ids.len());
+            }
+            Filter::Or(filters) => {
+                let mut ids = BTreeSet::new();
+                for filter in filters {
+                    ids.extend(self.filter_ids(filter)?);
+                }
+                return Some(ids);
+            }
+            Filter::LessThan(field, value)
+            | Filter::LessOrEqual(field, value)
+            | Filter::GreaterThan(field, value)
+            | Filter::GreaterOrEqual(field, value) => (field, value),
+            _ => return None,
+        };
+        let map = match self.index(*field)? {
+            Index::BTree(map) => map,
+            Index::Hash(_map) => return None,
+        };
+        let bound = IndexValue::new(value.clone());
+        let rank = bound.rank();
+        let range = match filter {
+            Filter::LessThan(..) => (Bound::Unbounded, Bound::Excluded(bound)),
+            Filter::LessOrEqual(..) => (Bound::Unbounded, Bound::Included(bound)),
+            Filter::GreaterThan(..) => (Bound::Excluded(bound), Bound::Unbounded),
+            _ => (Bound::Included(bound), Bound::Unbounded),
+        };
+        // Values of other types never match, and they sort before or after every value of this one
+        Some(
+            map.range(range)
+                .filter(|(found, _ids)| found.rank() == rank)
+                .flat_map(|(_found, ids)| ids.iter().cloned())
+                .collect(),
+        )
+    }
+
+    // Where the entries that match a filter are in vector, in order
+    fn matching_positions(&self, filter: &Filter<E>) -> Vec<usize> {
+        let positions: Box<dyn Iterator<Item = usize>> = match self.filter_ids(filter) {
+            Some(ids) => Box::new(ids.into_iter().map(|id| self.positions[&id])),
+            None => Box::new(0..self.vector.len()),
+        };
+        positions
+            .filter(|position| filter.matches(&self.vector[*position].1))
+            .collect()
+    }
+
+    // Sorts the entries with the given keys, or every entry for None, and gives back one page
+    fn page(
+        &self,
+        ids: Option<Vec<usize>>,
+        limit: u16,
+        sort_field: E::FieldNames,
+        sort_dir: SortDirection,
+        page: u16,
+    ) -> Result<Vec<(VecTableKey, E)>, String> {
+        // A BTree index already has the keys in order. Keys with the same value stay in the order
+        // they were inserted either way, the same as with sort_page
+        if let Some(Index::BTree(map)) = self.index(sort_field) {
+            let limit = limit.min(MAX_LIMIT);
+            let start = u64::from(limit) * u64::from(page.saturating_sub(1));
+            let wanted: Option<HashSet<usize>> = ids.map(|ids| ids.into_iter().collect());
+            let in_order: Box<dyn Iterator<Item = &BTreeSet<usize>>> = match sort_dir {
+                SortDirection::Asc => Box::new(map.values()),
+                SortDirection::Desc => Box::new(map.values().rev()),
+            };
+            return Ok(in_order
+                .flat_map(|ids| ids.iter())
+                .filter(|id| wanted.as_ref().is_none_or(|wanted| wanted.contains(id)))
+                .skip(start as usize)
+                .take(usize::from(limit))
+                .map(|id| {
+                    (
+                        VecTableKey { id: *id },
+                        self.vector[self.positions[id]].1.clone(),
+                    )
+                })
+                .collect());
+        }
+
+        let entries: Vec<(VecTableKey, &E)> = match ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| (VecTableKey { id: *id }, &self.vector[self.positions[id]].1))
+                .collect(),
+            None => self
+                .vector
+                .iter()
+                .map(|(id, e)| (VecTableKey { id: *id }, e))
+                .collect(),
+        };
+        sort_page(entries, limit, sort_field, sort_dir, page)
+    }
+}
+
+/**
+ *  The kinds of index VecTable::add_index can keep. Both find the entries with a value of the
+ *  field, and a BTree index also keeps them sorted by the field, so query can page through them
+ *  in order without sorting.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    Hash,
+    BTree,
+}
+
+// The keys of the entries with each value of a field. The keys are in the order they were made,
+// which is the order of the entries in vector
+enum Index {
+    Hash(HashMap<IndexValue, BTreeSet<usize>>),
+    BTree(BTreeMap<IndexValue, BTreeSet<usize>>),
+}
+
+impl Index {
+    fn add(&mut self, value: Value, id: usize) {
+        let ids = match self {
+            Index::Hash(map) => map.entry(IndexValue::new(value)).or_default(),
+            Index::BTree(map) => map.entry(IndexValue::new(value)).or_default(),
+        };
+        ids.insert(id);
+    }
+
+    fn remove(&mut self, value: &Value, id: usize) {
+        let value = IndexValue::new(value.clone());
+        let now_empty = match self {
+            Index::Hash(map) => map
+                .get_mut(&value)
+                .map(|ids| ids.remove(&id) && ids.is_empty()),
+            Index::BTree(map) => map
+                .get_mut(&value)
+                .map(|ids| ids.remove(&id) && ids.is_empty()),
+        };
+        if now_empty == Some(true) {
+            match self {
+                Index::Hash(map) => map.remove(&value),
+                Index::BTree(map) => map.remove(&value),
+            };
+        }
+    }
+
+    fn get(&self, value: &Value) -> Option<&BTreeSet<usize>> {
+        let value = IndexValue::new(value.clone());
+        match self {
+            Index::Hash(map) => map.get(&value),
+            Index::BTree(map) => map.get(&value),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            Index::Hash(map) => map.clear(),
+            Index::BTree(map) => map.clear(),
+        }
+    }
+}
+
+// A Value that can be the key of a HashMap or BTreeMap. It sorts the same way as Value, with
+// floats compared by total_cmp so every float has a place, even NaN
+#[derive(Debug, Clone)]
+struct IndexValue(Value);
+
+impl IndexValue {
+    fn new(value: Value) -> IndexValue {
+        match value {
+            // 0.0 and -0.0 are equal as Values, so they need to be the same key
+            Value::Float(0.0) => IndexValue(Value::Float(0.0)),
+            value => IndexValue(value),
+        }
+    }
+
+    // The order of the kinds of Value, the same as the order they are declared in
+    fn rank(&self) -> u8 {
+        match self.0 {
+            Value::Integer(_) => 0,
+            Value::Float(_) => 1,
+            Value::String(_) => 2,
+            Value::Boolean(_) => 3,
+        }
+    }
+}
+
+impl Ord for IndexValue {
+    fn cmp(&self, other: &IndexValue) -> Ordering {
+        match (&self.0, &other.0) {
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexValue {
+    fn partial_cmp(&self, other: &IndexValue) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexValue {
+    fn eq(&self, other: &IndexValue) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexValue {}
+
+impl Hash for IndexValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self.0 {
+            Value::Integer(i) => i.hash(state),
+            Value::Float(f) => f.to_bits().hash(state),
+            Value::String(ref s) => s.hash(state),
+            Value::Boolean(b) => b.hash(state),
         }
     }
 }
@@ -47,31 +596,28 @@ impl<E: Entry> Table<E> for VecTable<E> {
     type Key = VecTableKey;
 
     fn insert(&mut self, entry: E) -> Self::Key {
-        self.vector.push((self.next_key, entry));
         let key = VecTableKey { id: self.next_key };
+        self.push(key.id, entry);
         self.next_key += 1;
+        self.changed();
         key
     }
 
     fn lookup(&self, key: Self::Key) -> Option<E> {
-        for (k, e) in &self.vector {
-            if key.id == *k {
-                return Some(e.clone());
-            }
-        }
-
-        None
+        self.positions
+            .get(&key.id)
+            .map(|position| self.vector[*position].1.clone())
     }
 
     fn update(&mut self, key: Self::Key, entry: E) -> Result<(), String> {
-        for (k, e) in self.vector.iter_mut() {
-            if key.id == *k {
-                *e = entry;
-                return Ok(());
+        match self.positions.get(&key.id) {
+            Some(position) => {
+                self.set_entry(*position, entry);
+                self.changed();
+                Ok(())
             }
+            None => Err("Key not found".to_string()),
         }
-
-        Err("Key not found".to_string())
     }
 
     fn query(
@@ -91,44 +637,56 @@ impl<E: Entry> Table<E> for VecTable<E> {
                 }
             }
 
-            QueryType::GetAll(limit, sort_field, sort_dir, page) => {
-                // sort, skip lim*(pg-1), take lim
-
-                let mut i = 0;
-                let field_index = loop {
-                    match E::get_field_names().get(i) {
-                        Some(field_name) => if *field_name == sort_field { break i } else { i += 1 },
-                        None => return Err("Bad field".to_string()),
-                    }
-                };
-
-                let mut slice = self.vector.clone();
-                slice.sort_by(|(_, a), (_, b)| {
-                    match sort_dir {
-                        SortDirection::Asc => {
-                            a.get_fields()[field_index]
-                                .partial_cmp(&b.get_fields()[field_index])
-                                .unwrap_or(Ordering::Equal)
-                        }
+            QueryType::Search(field_name, field_value, limit, sort_field, sort_dir, page) => {
+                let ids = self.search_ids(field_name, field_value)?;
+                self.page(Some(ids), limit, sort_field, sort_dir, page)
+            }
 
-                        SortDirection::Desc => {
-                            a.get_fields()[field_index]
-                                .partial_cmp(&b.get_fields()[field_index])
-                                .unwrap_or(Ordering::Equal)
-                                .reverse()
+            QueryType::MultiSearch(
+                field_names,
+                field_values,
+                limit,
+                sort_field,
+                sort_dir,
+                page,
+            ) => {
+                if field_names.len() != field_values.len() {
+                    return Err("Field and Value vectors do not pair".to_string());
+                }
+                let mut ids: Option<Vec<usize>> = None;
+                for (field_name, field_value) in field_names.into_iter().zip(field_values) {
+                    let found = self.search_ids(field_name, field_value)?;
+                    ids = Some(match ids {
+                        Some(ids) => {
+                            let found: HashSet<usize> = found.into_iter().collect();
+                            ids.into_iter().filter(|id| found.contains(id)).collect()
                         }
-                    }
-                });
+                        None => found,
+                    });
+                }
+                self.page(ids, limit, sort_field, sort_dir, page)
+            }
 
-                Ok(slice
-                   .into_iter()
-                   .skip((limit * (page-1)) as usize)
-                   .take(limit as usize)
-                   .map(|(id, entry)| (VecTableKey { id }, entry))
-                   .collect())
+            QueryType::GetAll(limit, sort_field, sort_dir, page) => {
+                self.page(None, limit, sort_field, sort_dir, page)
             }
 
-            _ => unimplemented!(),
+            QueryType::PartialSearch(
+                field_name,
+                field_value,
+                limit,
+                sort_field,
+                sort_dir,
+                page,
+            ) => {
+                let filter = Filter::contains(field_name, &field_value.to_string());
+                let ids = self
+                    .matching_positions(&filter)
+                    .into_iter()
+                    .map(|position| self.vector[position].0)
+                    .collect();
+                self.page(Some(ids), limit, sort_field, sort_dir, page)
+            }
         }
     }
 
@@ -137,38 +695,28 @@ impl<E: Entry> Table<E> for VecTable<E> {
         field_name: E::FieldNames,
         field_value: Value,
     ) -> Result<Vec<(Self::Key, E)>, String> {
-        let mut good_value: bool = true;
-        let temp = self.vector.iter().fold(Vec::new(), |mut v, (id, e)| {
-            if let Some(value) = e.get_field(field_name) {
-                if value == field_value {
-                    v.push((VecTableKey { id: *id }, e.clone()));
-                }
-            } else {
-                good_value = false;
-            }
-            v
-        });
-        if good_value == true {
-            Ok(temp)
-        } else {
-            Err("Error converting vec in vectable".to_string())
-        }
+        Ok(self
+            .search_ids(field_name, field_value)?
+            .into_iter()
+            .map(|id| {
+                (
+                    VecTableKey { id },
+                    self.vector[self.positions[&id]].1.clone(),
+                )
+            })
+            .collect())
     }
 
     fn remove(&mut self, key: Self::Key) -> Result<(), String> {
-        let index = self.vector.iter().fold(
-            None,
-            |i, (id, _e)| {
-                if *id == key.id {
-                    Some(*id)
-                } else {
-                    i
-                }
-            },
-        );
-
-        if let Some(index) = index {
-            self.vector.remove(index);
+        if let Some(position) = self.positions.remove(&key.id) {
+            let (_id, e) = self.vector.remove(position);
+            self.unindex(key.id, &e);
+            // Everything after it moved down one
+            for (id, _e) in &self.vector[position..] {
+                *self.positions.get_mut(id).unwrap() -= 1;
+            }
+            self.versions.remove(&key.id);
+            self.changed();
             Ok(())
         } else {
             Err("Key not in table".to_string())
@@ -176,72 +724,413 @@ impl<E: Entry> Table<E> for VecTable<E> {
     }
 
     fn contains(&self, key: Self::Key) -> bool {
-        self.vector.iter().any(|(k, _e)| *k == key.id)
+        self.positions.contains_key(&key.id)
     }
-}
-
-#[cfg(test)]
-mod tests {
-
-    use interface::QueryType;
-    use interface::SortDirection;
-    use interface::Table;
-    use interface::Value;
-    use tests::Department;
-    use tests::DepartmentFields;
-    use vec_table::VecTable;
-    use vec_table::VecTableKey;
 
-    #[test]
-    fn test_vectable_key_partial_eq() {
-        let key_1 = VecTableKey { id: 1 };
-        let key_2 = VecTableKey { id: 1 };
+    fn insert_many(&mut self, entries: Vec<E>) -> Result<Vec<Self::Key>, String> {
+        let first_key = self.next_key;
+        self.next_key += entries.len();
+        for (id, entry) in (first_key..).zip(entries) {
+            self.push(id, entry);
+        }
+        self.changed();
+        Ok((first_key..self.next_key)
+            .map(|id| VecTableKey { id })
+            .collect())
+    }
 
-        assert!(key_1 == key_2);
+    // Checks every key before changing any, so a missing key leaves the table alone
+    fn update_many(&mut self, updates: Vec<(Self::Key, E)>) -> Result<(), String> {
+        let mut update_map: HashMap<usize, E> = HashMap::new();
+        for (key, entry) in updates {
+            if !self.positions.contains_key(&key.id) {
+                return Err("Key not found".to_string());
+            }
+            update_map.insert(key.id, entry);
+        }
+        for (id, entry) in update_map {
+            let position = self.positions[&id];
+            self.set_entry(position, entry);
+        }
+        self.changed();
+        Ok(())
     }
 
-    #[test]
-    fn test_vectable() {
-        let mut department_table: VecTable<Department> = VecTable::new();
+    // Uses the indexes to find the entries, so aggregate and distinct do as well
+    fn scan<'a>(&'a self, filter: Filter<E>) -> Scan<'a, Self::Key, E> {
+        Box::new(
+            self.matching_positions(&filter)
+                .into_iter()
+                .map(move |position| {
+                    let (id, e) = &self.vector[position];
+                    Ok((VecTableKey { id: *id }, e.clone()))
+                }),
+        )
+    }
 
-        let ece_department = Department {
-            name: "Electrical and Computer Engineering".to_string(),
-            abreviation: "ECE".to_string(),
-        };
+    fn update_where(
+        &mut self,
+        filter: Filter<E>,
+        assignments: Vec<(E::FieldNames, Value)>,
+    ) -> Result<u64, String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let field_names = E::get_field_names();
+        let mut indexed_assignments = Vec::new();
+        for (field, value) in assignments {
+            match field_names.iter().position(|f| *f == field) {
+                Some(index) => indexed_assignments.push((index, value)),
+                None => return Err("Bad field".to_string()),
+            }
+        }
 
-        let ece_key = department_table.insert(ece_department);
+        // Makes every new entry before changing any, so a bad value leaves the table alone
+        let mut changed = Vec::new();
+        for i in self.matching_positions(&filter) {
+            let mut fields = self.vector[i].1.get_fields();
+            for (index, value) in &indexed_assignments {
+                fields[*index] = value.clone();
+            }
+            changed.push((i, E::from_fields(&fields)?));
+        }
 
-        let ece_department_after = department_table.lookup(ece_key).unwrap();
+        let count = changed.len() as u64;
+        for (i, entry) in changed {
+            self.set_entry(i, entry);
+        }
+        self.changed();
+        Ok(count)
+    }
 
-        assert_eq!(
-            ece_department_after.name,
-            "Electrical and Computer Engineering".to_string()
-        );
-        assert_eq!(ece_department_after.abreviation, "ECE".to_string());
+    fn patch(
+        &mut self,
+        key: Self::Key,
+        assignments: Vec<(E::FieldNames, Value)>,
+    ) -> Result<(), String> {
+        if assignments.is_empty() {
+            return Err("No fields to update".to_string());
+        }
+        check_field_types::<E>(&assignments)?;
+        let field_names = E::get_field_names();
+        let position = match self.positions.get(&key.id) {
+            Some(position) => *position,
+            None => return Err("Key not found".to_string()),
+        };
+        let mut fields = self.vector[position].1.get_fields();
+        for (field, value) in assignments {
+            if let Some(index) = field_names.iter().position(|f| *f == field) {
+                fields[index] = value;
+            }
+        }
+        let entry = E::from_fields(&fields)?;
+        self.set_entry(position, entry);
+        self.changed();
+        Ok(())
     }
 
-    #[test]
-    fn test_search() {
-        let mut department_table: VecTable<Department> = VecTable::new();
+    fn remove_where(&mut self, filter: Filter<E>) -> Result<u64, String> {
+        let remove_set: HashSet<usize> = self
+            .matching_positions(&filter)
+            .into_iter()
+            .map(|position| self.vector[position].0)
+            .collect();
+        if remove_set.is_empty() {
+            return Ok(0);
+        }
+        self.vector.retain(|(id, _e)| !remove_set.contains(id));
+        self.versions
+            .retain(|id, _version| !remove_set.contains(id));
+        self.reindex();
+        self.changed();
+        Ok(remove_set.len() as u64)
+    }
 
-        let ece_key = department_table.insert(Department {
-            name: "Electrical and Computer Engineering".to_string(),
-            abreviation: "ECE".to_string(),
-        });
+    // Finds the match and changes it in the same call, so nothing else can change the table in between
+    fn upsert(
+        &mut self,
+        match_fields: &[E::FieldNames],
+        entry: E,
+    ) -> Result<(Self::Key, Upserted), String> {
+        if match_fields.is_empty() {
+            return Err("Need at least one field to match on".to_string());
+        }
+        let mut match_values = Vec::new();
+        for field in match_fields {
+            match entry.get_field(*field) {
+                Some(value) => match_values.push((*field, value)),
+                None => return Err("Bad field".to_string()),
+            }
+        }
 
-        let _me_key = department_table.insert(Department {
-            name: "Mechanical Engineering".to_string(),
-            abreviation: "ME".to_string(),
-        });
+        let matches: Vec<usize> = self
+            .vector
+            .iter()
+            .enumerate()
+            .filter(|(_i, (_id, e))| {
+                match_values
+                    .iter()
+                    .all(|(field, value)| e.get_field(*field).as_ref() == Some(value))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        match matches.len() {
+            0 => Ok((self.insert(entry), Upserted::Inserted)),
+            1 => {
+                let id = self.vector[matches[0]].0;
+                self.set_entry(matches[0], entry);
+                self.changed();
+                Ok((VecTableKey { id }, Upserted::Updated))
+            }
+            _ => Err("More than one entry matches".to_string()),
+        }
+    }
 
-        let (found_key, _found_entry) = department_table
-            .search(
-                DepartmentFields::Abreviation,
-                Value::String("ECE".to_string()),
-            )
-            .unwrap()
-            .pop()
-            .unwrap();
+    fn remove_many(&mut self, keys: Vec<Self::Key>) -> Result<(), String> {
+        let remove_set: HashSet<usize> = keys.into_iter().map(|key| key.id).collect();
+        let old_len = self.vector.len();
+        self.vector.retain(|(id, _e)| !remove_set.contains(id));
+        self.versions
+            .retain(|id, _version| !remove_set.contains(id));
+        self.reindex();
+        self.changed();
+
+        if old_len - self.vector.len() == remove_set.len() {
+            Ok(())
+        } else {
+            Err("Key not in table".to_string())
+        }
+    }
+}
+
+/**
+ *  Transactions copy the whole table when they start, and put the copy back on a rollback
+*/
+impl<E: Entry> Transactional<E> for VecTable<E> {
+    fn begin(&mut self) -> Result<(), String> {
+        if self.snapshot.is_some() {
+            return Err("A transaction is already open".to_string());
+        }
+        self.snapshot = Some((self.vector.clone(), self.next_key, self.versions.clone()));
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), String> {
+        match self.snapshot.take() {
+            Some(_) => {
+                self.changed();
+                Ok(())
+            }
+            None => Err("No transaction is open".to_string()),
+        }
+    }
+
+    fn rollback(&mut self) -> Result<(), String> {
+        match self.snapshot.take() {
+            Some((vector, next_key, versions)) => {
+                self.vector = vector;
+                self.next_key = next_key;
+                self.versions = versions;
+                self.reindex();
+                Ok(())
+            }
+            None => Err("No transaction is open".to_string()),
+        }
+    }
+}
+
+/**
+ *  Every entry starts at version 0, and any change to it adds one
+*/
+impl<E: Entry> Versioned<E> for VecTable<E> {
+    fn lookup_version(&self, key: Self::Key) -> Option<(E, u64)> {
+        self.lookup(key)
+            .map(|entry| (entry, *self.versions.get(&key.id).unwrap_or(&0)))
+    }
+
+    fn update_if_version(&mut self, key: Self::Key, version: u64, entry: E) -> Result<u64, String> {
+        if !self.contains(key) {
+            return Err("Key not found".to_string());
+        }
+        let current = *self.versions.get(&key.id).unwrap_or(&0);
+        if current != version {
+            return Err(VERSION_CONFLICT.to_string());
+        }
+        self.update(key, entry)?;
+        Ok(current + 1)
+    }
+}
+
+/**
+ *  A VecTable behind a RwLock, so it can be shared between threads as a SharedTable. Lookups and
+ *  searches run at the same time as each other, and changes wait for everything else.
+*/
+#[derive(Default)]
+pub struct SharedVecTable<E: Entry> {
+    table: RwLock<VecTable<E>>,
+}
+
+impl<E: Entry> SharedVecTable<E> {
+    /**
+     *  Gives a new `SharedVecTable` with an empty vector
+     */
+    pub fn new() -> SharedVecTable<E> {
+        SharedVecTable {
+            table: RwLock::new(VecTable::new()),
+        }
+    }
+
+    /**
+     *  Shares a VecTable that already has entries or indexes
+     */
+    pub fn from_table(table: VecTable<E>) -> SharedVecTable<E> {
+        SharedVecTable {
+            table: RwLock::new(table),
+        }
+    }
+
+    /**
+     *  Gives back the VecTable once it does not need to be shared anymore
+     */
+    pub fn into_inner(self) -> VecTable<E> {
+        self.table
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // A thread that panicked while holding the lock does not stop the others from using the table,
+    // the same as if it had panicked while using a VecTable of its own
+    fn read(&self) -> RwLockReadGuard<'_, VecTable<E>> {
+        self.table.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, VecTable<E>> {
+        self.table.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<E: Entry + Send + Sync> SharedTable<E> for SharedVecTable<E> {
+    type Key = VecTableKey;
+
+    fn insert(&self, entry: E) -> Self::Key {
+        self.write().insert(entry)
+    }
+
+    fn lookup(&self, key: Self::Key) -> Option<E> {
+        self.read().lookup(key)
+    }
+
+    fn search(
+        &self,
+        field_name: E::FieldNames,
+        field_value: Value,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        self.read().search(field_name, field_value)
+    }
+
+    fn update(&self, key: Self::Key, entry: E) -> Result<(), String> {
+        self.write().update(key, entry)
+    }
+
+    fn remove(&self, key: Self::Key) -> Result<(), String> {
+        self.write().remove(key)
+    }
+
+    fn contains(&self, key: Self::Key) -> bool {
+        self.read().contains(key)
+    }
+
+    fn query(
+        &self,
+        q: QueryType<E>,
+        key: Option<Self::Key>,
+    ) -> Result<Vec<(Self::Key, E)>, String> {
+        self.read().query(q, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use interface::Aggregate;
+    use interface::Filter;
+    use interface::QueryType;
+    use interface::Reference;
+    use interface::SharedTable;
+    use interface::SortDirection;
+    use interface::Table;
+    use interface::Transactional;
+    use interface::Upserted;
+    use interface::Value;
+    use interface::Versioned;
+    use interface::VERSION_CONFLICT;
+    use std::collections::HashSet;
+    use std::env;
+    use std::fs;
+    use std::sync::Arc;
+    use std::thread;
+    use tests::Course;
+    use tests::CourseFields;
+    use tests::Department;
+    use tests::DepartmentFields;
+    use tests::User;
+    use vec_table::IndexKind;
+    use vec_table::SharedVecTable;
+    use vec_table::VecTable;
+    use vec_table::VecTableKey;
+
+    #[test]
+    fn test_vectable_key_partial_eq() {
+        let key_1 = VecTableKey { id: 1 };
+        let key_2 = VecTableKey { id: 1 };
+
+        assert!(key_1 == key_2);
+    }
+
+    #[test]
+    fn test_vectable() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let ece_department = Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        };
+
+        let ece_key = department_table.insert(ece_department);
+
+        let ece_department_after = department_table.lookup(ece_key).unwrap();
+
+        assert_eq!(
+            ece_department_after.name,
+            "Electrical and Computer Engineering".to_string()
+        );
+        assert_eq!(ece_department_after.abreviation, "ECE".to_string());
+    }
+
+    #[test]
+    fn test_search() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let ece_key = department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+
+        let _me_key = department_table.insert(Department {
+            name: "Mechanical Engineering".to_string(),
+            abreviation: "ME".to_string(),
+        });
+
+        let (found_key, _found_entry) = department_table
+            .search(
+                DepartmentFields::Abreviation,
+                Value::String("ECE".to_string()),
+            )
+            .unwrap()
+            .pop()
+            .unwrap();
 
         assert_eq!(ece_key, found_key);
     }
@@ -376,7 +1265,10 @@ mod tests {
         assert_eq!(found_ece_key, ece_key);
         assert_eq!(found_bme_key, bme_key);
 
-        assert_eq!(found_ece_entry.name, "Electrical and Computer Engineering".to_string());
+        assert_eq!(
+            found_ece_entry.name,
+            "Electrical and Computer Engineering".to_string()
+        );
         assert_eq!(found_ece_entry.abreviation, "ECE".to_string());
 
         assert_eq!(found_bme_entry.name, "Biomedical Engineering".to_string());
@@ -434,7 +1326,10 @@ mod tests {
         let (found_ece_key, found_ece_entry) = found.remove(0);
 
         assert_eq!(found_ece_key, ece_key);
-        assert_eq!(found_ece_entry.name, "Electrical and Computer Engineering".to_string());
+        assert_eq!(
+            found_ece_entry.name,
+            "Electrical and Computer Engineering".to_string()
+        );
         assert_eq!(found_ece_entry.abreviation, "ECE".to_string());
 
         assert_eq!(found_me_key, me_key);
@@ -456,6 +1351,1197 @@ mod tests {
 
         assert_eq!(found_bme_entry.name, "Biomedical Engineering".to_string());
         assert_eq!(found_bme_entry.abreviation, "BME".to_string());
+    }
+
+    #[test]
+    fn test_vectable_transaction_commit() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let ece_key = department_table
+            .transaction(|table| {
+                let ece_key = table.insert(Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                });
+                table.insert(Department {
+                    name: "Mechanical Engineering".to_string(),
+                    abreviation: "ME".to_string(),
+                });
+                Ok(ece_key)
+            })
+            .unwrap();
+
+        assert!(department_table.contains(ece_key));
+        assert_eq!(
+            department_table
+                .query(
+                    QueryType::GetAll(10, DepartmentFields::Name, SortDirection::Asc, 1),
+                    None,
+                )
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(department_table.commit().is_err());
+    }
+
+    #[test]
+    fn test_vectable_transaction_rollback() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let ece_key = department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+
+        let result: Result<(), String> = department_table.transaction(|table| {
+            table.insert(Department {
+                name: "Mechanical Engineering".to_string(),
+                abreviation: "ME".to_string(),
+            });
+            table.remove(ece_key)?;
+            table.update(
+                ece_key,
+                Department {
+                    name: "Electrical and Computer Engineering Department".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+            )
+        });
+
+        assert_eq!(result, Err("Key not found".to_string()));
+        assert_eq!(
+            department_table.lookup(ece_key).unwrap().name,
+            "Electrical and Computer Engineering".to_string()
+        );
+        assert!(department_table
+            .search(
+                DepartmentFields::Abreviation,
+                Value::String("ME".to_string())
+            )
+            .unwrap()
+            .is_empty());
+
+        //begin and rollback can also be called without a closure
+        department_table.begin().unwrap();
+        assert!(department_table.begin().is_err());
+        let me_key = department_table.insert(Department {
+            name: "Mechanical Engineering".to_string(),
+            abreviation: "ME".to_string(),
+        });
+        department_table.rollback().unwrap();
+        assert!(!department_table.contains(me_key));
+        assert!(department_table.rollback().is_err());
+    }
+
+    #[test]
+    fn test_vectable_remove_after_remove() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let ece_key = department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+
+        let me_key = department_table.insert(Department {
+            name: "Mechanical Engineering".to_string(),
+            abreviation: "ME".to_string(),
+        });
+
+        department_table.remove(ece_key).unwrap();
+        department_table.remove(me_key).unwrap();
+
+        assert!(!department_table.contains(me_key));
+        assert!(department_table.remove(me_key).is_err());
+    }
+
+    #[test]
+    fn test_vectable_many() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let keys = department_table
+            .insert_many(vec![
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+                Department {
+                    name: "Mechanical Engineering".to_string(),
+                    abreviation: "ME".to_string(),
+                },
+                Department {
+                    name: "Biomedical Engineering".to_string(),
+                    abreviation: "BME".to_string(),
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(department_table.lookup(keys[1]).unwrap().abreviation, "ME");
+        let next_key = department_table.insert(Department {
+            name: "Chemical Engineering".to_string(),
+            abreviation: "ChE".to_string(),
+        });
+        assert!(!keys.contains(&next_key));
+
+        department_table
+            .update_many(vec![
+                (
+                    keys[0],
+                    Department {
+                        name: "Electrical and Computer Engineering Department".to_string(),
+                        abreviation: "ECE".to_string(),
+                    },
+                ),
+                (
+                    keys[2],
+                    Department {
+                        name: "Biomedical Engineering Department".to_string(),
+                        abreviation: "BME".to_string(),
+                    },
+                ),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            department_table.lookup(keys[0]).unwrap().name,
+            "Electrical and Computer Engineering Department".to_string()
+        );
+        assert_eq!(
+            department_table.lookup(keys[2]).unwrap().name,
+            "Biomedical Engineering Department".to_string()
+        );
+
+        department_table
+            .remove_many(vec![keys[0], keys[2]])
+            .unwrap();
+
+        assert!(!department_table.contains(keys[0]));
+        assert!(department_table.contains(keys[1]));
+        assert!(!department_table.contains(keys[2]));
+        assert!(department_table.contains(next_key));
+
+        assert!(department_table
+            .remove_many(vec![keys[0], keys[1]])
+            .is_err());
+        assert!(!department_table.contains(keys[1]));
+        assert!(department_table
+            .update_many(vec![(
+                keys[0],
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+            )])
+            .is_err());
+    }
+
+    #[test]
+    fn test_vectable_upsert() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let (ece_key, ece_upserted) = department_table
+            .upsert(
+                &[DepartmentFields::Abreviation],
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(ece_upserted, Upserted::Inserted);
+
+        let (found_key, found_upserted) = department_table
+            .upsert(
+                &[DepartmentFields::Abreviation],
+                Department {
+                    name: "Electrical and Computer Engineering Department".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(found_upserted, Upserted::Updated);
+        assert_eq!(found_key, ece_key);
+        assert_eq!(
+            department_table.lookup(ece_key).unwrap().name,
+            "Electrical and Computer Engineering Department".to_string()
+        );
+
+        //Both fields have to match for it to count
+        let (me_key, me_upserted) = department_table
+            .upsert(
+                &[DepartmentFields::Abreviation, DepartmentFields::Name],
+                Department {
+                    name: "Mechanical Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(me_upserted, Upserted::Inserted);
+        assert_ne!(me_key, ece_key);
+
+        assert!(department_table
+            .upsert(
+                &[DepartmentFields::Abreviation],
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+            )
+            .is_err());
+        assert!(department_table
+            .upsert(
+                &[],
+                Department {
+                    name: "Biomedical Engineering".to_string(),
+                    abreviation: "BME".to_string(),
+                },
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_vectable_update_remove_where() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let keys = department_table
+            .insert_many(vec![
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+                Department {
+                    name: "Mechanical Engineering".to_string(),
+                    abreviation: "ME".to_string(),
+                },
+                Department {
+                    name: "Physics".to_string(),
+                    abreviation: "PHYS".to_string(),
+                },
+            ])
+            .unwrap();
+
+        let engineering = Filter::Like(DepartmentFields::Name, "%engineering".to_string());
+        let updated = department_table
+            .update_where(
+                engineering.clone(),
+                vec![(
+                    DepartmentFields::Abreviation,
+                    Value::String("ENG".to_string()),
+                )],
+            )
+            .unwrap();
+        assert_eq!(updated, 2);
+        assert_eq!(department_table.lookup(keys[0]).unwrap().abreviation, "ENG");
+        assert_eq!(department_table.lookup(keys[1]).unwrap().abreviation, "ENG");
+        assert_eq!(
+            department_table.lookup(keys[2]).unwrap().abreviation,
+            "PHYS"
+        );
+
+        //A value of the wrong type changes nothing
+        assert!(department_table
+            .update_where(
+                Filter::All,
+                vec![(DepartmentFields::Abreviation, Value::Integer(4))],
+            )
+            .is_err());
+        assert_eq!(
+            department_table.lookup(keys[2]).unwrap().abreviation,
+            "PHYS"
+        );
+        //even when it matches nothing
+        assert_eq!(
+            department_table.update_where(
+                Filter::Not(Box::new(Filter::All)),
+                vec![(DepartmentFields::Abreviation, Value::Integer(4))],
+            ),
+            Err("Incorrect type for Abreviation. Should be String".to_string())
+        );
+        assert!(department_table
+            .update_where(Filter::All, Vec::new())
+            .is_err());
+
+        let removed = department_table
+            .remove_where(Filter::Not(Box::new(engineering)))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert!(!department_table.contains(keys[2]));
+        assert!(department_table.contains(keys[0]));
+        assert_eq!(department_table.remove_where(Filter::All).unwrap(), 2);
+        assert!(!department_table.contains(keys[0]));
+    }
+
+    #[test]
+    fn test_vectable_patch() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let key = department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+
+        department_table
+            .patch(
+                key,
+                vec![(
+                    DepartmentFields::Abreviation,
+                    Value::String("ECEN".to_string()),
+                )],
+            )
+            .unwrap();
+        let patched = department_table.lookup(key).unwrap();
+        assert_eq!(patched.abreviation, "ECEN");
+        assert_eq!(patched.name, "Electrical and Computer Engineering");
+
+        //A value of the wrong type changes nothing
+        assert!(department_table
+            .patch(key, vec![(DepartmentFields::Name, Value::Integer(4))])
+            .is_err());
+        assert_eq!(
+            department_table.lookup(key).unwrap().name,
+            "Electrical and Computer Engineering"
+        );
+        assert!(department_table.patch(key, Vec::new()).is_err());
+
+        department_table.remove(key).unwrap();
+        assert!(department_table
+            .patch(
+                key,
+                vec![(
+                    DepartmentFields::Abreviation,
+                    Value::String("ECE".to_string()),
+                )],
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_vectable_versions() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+
+        let ece = Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        };
+        let key = department_table.insert(ece.clone());
+        let (_entry, version) = department_table.lookup_version(key).unwrap();
+        assert_eq!(version, 0);
+
+        //Two people look up the same entry, the first one to update wins
+        let first = Department {
+            abreviation: "ECEN".to_string(),
+            ..ece.clone()
+        };
+        let second = Department {
+            name: "Electrical Engineering".to_string(),
+            ..ece
+        };
+        assert_eq!(
+            department_table.update_if_version(key, version, first),
+            Ok(1)
+        );
+        assert_eq!(
+            department_table.update_if_version(key, version, second.clone()),
+            Err(VERSION_CONFLICT.to_string())
+        );
+        assert_eq!(department_table.lookup(key).unwrap().abreviation, "ECEN");
+
+        //Any other change moves the version up too
+        department_table
+            .patch(
+                key,
+                vec![(
+                    DepartmentFields::Abreviation,
+                    Value::String("EE".to_string()),
+                )],
+            )
+            .unwrap();
+        let (_entry, version) = department_table.lookup_version(key).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(department_table.update_if_version(key, 2, second), Ok(3));
+
+        department_table.remove(key).unwrap();
+        assert!(department_table.lookup_version(key).is_none());
+    }
+
+    #[test]
+    fn test_vectable_scan() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+        assert!(department_table.iter().next().is_none());
+
+        let keys = department_table
+            .insert_many(vec![
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+                Department {
+                    name: "Physics".to_string(),
+                    abreviation: "PHYS".to_string(),
+                },
+                Department {
+                    name: "Mechanical Engineering".to_string(),
+                    abreviation: "ME".to_string(),
+                },
+            ])
+            .unwrap();
+        department_table.remove(keys[1]).unwrap();
+
+        let all: Vec<VecTableKey> = department_table
+            .iter()
+            .map(|result| result.unwrap().0)
+            .collect();
+        assert_eq!(all, vec![keys[0], keys[2]]);
+
+        let mut engineering = department_table.scan(Filter::Like(
+            DepartmentFields::Name,
+            "%engineering".to_string(),
+        ));
+        assert_eq!(engineering.next().unwrap().unwrap().1.abreviation, "ECE");
+        assert_eq!(engineering.next().unwrap().unwrap().1.abreviation, "ME");
+        assert!(engineering.next().is_none());
+    }
+
+    #[test]
+    fn test_vectable_indexes() {
+        let departments = vec![
+            ("Electrical and Computer Engineering", "ECE"),
+            ("Physics", "PHYS"),
+            ("Mechanical Engineering", "ME"),
+            ("Civil Engineering", "CE"),
+            ("Chemical Engineering", "CHE"),
+        ];
+        let mut indexed: VecTable<Department> = VecTable::new();
+        let mut unindexed: VecTable<Department> = VecTable::new();
+        for (name, abreviation) in departments {
+            let department = Department {
+                name: name.to_string(),
+                abreviation: abreviation.to_string(),
+            };
+            indexed.insert(department.clone());
+            unindexed.insert(department);
+        }
+        assert_eq!(
+            indexed.add_index(DepartmentFields::Abreviation, IndexKind::Hash),
+            Ok(())
+        );
+        assert_eq!(
+            indexed.add_index(DepartmentFields::Name, IndexKind::BTree),
+            Ok(())
+        );
+
+        // Both tables have to give back the same thing for every query
+        let same = |indexed: &VecTable<Department>, unindexed: &VecTable<Department>| {
+            for dir in [SortDirection::Asc, SortDirection::Desc] {
+                for page in 1..4 {
+                    let q = || QueryType::GetAll(2, DepartmentFields::Name, dir, page);
+                    let a: Vec<VecTableKey> = indexed
+                        .query(q(), None)
+                        .unwrap()
+                        .into_iter()
+                        .map(|(k, _e)| k)
+                        .collect();
+                    let b: Vec<VecTableKey> = unindexed
+                        .query(q(), None)
+                        .unwrap()
+                        .into_iter()
+                        .map(|(k, _e)| k)
+                        .collect();
+                    assert_eq!(a, b);
+                }
+            }
+            for abreviation in ["ECE", "ME", "EE", "PHYS"] {
+                let value = Value::String(abreviation.to_string());
+                let a: Vec<VecTableKey> = indexed
+                    .search(DepartmentFields::Abreviation, value.clone())
+                    .unwrap()
+                    .into_iter()
+                    .map(|(k, _e)| k)
+                    .collect();
+                let b: Vec<VecTableKey> = unindexed
+                    .search(DepartmentFields::Abreviation, value.clone())
+                    .unwrap()
+                    .into_iter()
+                    .map(|(k, _e)| k)
+                    .collect();
+                assert_eq!(a, b);
+                let q = || {
+                    QueryType::Search(
+                        DepartmentFields::Abreviation,
+                        value.clone(),
+                        10,
+                        DepartmentFields::Name,
+                        SortDirection::Desc,
+                        1,
+                    )
+                };
+                assert_eq!(
+                    indexed.query(q(), None).unwrap().len(),
+                    unindexed.query(q(), None).unwrap().len()
+                );
+            }
+        };
+        same(&indexed, &unindexed);
+
+        // Paging through the BTree works the same as sort_page at the edges
+        let keys = |q: QueryType<Department>| -> Vec<VecTableKey> {
+            indexed
+                .query(q, None)
+                .unwrap()
+                .into_iter()
+                .map(|(k, _e)| k)
+                .collect()
+        };
+        let by_name = |limit, page| {
+            QueryType::GetAll(limit, DepartmentFields::Name, SortDirection::Asc, page)
+        };
+        assert_eq!(keys(by_name(2, 0)), keys(by_name(2, 1)));
+        assert_eq!(keys(by_name(u16::MAX, 1)).len(), 5);
+        assert!(keys(by_name(u16::MAX, u16::MAX)).is_empty());
+
+        let found = indexed
+            .query(
+                QueryType::GetAll(1, DepartmentFields::Name, SortDirection::Asc, 1),
+                None,
+            )
+            .unwrap();
+        assert_eq!(found[0].1.abreviation, "CHE");
+
+        // Changes have to keep the indexes up to date
+        for table in [&mut indexed, &mut unindexed] {
+            table
+                .patch(
+                    VecTableKey { id: 0 },
+                    vec![(
+                        DepartmentFields::Abreviation,
+                        Value::String("EE".to_string()),
+                    )],
+                )
+                .unwrap();
+            table.remove(VecTableKey { id: 1 }).unwrap();
+            table
+                .update_where(
+                    Filter::Equals(
+                        DepartmentFields::Abreviation,
+                        Value::String("CE".to_string()),
+                    ),
+                    vec![(
+                        DepartmentFields::Name,
+                        Value::String("Aardvark Studies".to_string()),
+                    )],
+                )
+                .unwrap();
+            table.begin().unwrap();
+            table
+                .remove_where(Filter::Like(DepartmentFields::Name, "%Mech%".to_string()))
+                .unwrap();
+            table.insert(Department {
+                name: "Biology".to_string(),
+                abreviation: "ME".to_string(),
+            });
+            table.rollback().unwrap();
+            table.insert(Department {
+                name: "Biomedical Engineering".to_string(),
+                abreviation: "ME".to_string(),
+            });
+        }
+        same(&indexed, &unindexed);
+
+        let both = indexed
+            .query(
+                QueryType::MultiSearch(
+                    vec![DepartmentFields::Abreviation, DepartmentFields::Name],
+                    vec![
+                        Value::String("ME".to_string()),
+                        Value::String("Biomedical Engineering".to_string()),
+                    ],
+                    10,
+                    DepartmentFields::Name,
+                    SortDirection::Asc,
+                    1,
+                ),
+                None,
+            )
+            .unwrap();
+        assert_eq!(both.len(), 1);
+        assert_eq!(both[0].0, VecTableKey { id: 5 });
+
+        assert!(indexed.remove_index(DepartmentFields::Name));
+        assert!(!indexed.remove_index(DepartmentFields::Name));
+        same(&indexed, &unindexed);
+
+        let shared = SharedVecTable::from_table(indexed);
+        let found = SharedTable::search(
+            &shared,
+            DepartmentFields::Abreviation,
+            Value::String("EE".to_string()),
+        )
+        .unwrap();
+        assert_eq!(found[0].0, VecTableKey { id: 0 });
+    }
+
+    #[test]
+    fn test_vectable_save_load() {
+        let path = env::temp_dir().join(format!("database_lib_vectable_{}", std::process::id()));
+        let mut department_table: VecTable<Department> = VecTable::new();
+        let keys = department_table
+            .insert_many(vec![
+                Department {
+                    name: "Electrical and Computer Engineering".to_string(),
+                    abreviation: "ECE".to_string(),
+                },
+                Department {
+                    name: "Physics".to_string(),
+                    abreviation: "PHYS".to_string(),
+                },
+                Department {
+                    name: "Mechanical Engineering".to_string(),
+                    abreviation: "ME".to_string(),
+                },
+            ])
+            .unwrap();
+        department_table.remove(keys[2]).unwrap();
+        department_table
+            .patch(
+                keys[1],
+                vec![(
+                    DepartmentFields::Name,
+                    Value::String("Physics and Astronomy".to_string()),
+                )],
+            )
+            .unwrap();
+        department_table.save_to(&path).unwrap();
+
+        let mut loaded: VecTable<Department> = VecTable::load_from(&path).unwrap();
+        assert!(!loaded.contains(keys[2]));
+        assert_eq!(loaded.lookup(keys[0]).unwrap().abreviation, "ECE");
+        let (physics, version) = loaded.lookup_version(keys[1]).unwrap();
+        assert_eq!(physics.name, "Physics and Astronomy");
+        assert_eq!(version, 1);
+        // The key of the removed entry is not given out again
+        let new_key = loaded.insert(Department {
+            name: "Chemistry".to_string(),
+            abreviation: "CHEM".to_string(),
+        });
+        assert_eq!(new_key, VecTableKey { id: 3 });
+
+        // Autosaving keeps the file the same as the table
+        loaded.autosave_to(&path).unwrap();
+        loaded.remove(keys[0]).unwrap();
+        let saved: VecTable<Department> = VecTable::load_from(&path).unwrap();
+        assert!(!saved.contains(keys[0]));
+        assert!(saved.contains(new_key));
+
+        // but not in the middle of a transaction
+        loaded.begin().unwrap();
+        loaded.remove(new_key).unwrap();
+        assert!(loaded.save_to(&path).is_err());
+        let saved: VecTable<Department> = VecTable::load_from(&path).unwrap();
+        assert_eq!(saved.iter().count(), 2);
+        loaded.commit().unwrap();
+        let saved: VecTable<Department> = VecTable::load_from(&path).unwrap();
+        assert_eq!(saved.iter().count(), 1);
+        assert_eq!(loaded.take_save_error(), None);
+
+        loaded.stop_autosave();
+        loaded.remove(keys[1]).unwrap();
+        let saved: VecTable<Department> = VecTable::load_from(&path).unwrap();
+        assert_eq!(saved.iter().count(), 1);
+
+        assert!(VecTable::<User>::load_from(&path).is_err());
+        fs::write(&path, b"DBLIBVEC").unwrap();
+        assert_eq!(
+            VecTable::<Department>::load_from(&path).err(),
+            Some("Unexpected end of file".to_string())
+        );
+        fs::write(&path, b"not a table").unwrap();
+        assert_eq!(
+            VecTable::<Department>::load_from(&path).err(),
+            Some("Not a VecTable file".to_string())
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_shared_vectable_is_send_sync() {
+        assert_send_sync::<SharedVecTable<Department>>();
+    }
+
+    #[test]
+    fn test_shared_vectable_threads() {
+        let department_table: Arc<SharedVecTable<Department>> = Arc::new(SharedVecTable::new());
+
+        //Every thread inserts its own departments and removes every other one
+        let handles: Vec<thread::JoinHandle<Vec<VecTableKey>>> = (0..8)
+            .map(|t| {
+                let department_table = department_table.clone();
+                thread::spawn(move || {
+                    let mut kept = Vec::new();
+                    for i in 0..100 {
+                        let key = department_table.insert(Department {
+                            name: "Department ".to_string() + &i.to_string(),
+                            abreviation: t.to_string(),
+                        });
+                        if i % 2 == 0 {
+                            department_table.remove(key).unwrap();
+                        } else {
+                            kept.push(key);
+                        }
+                    }
+                    kept
+                })
+            })
+            .collect();
+
+        let mut kept = HashSet::new();
+        for handle in handles {
+            for key in handle.join().unwrap() {
+                assert!(kept.insert(key)); //No two threads got the same key
+                assert!(department_table.contains(key));
+            }
+        }
+        assert_eq!(kept.len(), 400);
+        let found = department_table
+            .search(
+                DepartmentFields::Abreviation,
+                Value::String("3".to_string()),
+            )
+            .unwrap();
+        assert_eq!(found.len(), 50);
+
+        let department_table = Arc::try_unwrap(department_table).ok().unwrap();
+        assert_eq!(department_table.into_inner().iter().count(), 400);
+    }
+
+    #[test]
+    fn test_vectable_aggregate() {
+        let mut course_table: VecTable<Course> = VecTable::new();
+        for (name, department, credits, rating) in [
+            ("Circuits", 1, 4, 4.0),
+            ("Signals", 1, 3, 3.0),
+            ("Statics", 2, 3, 2.5),
+            ("Thermo", 2, 3, 3.5),
+            ("Dynamics", 2, 2, 5.0),
+        ] {
+            course_table.insert(Course {
+                name: name.to_string(),
+                department,
+                credits,
+                rating,
+            });
+        }
+
+        //Courses, credits and average rating for each department
+        let aggregates = [
+            Aggregate::Count,
+            Aggregate::Sum(CourseFields::Credits),
+            Aggregate::Avg(CourseFields::Rating),
+        ];
+        let rows = course_table
+            .aggregate(&aggregates, &[CourseFields::Department], Filter::All)
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    vec![Value::Integer(1)],
+                    vec![Value::Integer(2), Value::Integer(7), Value::Float(3.5)]
+                ),
+                (
+                    vec![Value::Integer(2)],
+                    vec![
+                        Value::Integer(3),
+                        Value::Integer(8),
+                        Value::Float(3.6666667)
+                    ]
+                ),
+            ]
+        );
+
+        //One group for the whole table
+        let rows = course_table
+            .aggregate(
+                &[
+                    Aggregate::Min(CourseFields::Name),
+                    Aggregate::Max(CourseFields::Rating),
+                ],
+                &[],
+                Filter::GreaterThan(CourseFields::Credits, Value::Integer(2)),
+            )
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![(
+                vec![],
+                vec![Value::String("Circuits".to_string()), Value::Float(4.0)]
+            )]
+        );
+
+        //Two group fields, and a filter that matches nothing
+        let rows = course_table
+            .aggregate(
+                &[Aggregate::Count],
+                &[CourseFields::Department, CourseFields::Credits],
+                Filter::All,
+            )
+            .unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[3].0, vec![Value::Integer(2), Value::Integer(3)]);
+        assert_eq!(rows[3].1, vec![Value::Integer(2)]);
+        let rows = course_table
+            .aggregate(
+                &[Aggregate::Count],
+                &[],
+                Filter::Equals(CourseFields::Department, Value::Integer(3)),
+            )
+            .unwrap();
+        assert!(rows.is_empty());
+
+        assert_eq!(
+            course_table.aggregate(&[Aggregate::Sum(CourseFields::Name)], &[], Filter::All),
+            Err("Cannot sum Name, it is not a number".to_string())
+        );
+    }
+
+    #[test]
+    fn test_vectable_distinct() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+        for (name, abreviation) in [
+            ("Mechanical Engineering", "ME"),
+            ("Electrical and Computer Engineering", "ECE"),
+            ("Computer Science", "CS"),
+            ("Marine Engineering", "ME"),
+        ] {
+            department_table.insert(Department {
+                name: name.to_string(),
+                abreviation: abreviation.to_string(),
+            });
+        }
+
+        let abreviations = department_table
+            .distinct(DepartmentFields::Abreviation, Filter::All)
+            .unwrap();
+        assert_eq!(
+            abreviations,
+            vec![
+                Value::String("CS".to_string()),
+                Value::String("ECE".to_string()),
+                Value::String("ME".to_string()),
+            ]
+        );
+
+        let counts = department_table
+            .distinct_counts(
+                DepartmentFields::Abreviation,
+                Filter::Like(DepartmentFields::Name, "%engineering".to_string()),
+            )
+            .unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                (Value::String("ECE".to_string()), 1),
+                (Value::String("ME".to_string()), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vectable_filter_indexes() {
+        let courses = [
+            ("Circuits", 1, 4, 4.0),
+            ("Signals", 1, 3, 3.0),
+            ("Statics", 2, 3, 2.5),
+            ("Thermo", 2, 3, 3.5),
+            ("Dynamics", 2, 2, 5.0),
+        ];
+        let make_table = || {
+            let mut course_table: VecTable<Course> = VecTable::new();
+            for (name, department, credits, rating) in courses {
+                course_table.insert(Course {
+                    name: name.to_string(),
+                    department,
+                    credits,
+                    rating,
+                });
+            }
+            course_table
+        };
+        let mut indexed = make_table();
+        let mut unindexed = make_table();
+        indexed
+            .add_index(CourseFields::Department, IndexKind::Hash)
+            .unwrap();
+        indexed
+            .add_index(CourseFields::Credits, IndexKind::BTree)
+            .unwrap();
+
+        // The filters that can use an index only look at the entries the index gives
+        let ids = |filter: Filter<Course>| {
+            indexed
+                .filter_ids(&filter)
+                .map(|ids| ids.into_iter().collect::<Vec<usize>>())
+        };
+        let department = |d| Filter::Equals(CourseFields::Department, Value::Integer(d));
+        let credits = |c| Value::Integer(c);
+        assert_eq!(ids(department(2)), Some(vec![2, 3, 4]));
+        assert_eq!(
+            ids(Filter::GreaterOrEqual(CourseFields::Credits, credits(3))),
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(
+            ids(Filter::GreaterThan(CourseFields::Credits, credits(3))),
+            Some(vec![0])
+        );
+        assert_eq!(
+            ids(Filter::LessThan(CourseFields::Credits, credits(3))),
+            Some(vec![4])
+        );
+        assert_eq!(
+            ids(Filter::LessOrEqual(CourseFields::Credits, credits(2))),
+            Some(vec![4])
+        );
+        assert_eq!(
+            ids(Filter::LessThan(CourseFields::Credits, Value::Float(9.0))),
+            Some(vec![])
+        );
+        assert_eq!(
+            ids(Filter::And(vec![
+                Filter::Like(CourseFields::Name, "%s".to_string()),
+                department(1),
+                Filter::GreaterThan(CourseFields::Credits, credits(3)),
+            ])),
+            Some(vec![0])
+        );
+        assert_eq!(
+            ids(Filter::Or(vec![department(1), department(3)])),
+            Some(vec![0, 1])
+        );
+        // A hash index has no order, and the other filters need every entry
+        assert_eq!(
+            ids(Filter::GreaterThan(
+                CourseFields::Department,
+                Value::Integer(1)
+            )),
+            None
+        );
+        assert_eq!(
+            ids(Filter::Equals(CourseFields::Rating, Value::Float(3.0))),
+            None
+        );
+        assert_eq!(ids(Filter::Or(vec![department(1), Filter::All])), None);
+        assert_eq!(ids(Filter::Not(Box::new(department(1)))), None);
+
+        // Both tables give back the same thing for every filter
+        let filters = || {
+            vec![
+                department(2),
+                Filter::GreaterOrEqual(CourseFields::Credits, credits(3)),
+                Filter::LessThan(CourseFields::Credits, credits(4)),
+                Filter::And(vec![
+                    department(2),
+                    Filter::LessThan(CourseFields::Rating, Value::Float(3.0)),
+                ]),
+                Filter::Or(vec![
+                    department(1),
+                    Filter::LessOrEqual(CourseFields::Credits, credits(2)),
+                ]),
+                Filter::Not(Box::new(department(1))),
+            ]
+        };
+        let keys = |table: &VecTable<Course>, filter| -> Vec<VecTableKey> {
+            table.scan(filter).map(|row| row.unwrap().0).collect()
+        };
+        let aggregates = [Aggregate::Count, Aggregate::Sum(CourseFields::Credits)];
+        for (a, b) in filters().into_iter().zip(filters()) {
+            assert_eq!(keys(&indexed, a.clone()), keys(&unindexed, b.clone()));
+            assert_eq!(
+                indexed.aggregate(&aggregates, &[CourseFields::Department], a),
+                unindexed.aggregate(&aggregates, &[CourseFields::Department], b)
+            );
+        }
+
+        let mut department_table: VecTable<Department> = VecTable::new();
+        for abreviation in ["ECE", "ME"] {
+            department_table.insert(Department {
+                name: abreviation.to_string(),
+                abreviation: abreviation.to_string(),
+            });
+        }
+        let reference: Reference<Course, Department> =
+            Reference::new(CourseFields::Department).unwrap();
+        let joined = |table: &VecTable<Course>| -> Vec<(VecTableKey, Option<String>)> {
+            table
+                .join(&department_table, &reference, department(1))
+                .unwrap()
+                .into_iter()
+                .map(|(key, _course, department)| (key, department.map(|d| d.abreviation)))
+                .collect()
+        };
+        assert_eq!(joined(&indexed), joined(&unindexed));
+        assert_eq!(joined(&indexed).len(), 2);
+
+        // Changes through an index keep it up to date
+        for table in [&mut indexed, &mut unindexed] {
+            let changed = table
+                .update_where(
+                    Filter::LessThan(CourseFields::Credits, credits(4)),
+                    vec![(CourseFields::Credits, credits(4))],
+                )
+                .unwrap();
+            assert_eq!(changed, 4);
+            assert_eq!(table.remove_where(department(2)), Ok(3));
+        }
+        let ids = |filter: Filter<Course>| {
+            indexed
+                .filter_ids(&filter)
+                .map(|ids| ids.into_iter().collect::<Vec<usize>>())
+        };
+        assert_eq!(
+            ids(Filter::LessThan(CourseFields::Credits, credits(4))),
+            Some(vec![])
+        );
+        assert_eq!(
+            ids(Filter::GreaterOrEqual(CourseFields::Credits, credits(4))),
+            Some(vec![0, 1])
+        );
+        for (a, b) in filters().into_iter().zip(filters()) {
+            assert_eq!(keys(&indexed, a), keys(&unindexed, b));
+        }
+    }
+
+    #[test]
+    fn test_vectable_partial_search() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+        for (name, abreviation) in [
+            ("Electrical and Computer Engineering", "ECE"),
+            ("Mechanical Engineering", "ME"),
+            ("Physics", "PHYS"),
+            ("50% Engineering", "HALF"),
+        ] {
+            department_table.insert(Department {
+                name: name.to_string(),
+                abreviation: abreviation.to_string(),
+            });
+        }
+
+        let search = |text: &str| -> Vec<String> {
+            department_table
+                .query(
+                    QueryType::PartialSearch(
+                        DepartmentFields::Name,
+                        Value::String(text.to_string()),
+                        10,
+                        DepartmentFields::Abreviation,
+                        SortDirection::Asc,
+                        1,
+                    ),
+                    None,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|(_key, department)| department.abreviation)
+                .collect()
+        };
+        assert_eq!(search("engineering"), vec!["ECE", "HALF", "ME"]);
+        assert_eq!(search("%"), vec!["HALF"]);
+        assert!(search("Chemistry").is_empty());
+    }
+
+    #[test]
+    fn test_vectable_query_fields() {
+        let mut course_table: VecTable<Course> = VecTable::new();
+        let circuits = course_table.insert(Course {
+            name: "Circuits".to_string(),
+            department: 1,
+            credits: 4,
+            rating: 4.0,
+        });
+        course_table.insert(Course {
+            name: "Statics".to_string(),
+            department: 2,
+            credits: 3,
+            rating: 2.5,
+        });
+
+        let rows = course_table
+            .query_fields(
+                QueryType::GetAll(10, CourseFields::Credits, SortDirection::Asc, 1),
+                None,
+                &[CourseFields::Rating, CourseFields::Name],
+            )
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0].1,
+            vec![Value::Float(2.5), Value::String("Statics".to_string())]
+        );
+        assert_eq!(rows[1].0, circuits);
+
+        let rows = course_table
+            .query_fields(QueryType::Lookup, Some(circuits), &[CourseFields::Credits])
+            .unwrap();
+        assert_eq!(rows, vec![(circuits, vec![Value::Integer(4)])]);
+    }
+
+    #[test]
+    fn test_vectable_join() {
+        let mut department_table: VecTable<Department> = VecTable::new();
+        let ece = department_table.insert(Department {
+            name: "Electrical and Computer Engineering".to_string(),
+            abreviation: "ECE".to_string(),
+        });
+        let me = department_table.insert(Department {
+            name: "Mechanical Engineering".to_string(),
+            abreviation: "ME".to_string(),
+        });
+        department_table.remove(me).unwrap();
+
+        let mut course_table: VecTable<Course> = VecTable::new();
+        for (name, department, credits) in [
+            ("Circuits", ece.id() as i32, 4),
+            ("Statics", me.id() as i32, 3),
+            ("Signals", ece.id() as i32, 3),
+        ] {
+            course_table.insert(Course {
+                name: name.to_string(),
+                department,
+                credits,
+                rating: 4.0,
+            });
+        }
+
+        let reference: Reference<Course, Department> =
+            Reference::new(CourseFields::Department).unwrap();
+        let rows = course_table
+            .join(&department_table, &reference, Filter::All)
+            .unwrap();
+        let joined: Vec<(String, Option<String>)> = rows
+            .into_iter()
+            .map(|(_key, course, department)| (course.name, department.map(|d| d.abreviation)))
+            .collect();
+        assert_eq!(
+            joined,
+            vec![
+                ("Circuits".to_string(), Some("ECE".to_string())),
+                ("Statics".to_string(), None),
+                ("Signals".to_string(), Some("ECE".to_string())),
+            ]
+        );
+
+        let rows = course_table
+            .join(
+                &department_table,
+                &reference,
+                Filter::LessThan(CourseFields::Credits, Value::Integer(4)),
+            )
+            .unwrap();
+        assert_eq!(rows.len(), 2);
 
+        let name_reference: Result<Reference<Course, Department>, String> =
+            Reference::new(CourseFields::Name);
+        assert!(name_reference.is_err());
     }
 }